@@ -1,19 +1,28 @@
 extern crate llvm_sys;
 
+use self::llvm_sys::bit_writer::LLVMWriteBitcodeToFile;
 use self::llvm_sys::analysis::{LLVMVerifyModule, LLVMVerifierFailureAction, LLVMVerifyFunction};
-use self::llvm_sys::core::{LLVMContextCreate, LLVMCreateBuilderInContext, LLVMModuleCreateWithNameInContext, LLVMContextDispose, LLVMDisposeBuilder, LLVMVoidTypeInContext, LLVMDumpModule, LLVMInt1TypeInContext, LLVMInt8TypeInContext, LLVMInt16TypeInContext, LLVMInt32Type, LLVMInt32TypeInContext, LLVMInt64TypeInContext, LLVMBuildRet, LLVMBuildRetVoid, LLVMPositionBuilderAtEnd, LLVMBuildCall, LLVMBuildStore, LLVMPointerType, LLVMStructTypeInContext, LLVMAddFunction, LLVMFunctionType, LLVMSetValueName, LLVMGetValueName, LLVMCreatePassManager, LLVMBuildExtractValue, LLVMAppendBasicBlockInContext, LLVMBuildLoad, LLVMBuildGEP, LLVMBuildCondBr, LLVMBuildICmp, LLVMBuildCast, LLVMGetNamedFunction, LLVMBuildAdd, LLVMBuildSub, LLVMBuildMul, LLVMConstInt, LLVMGetFirstParam, LLVMGetNextParam, LLVMCountParams, LLVMDisposePassManager, LLVMCreateFunctionPassManagerForModule, LLVMInitializeFunctionPassManager, LLVMDisposeMessage, LLVMArrayType, LLVMGetReturnType, LLVMTypeOf, LLVMGetElementType, LLVMBuildNeg, LLVMBuildNot, LLVMGetNextBasicBlock, LLVMGetFirstBasicBlock, LLVMGetLastBasicBlock, LLVMGetInsertBlock, LLVMGetBasicBlockParent, LLVMConstReal, LLVMConstArray, LLVMBuildBr, LLVMBuildPhi, LLVMAddIncoming, LLVMBuildAlloca, LLVMBuildMalloc, LLVMBuildArrayMalloc, LLVMBuildArrayAlloca, LLVMGetUndef, LLVMSetDataLayout, LLVMGetBasicBlockTerminator, LLVMInsertIntoBuilder, LLVMIsABasicBlock, LLVMIsAFunction, LLVMIsFunctionVarArg, LLVMDumpType, LLVMPrintValueToString, LLVMPrintTypeToString, LLVMInsertBasicBlock, LLVMInsertBasicBlockInContext, LLVMGetParam, LLVMGetTypeKind, LLVMIsConstant, LLVMVoidType, LLVMSetLinkage, LLVMBuildInsertValue, LLVMIsNull, LLVMBuildIsNull, LLVMIsAConstantArray, LLVMIsAConstantDataArray, LLVMBuildPointerCast, LLVMSetGlobalConstant, LLVMSetInitializer, LLVMAddGlobal, LLVMFloatTypeInContext, LLVMDoubleTypeInContext, LLVMStructGetTypeAtIndex, LLVMMoveBasicBlockAfter, LLVMMoveBasicBlockBefore, LLVMGetTypeByName, LLVMBuildFree, LLVMGetParamTypes, LLVMGetBasicBlocks, LLVMIsUndef, LLVMBuildAnd, LLVMBuildOr, LLVMBuildSDiv, LLVMBuildUDiv, LLVMBuildFAdd, LLVMBuildFDiv, LLVMBuildFMul, LLVMBuildXor, LLVMBuildFCmp, LLVMBuildFNeg, LLVMBuildFSub, LLVMBuildUnreachable, LLVMBuildFence, LLVMGetPointerAddressSpace, LLVMIsAConstantPointerNull, LLVMCountParamTypes, LLVMFP128TypeInContext, LLVMIntTypeInContext};
+use self::llvm_sys::core::{LLVMContextCreate, LLVMCreateBuilderInContext, LLVMModuleCreateWithNameInContext, LLVMContextDispose, LLVMDisposeBuilder, LLVMVoidTypeInContext, LLVMDumpModule, LLVMInt1TypeInContext, LLVMInt8TypeInContext, LLVMInt16TypeInContext, LLVMInt32TypeInContext, LLVMInt64TypeInContext, LLVMBuildRet, LLVMBuildRetVoid, LLVMPositionBuilderAtEnd, LLVMBuildCall, LLVMBuildStore, LLVMPointerType, LLVMStructTypeInContext, LLVMAddFunction, LLVMFunctionType, LLVMSetValueName, LLVMGetValueName, LLVMCreatePassManager, LLVMBuildExtractValue, LLVMAppendBasicBlockInContext, LLVMBuildLoad, LLVMBuildGEP, LLVMBuildCondBr, LLVMBuildICmp, LLVMBuildCast, LLVMGetNamedFunction, LLVMBuildAdd, LLVMBuildSub, LLVMBuildMul, LLVMConstInt, LLVMGetFirstParam, LLVMGetNextParam, LLVMCountParams, LLVMDisposePassManager, LLVMCreateFunctionPassManagerForModule, LLVMInitializeFunctionPassManager, LLVMDisposeMessage, LLVMArrayType, LLVMGetReturnType, LLVMTypeOf, LLVMGetElementType, LLVMBuildNeg, LLVMBuildNot, LLVMGetNextBasicBlock, LLVMGetFirstBasicBlock, LLVMGetLastBasicBlock, LLVMGetInsertBlock, LLVMGetBasicBlockParent, LLVMConstReal, LLVMConstArray, LLVMBuildBr, LLVMBuildPhi, LLVMAddIncoming, LLVMBuildAlloca, LLVMBuildMalloc, LLVMBuildArrayMalloc, LLVMBuildArrayAlloca, LLVMGetUndef, LLVMSetDataLayout, LLVMGetBasicBlockTerminator, LLVMInsertIntoBuilder, LLVMIsABasicBlock, LLVMIsAFunction, LLVMIsFunctionVarArg, LLVMDumpType, LLVMPrintValueToString, LLVMPrintTypeToString, LLVMInsertBasicBlock, LLVMInsertBasicBlockInContext, LLVMGetParam, LLVMGetTypeKind, LLVMIsConstant, LLVMVoidType, LLVMSetLinkage, LLVMBuildInsertValue, LLVMIsNull, LLVMBuildIsNull, LLVMIsAConstantArray, LLVMIsAConstantDataArray, LLVMBuildPointerCast, LLVMSetGlobalConstant, LLVMSetInitializer, LLVMAddGlobal, LLVMFloatTypeInContext, LLVMDoubleTypeInContext, LLVMStructGetTypeAtIndex, LLVMMoveBasicBlockAfter, LLVMMoveBasicBlockBefore, LLVMGetTypeByName, LLVMBuildFree, LLVMGetParamTypes, LLVMGetBasicBlocks, LLVMIsUndef, LLVMBuildAnd, LLVMBuildOr, LLVMBuildSDiv, LLVMBuildUDiv, LLVMBuildFAdd, LLVMBuildFDiv, LLVMBuildFMul, LLVMBuildXor, LLVMBuildFCmp, LLVMBuildFNeg, LLVMBuildFSub, LLVMBuildUnreachable, LLVMBuildFence, LLVMGetPointerAddressSpace, LLVMIsAConstantPointerNull, LLVMCountParamTypes, LLVMFP128TypeInContext, LLVMIntTypeInContext, LLVMBuildURem, LLVMBuildFRem, LLVMConstBitCast, LLVMGetNamedGlobal, LLVMConstNull, LLVMSetTarget, LLVMGetFirstFunction, LLVMGetNextFunction, LLVMRunPassManager, LLVMRunFunctionPassManager, LLVMFinalizeFunctionPassManager, LLVMGetFirstInstruction, LLVMPositionBuilderBefore, LLVMBuildSRem};
 use self::llvm_sys::execution_engine::{LLVMGetExecutionEngineTargetData, LLVMCreateExecutionEngineForModule, LLVMExecutionEngineRef, LLVMRunFunction, LLVMRunFunctionAsMain, LLVMDisposeExecutionEngine, LLVMLinkInInterpreter, LLVMGetFunctionAddress, LLVMLinkInMCJIT, LLVMAddModule};
 use self::llvm_sys::LLVMLinkage::LLVMCommonLinkage;
 use self::llvm_sys::prelude::{LLVMBuilderRef, LLVMContextRef, LLVMModuleRef, LLVMTypeRef, LLVMValueRef, LLVMBasicBlockRef, LLVMPassManagerRef};
-use self::llvm_sys::target::{LLVMOpaqueTargetData, LLVMTargetDataRef, LLVM_InitializeNativeTarget, LLVM_InitializeNativeAsmPrinter, LLVM_InitializeNativeAsmParser, LLVMCopyStringRepOfTargetData, LLVMAddTargetData, LLVM_InitializeNativeDisassembler, LLVMSizeOfTypeInBits};
-use self::llvm_sys::transforms::scalar::{LLVMAddMemCpyOptPass};
-use self::llvm_sys::{LLVMOpcode, LLVMIntPredicate, LLVMTypeKind, LLVMRealPredicate, LLVMAtomicOrdering};
+use self::llvm_sys::target::{LLVMOpaqueTargetData, LLVMTargetDataRef, LLVM_InitializeNativeTarget, LLVM_InitializeNativeAsmPrinter, LLVM_InitializeNativeAsmParser, LLVMCopyStringRepOfTargetData, LLVMDisposeTargetData, LLVM_InitializeNativeDisassembler, LLVM_InitializeAllTargetInfos, LLVM_InitializeAllTargets, LLVM_InitializeAllTargetMCs, LLVM_InitializeAllAsmPrinters, LLVMSizeOfTypeInBits};
+use self::llvm_sys::target_machine::{LLVMTargetMachineRef, LLVMCreateTargetMachine, LLVMDisposeTargetMachine, LLVMGetTargetFromTriple, LLVMGetDefaultTargetTriple, LLVMGetTargetMachineTriple, LLVMTargetMachineEmitToFile, LLVMCodeGenFileType, LLVMCodeGenOptLevel, LLVMRelocMode, LLVMCodeModel};
+use self::llvm_sys::transforms::ipo::{LLVMAddAlwaysInlinerPass, LLVMAddFunctionInliningPass, LLVMAddGlobalDCEPass};
+use self::llvm_sys::transforms::scalar::{LLVMAddMemCpyOptPass, LLVMAddPromoteMemoryToRegisterPass, LLVMAddInstructionCombiningPass, LLVMAddReassociatePass, LLVMAddGVNPass, LLVMAddCFGSimplificationPass, LLVMAddDeadStoreEliminationPass, LLVMAddLICMPass, LLVMAddLoopRotatePass, LLVMAddIndVarSimplifyPass, LLVMAddLoopDeletionPass, LLVMAddLoopUnrollPass};
+use self::llvm_sys::transforms::vectorize::LLVMAddLoopVectorizePass;
+use self::llvm_sys::{LLVMOpcode, LLVMIntPredicate, LLVMTypeKind, LLVMRealPredicate, LLVMAtomicOrdering, LLVMLinkage};
 
 use std::ffi::{CString, CStr};
 use std::fmt;
 use std::mem::{transmute, uninitialized, zeroed};
 use std::os::raw::c_char;
 
+// Newer than these bindings, which only have the target machine's own target data that LLVM has since removed
+extern "C" {
+    fn LLVMCreateTargetDataLayout(target_machine: LLVMTargetMachineRef) -> LLVMTargetDataRef;
+}
+
 // Misc Notes
 // Always pass a c_string.as_ptr() call into the function call directly and never
 // before hand. Seems to make a huge difference (stuff stops working) otherwise
@@ -43,7 +52,8 @@ impl Context {
         assert!(!builder.is_null());
 
         Builder {
-            builder: builder
+            builder: builder,
+            context: self.context,
         }
     }
 
@@ -208,6 +218,7 @@ impl Drop for Context {
 
 pub struct Builder {
     builder: LLVMBuilderRef,
+    context: LLVMContextRef, // The context the builder was created in, for values it makes itself
 }
 
 impl Builder {
@@ -224,7 +235,7 @@ impl Builder {
         Value::new(value)
     }
 
-    pub fn build_call<V: Into<Value> + Copy>(&self, function: &FunctionValue, args: &Vec<V>, name: &str) -> Value {
+    pub fn build_call<V: IntoValue>(&self, function: &FunctionValue, args: &Vec<V>, name: &str) -> Value {
         // LLVM gets upset when void calls are named because they don't return anything
         let name = unsafe {
             match LLVMGetTypeKind(LLVMGetReturnType(LLVMGetElementType(LLVMTypeOf(function.fn_value)))) {
@@ -238,7 +249,7 @@ impl Builder {
         // REVIEW: Had to make Value Copy + Clone to get this to work...
         // Is this safe, given Value is a raw ptr wrapper?
         // I suppose in theory LLVM should never delete the values in the scope of this call, but still
-        let arg_values: Vec<Value> = args.iter().map(|val| (*val).into()).collect();
+        let arg_values: Vec<Value> = args.iter().map(|val| val.into_value(self.context)).collect();
 
         // WARNING: transmute will no longer work correctly if Value gains more fields
         // We're avoiding reallocation by telling rust Vec<Value> is identical to Vec<LLVMValueRef>
@@ -253,14 +264,39 @@ impl Builder {
         Value::new(value)
     }
 
-    pub fn build_gep<V: Into<Value> + Copy>(&self, ptr: &Value, ordered_indexes: &Vec<V>, name: &str) -> Value {
+    // Call through a function pointer, ie one loaded from a vtable
+    pub fn build_indirect_call(&self, fn_ptr: &Value, args: &Vec<Value>, name: &str) -> Value {
+        // LLVM gets upset when void calls are named because they don't return anything
+        let name = unsafe {
+            match LLVMGetTypeKind(LLVMGetReturnType(LLVMGetElementType(LLVMTypeOf(fn_ptr.value)))) {
+                LLVMTypeKind::LLVMVoidTypeKind => "",
+                _ => name,
+            }
+        };
+
+        let c_string = CString::new(name).expect("Conversion to CString failed unexpectedly");
+
+        // WARNING: transmute will no longer work correctly if Value gains more fields
+        // We're avoiding reallocation by telling rust Vec<Value> is identical to Vec<LLVMValueRef>
+        let mut args: Vec<LLVMValueRef> = unsafe {
+            transmute(args.clone())
+        };
+
+        let value = unsafe {
+            LLVMBuildCall(self.builder, fn_ptr.value, args.as_mut_ptr(), args.len() as u32, c_string.as_ptr())
+        };
+
+        Value::new(value)
+    }
+
+    pub fn build_gep<V: IntoValue>(&self, ptr: &Value, ordered_indexes: &Vec<V>, name: &str) -> Value {
         let c_string = CString::new(name).expect("Conversion to CString failed unexpectedly");
 
         // TODO: Assert vec values are all i32 => Result? Might not always be desirable
         // REVIEW: Had to make Value Copy + Clone to get this to work...
         // Is this safe, given Value is a raw ptr wrapper?
         // I suppose in theory LLVM should never delete the values in the scope of this call, but still
-        let index_values: Vec<Value> = ordered_indexes.iter().map(|val| (*val).into()).collect();
+        let index_values: Vec<Value> = ordered_indexes.iter().map(|val| val.into_value(self.context)).collect();
 
         // WARNING: transmute will no longer work correctly if Value gains more fields
         // We're avoiding reallocation by telling rust Vec<Value> is identical to Vec<LLVMValueRef>
@@ -324,21 +360,21 @@ impl Builder {
     }
 
     // TODO: Rename to "build_heap_allocated_aRray" + stack version?
-    pub fn build_array_heap_allocation<V: Into<Value> + Copy>(&self, type_: &Type, size: &V, name: &str) -> Value {
+    pub fn build_array_heap_allocation<V: IntoValue>(&self, type_: &Type, size: &V, name: &str) -> Value {
         let c_string = CString::new(name).expect("Conversion to CString failed unexpectedly");
 
         let value = unsafe {
-            LLVMBuildArrayMalloc(self.builder, type_.type_, (*size).into().value, c_string.as_ptr())
+            LLVMBuildArrayMalloc(self.builder, type_.type_, size.into_value(self.context).value, c_string.as_ptr())
         };
 
         Value::new(value)
     }
 
-    pub fn build_stack_allocated_array<V: Into<Value> + Copy>(&self, type_: &Type, size: &V, name: &str) -> Value {
+    pub fn build_stack_allocated_array<V: IntoValue>(&self, type_: &Type, size: &V, name: &str) -> Value {
         let c_string = CString::new(name).expect("Conversion to CString failed unexpectedly");
 
         let value = unsafe {
-            LLVMBuildArrayAlloca(self.builder, type_.type_, (*size).into().value, c_string.as_ptr())
+            LLVMBuildArrayAlloca(self.builder, type_.type_, size.into_value(self.context).value, c_string.as_ptr())
         };
 
         Value::new(value)
@@ -367,13 +403,28 @@ impl Builder {
         BasicBlock::new(bb)
     }
 
-    pub fn build_int_div(&self, left_value: &Value, right_value: &Value, name: &str) -> Value {
-        let c_string = CString::new(name).expect("Conversion to CString failed unexpectedly");
+    // None until the builder has been positioned somewhere
+    pub fn try_get_insert_block(&self) -> Option<BasicBlock> {
+        let bb = unsafe {
+            LLVMGetInsertBlock(self.builder)
+        };
 
-        // TODO: Support signed
+        if bb.is_null() {
+            return None;
+        }
+
+        Some(BasicBlock::new(bb))
+    }
+
+    pub fn build_int_div(&self, left_value: &Value, right_value: &Value, signed: bool, name: &str) -> Value {
+        let c_string = CString::new(name).expect("Conversion to CString failed unexpectedly");
 
         let value = unsafe {
-            LLVMBuildUDiv(self.builder, left_value.value, right_value.value, c_string.as_ptr())
+            if signed {
+                LLVMBuildSDiv(self.builder, left_value.value, right_value.value, c_string.as_ptr())
+            } else {
+                LLVMBuildUDiv(self.builder, left_value.value, right_value.value, c_string.as_ptr())
+            }
         };
 
         Value::new(value)
@@ -389,6 +440,30 @@ impl Builder {
         Value::new(value)
     }
 
+    pub fn build_int_rem(&self, left_value: &Value, right_value: &Value, signed: bool, name: &str) -> Value {
+        let c_string = CString::new(name).expect("Conversion to CString failed unexpectedly");
+
+        let value = unsafe {
+            if signed {
+                LLVMBuildSRem(self.builder, left_value.value, right_value.value, c_string.as_ptr())
+            } else {
+                LLVMBuildURem(self.builder, left_value.value, right_value.value, c_string.as_ptr())
+            }
+        };
+
+        Value::new(value)
+    }
+
+    pub fn build_float_rem(&self, left_value: &Value, right_value: &Value, name: &str) -> Value {
+        let c_string = CString::new(name).expect("Conversion to CString failed unexpectedly");
+
+        let value = unsafe {
+            LLVMBuildFRem(self.builder, left_value.value, right_value.value, c_string.as_ptr())
+        };
+
+        Value::new(value)
+    }
+
     pub fn build_int_add(&self, left_value: &Value, right_value: &Value, name: &str) -> Value {
         let c_string = CString::new(name).expect("Conversion to CString failed unexpectedly");
 
@@ -586,6 +661,12 @@ impl Builder {
         }
     }
 
+    pub fn position_before(&self, instruction: &Value) {
+        unsafe {
+            LLVMPositionBuilderBefore(self.builder, instruction.value);
+        }
+    }
+
     pub fn build_extract_value<V: AsRef<LLVMValueRef>>(&self, value: &V, index: u32, name: &str) -> Value {
         let c_string = CString::new(name).expect("Conversion to CString failed unexpectedly");
 
@@ -669,6 +750,39 @@ impl Module {
         Some(FunctionValue::new(value))
     }
 
+    pub fn get_global(&self, name: &str) -> Option<Value> {
+        let c_string = CString::new(name).expect("Conversion to CString failed unexpectedly");
+
+        let value = unsafe {
+            LLVMGetNamedGlobal(self.module, c_string.as_ptr())
+        };
+
+        if value.is_null() {
+            return None;
+        }
+
+        Some(Value::new(value))
+    }
+
+    // Every function in the module, including those only declared in it
+    pub fn get_functions(&self) -> Vec<FunctionValue> {
+        let mut functions = Vec::new();
+
+        let mut value = unsafe {
+            LLVMGetFirstFunction(self.module)
+        };
+
+        while !value.is_null() {
+            functions.push(FunctionValue::new(value));
+
+            value = unsafe {
+                LLVMGetNextFunction(value)
+            };
+        }
+
+        functions
+    }
+
     pub fn get_type(&self, name: &str) -> Option<Type> {
         let c_string = CString::new(name).expect("Conversion to CString failed unexpectedly");
 
@@ -773,6 +887,29 @@ impl Module {
         Value::new(value)
     }
 
+    // The target triple the module is compiled for, ie `x86_64-unknown-linux-gnu`
+    pub fn set_triple(&self, triple: &str) {
+        let c_string = CString::new(triple).expect("Conversion to CString failed unexpectedly");
+
+        unsafe {
+            LLVMSetTarget(self.module, c_string.as_ptr())
+        }
+    }
+
+    pub fn write_bitcode_to_file(&self, path: &str) -> Result<(), String> {
+        let c_string = CString::new(path).expect("Conversion to CString failed unexpectedly");
+
+        let code = unsafe {
+            LLVMWriteBitcodeToFile(self.module, c_string.as_ptr())
+        };
+
+        if code != 0 {
+            return Err(format!("Failed to write bitcode to {}", path));
+        }
+
+        Ok(())
+    }
+
     pub fn verify(&self, print: bool) -> bool {
         let err_str: *mut *mut i8 = unsafe { zeroed() };
 
@@ -891,6 +1028,118 @@ impl Drop for ExecutionEngine {
     }
 }
 
+pub struct TargetMachine {
+    target_machine: LLVMTargetMachineRef,
+}
+
+impl TargetMachine {
+    // Every target LLVM was built with is initialized, so any of their triples can be found
+    // The optimization level is 0 to 3, as with `-O`
+    pub fn new(triple: &str, cpu: &str, features: &str, opt_level: u32) -> Result<TargetMachine, String> {
+        unsafe {
+            LLVM_InitializeAllTargetInfos();
+            LLVM_InitializeAllTargets();
+            LLVM_InitializeAllTargetMCs();
+            LLVM_InitializeAllAsmPrinters();
+        }
+
+        let triple = CString::new(triple).expect("Conversion to CString failed unexpectedly");
+        let cpu = CString::new(cpu).expect("Conversion to CString failed unexpectedly");
+        let features = CString::new(features).expect("Conversion to CString failed unexpectedly");
+
+        let mut target = unsafe { zeroed() };
+        let mut err_str = unsafe { zeroed() };
+
+        let code = unsafe {
+            LLVMGetTargetFromTriple(triple.as_ptr(), &mut target, &mut err_str)
+        };
+
+        if code == 1 {
+            return Err(take_message(err_str));
+        }
+
+        let opt_level = match opt_level {
+            0 => LLVMCodeGenOptLevel::LLVMCodeGenLevelNone,
+            1 => LLVMCodeGenOptLevel::LLVMCodeGenLevelLess,
+            2 => LLVMCodeGenOptLevel::LLVMCodeGenLevelDefault,
+            _ => LLVMCodeGenOptLevel::LLVMCodeGenLevelAggressive,
+        };
+
+        // Position independent, as most system linkers now default to PIE executables
+        let target_machine = unsafe {
+            LLVMCreateTargetMachine(target, triple.as_ptr(), cpu.as_ptr(), features.as_ptr(),
+                                    opt_level,
+                                    LLVMRelocMode::LLVMRelocPIC,
+                                    LLVMCodeModel::LLVMCodeModelDefault)
+        };
+
+        if target_machine.is_null() {
+            return Err(format!("Could not create a target machine for {:?}", triple));
+        }
+
+        Ok(TargetMachine {
+            target_machine: target_machine
+        })
+    }
+
+    // The triple of the machine the compiler is running on
+    pub fn get_default_triple() -> String {
+        take_message(unsafe { LLVMGetDefaultTargetTriple() })
+    }
+
+    pub fn get_triple(&self) -> String {
+        take_message(unsafe { LLVMGetTargetMachineTriple(self.target_machine) })
+    }
+
+    // The target's data layout, for modules to be optimized with before they're compiled
+    pub fn get_data_layout(&self) -> DataLayout {
+        unsafe {
+            let target_data = LLVMCreateTargetDataLayout(self.target_machine);
+            let data_layout = LLVMCopyStringRepOfTargetData(target_data);
+
+            LLVMDisposeTargetData(target_data);
+
+            DataLayout::new(data_layout)
+        }
+    }
+
+    // Compiles a module into an object or assembly file
+    pub fn write_to_file(&self, module: &Module, file_type: LLVMCodeGenFileType, path: &str) -> Result<(), String> {
+        let c_string = CString::new(path).expect("Conversion to CString failed unexpectedly");
+        let mut err_str = unsafe { zeroed() };
+
+        // Also sets the module's data layout to the target's
+        let code = unsafe {
+            LLVMTargetMachineEmitToFile(self.target_machine, module.module, c_string.as_ptr() as *mut c_char, file_type, &mut err_str)
+        };
+
+        if code == 1 {
+            return Err(take_message(err_str));
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for TargetMachine {
+    fn drop(&mut self) {
+        unsafe {
+            LLVMDisposeTargetMachine(self.target_machine);
+        }
+    }
+}
+
+// Copies a message LLVM allocated into a String, then frees it
+fn take_message(message: *mut c_char) -> String {
+    unsafe {
+        let rust_str = CStr::from_ptr(message).to_string_lossy().into_owned();
+
+        LLVMDisposeMessage(message);
+
+        rust_str
+    }
+}
+
 pub struct TargetData {
     target_data: LLVMTargetDataRef,
 }
@@ -954,6 +1203,15 @@ impl PassManager {
         }
     }
 
+    // Runs over a whole module at once, unlike a module's function pass manager
+    pub fn create_module_pass_manager() -> PassManager {
+        let pass_manager = unsafe {
+            LLVMCreatePassManager()
+        };
+
+        PassManager::new(pass_manager)
+    }
+
     pub fn initialize(&self) -> bool {
         // return true means some pass modified the module, not an error occurred
         unsafe {
@@ -961,15 +1219,128 @@ impl PassManager {
         }
     }
 
+    // Like the other run methods, true means the passes changed something
+    pub fn run_on_module(&self, module: &Module) -> bool {
+        unsafe {
+            LLVMRunPassManager(self.pass_manager, module.module) == 1
+        }
+    }
+
+    pub fn run_on_function(&self, function: &FunctionValue) -> bool {
+        unsafe {
+            LLVMRunFunctionPassManager(self.pass_manager, function.fn_value) == 1
+        }
+    }
+
+    pub fn finalize(&self) -> bool {
+        unsafe {
+            LLVMFinalizeFunctionPassManager(self.pass_manager) == 1
+        }
+    }
+
     pub fn add_optimize_memcpy_pass(&self) {
         unsafe {
             LLVMAddMemCpyOptPass(self.pass_manager)
         }
     }
 
-    pub fn add_target_data(&self, target_data: TargetData) {
+    // Moves variables from the stack into registers (mem2reg)
+    pub fn add_promote_memory_to_register_pass(&self) {
+        unsafe {
+            LLVMAddPromoteMemoryToRegisterPass(self.pass_manager)
+        }
+    }
+
+    // Folds and simplifies neighbouring instructions (instcombine)
+    pub fn add_instruction_combining_pass(&self) {
+        unsafe {
+            LLVMAddInstructionCombiningPass(self.pass_manager)
+        }
+    }
+
+    // Reorders commutative expressions so constants can be folded together
+    pub fn add_reassociate_pass(&self) {
+        unsafe {
+            LLVMAddReassociatePass(self.pass_manager)
+        }
+    }
+
+    // Removes redundant loads and repeated expressions (global value numbering)
+    pub fn add_gvn_pass(&self) {
+        unsafe {
+            LLVMAddGVNPass(self.pass_manager)
+        }
+    }
+
+    // Merges and removes blocks, ie the empty ones left behind by ifs and loops (simplifycfg)
+    pub fn add_cfg_simplification_pass(&self) {
+        unsafe {
+            LLVMAddCFGSimplificationPass(self.pass_manager)
+        }
+    }
+
+    pub fn add_dead_store_elimination_pass(&self) {
+        unsafe {
+            LLVMAddDeadStoreEliminationPass(self.pass_manager)
+        }
+    }
+
+    // Only inlines functions marked as always inline
+    pub fn add_always_inliner_pass(&self) {
+        unsafe {
+            LLVMAddAlwaysInlinerPass(self.pass_manager)
+        }
+    }
+
+    pub fn add_function_inlining_pass(&self) {
+        unsafe {
+            LLVMAddFunctionInliningPass(self.pass_manager)
+        }
+    }
+
+    // Removes globals and functions nothing refers to, if they're private to the module
+    pub fn add_global_dce_pass(&self) {
+        unsafe {
+            LLVMAddGlobalDCEPass(self.pass_manager)
+        }
+    }
+
+    // Moves a loop's condition to its end, which the other loop passes expect
+    pub fn add_loop_rotate_pass(&self) {
+        unsafe {
+            LLVMAddLoopRotatePass(self.pass_manager)
+        }
+    }
+
+    // Hoists code which doesn't change between iterations out of loops (loop invariant code motion)
+    pub fn add_licm_pass(&self) {
+        unsafe {
+            LLVMAddLICMPass(self.pass_manager)
+        }
+    }
+
+    // Rewrites loop counters into a simpler canonical form
+    pub fn add_ind_var_simplify_pass(&self) {
         unsafe {
-            LLVMAddTargetData(target_data.target_data, self.pass_manager)
+            LLVMAddIndVarSimplifyPass(self.pass_manager)
+        }
+    }
+
+    pub fn add_loop_deletion_pass(&self) {
+        unsafe {
+            LLVMAddLoopDeletionPass(self.pass_manager)
+        }
+    }
+
+    pub fn add_loop_unroll_pass(&self) {
+        unsafe {
+            LLVMAddLoopUnrollPass(self.pass_manager)
+        }
+    }
+
+    pub fn add_loop_vectorize_pass(&self) {
+        unsafe {
+            LLVMAddLoopVectorizePass(self.pass_manager)
         }
     }
 }
@@ -982,6 +1353,8 @@ impl Drop for PassManager {
     }
 }
 
+// LLVM only ever creates one of each type, so types are equal when they're the same pointer
+#[derive(Clone, Copy, PartialEq)]
 pub struct Type {
     type_: LLVMTypeRef,
 }
@@ -1065,6 +1438,15 @@ impl Type {
         Value::new(value)
     }
 
+    // Zero for numbers, or a null pointer
+    pub fn const_null(&self) -> Value {
+        let value = unsafe {
+            LLVMConstNull(self.type_)
+        };
+
+        Value::new(value)
+    }
+
     /// REVIEW: Untested
     pub fn get_undef(&self, type_: &Type) -> Value {
         let value = unsafe {
@@ -1094,6 +1476,15 @@ impl Type {
             LLVMGetTypeKind(self.type_)
         }
     }
+
+    // The type pointed to by a pointer type, or held by an array type
+    pub fn get_element_type(&self) -> Type {
+        let type_ = unsafe {
+            LLVMGetElementType(self.type_)
+        };
+
+        Type::new(type_)
+    }
 }
 
 impl fmt::Debug for Type {
@@ -1125,6 +1516,11 @@ impl FunctionValue {
         }
     }
 
+    // The function's IR, as `dump` would print it
+    pub fn print_to_string(&self) -> String {
+        take_message(unsafe { LLVMPrintValueToString(self.fn_value) })
+    }
+
     pub fn verify(&self, print: bool) {
         let action = if print == true {
             LLVMVerifierFailureAction::LLVMPrintMessageAction
@@ -1141,6 +1537,12 @@ impl FunctionValue {
         }
     }
 
+    pub fn as_value(&self) -> Value {
+        // REVIEW: Same as ParamValue::as_value, would be nice to not need this
+
+        Value::new(self.fn_value)
+    }
+
     pub fn get_first_param(&self) -> Option<ParamValue> {
         let param = unsafe {
             LLVMGetFirstParam(self.fn_value)
@@ -1185,6 +1587,12 @@ impl FunctionValue {
         }
     }
 
+    pub fn is_var_arg(&self) -> bool {
+        unsafe {
+            LLVMIsFunctionVarArg(LLVMGetElementType(LLVMTypeOf(self.fn_value))) == 1
+        }
+    }
+
     /// REVIEW: Untested
     pub fn get_basic_blocks(&self) -> Vec<BasicBlock> {
         let mut blocks = vec![];
@@ -1204,6 +1612,14 @@ impl FunctionValue {
         Type::new(type_)
     }
 
+    pub fn get_type(&self) -> FunctionType {
+        let fn_type = unsafe {
+            LLVMGetElementType(LLVMTypeOf(self.fn_value))
+        };
+
+        FunctionType::new(fn_type)
+    }
+
     pub fn params(&self) -> ParamValueIter {
         ParamValueIter {
             param_iter_value: self.fn_value,
@@ -1407,13 +1823,28 @@ impl Value {
         }
     }
 
+    // Reinterpret a constant as another type of the same size, ie a function as an i8 pointer
+    pub fn const_bit_cast(&self, to: &Type) -> Value {
+        let value = unsafe {
+            LLVMConstBitCast(self.value, to.type_)
+        };
+
+        Value::new(value)
+    }
+
     pub fn set_global_constant(&self, num: i32) { // REVIEW: Need better name for this arg
         unsafe {
             LLVMSetGlobalConstant(self.value, num)
         }
     }
 
-    fn set_name(&mut self, name: &str) {
+    pub fn set_linkage(&self, linkage: LLVMLinkage) {
+        unsafe {
+            LLVMSetLinkage(self.value, linkage)
+        }
+    }
+
+    pub fn set_name(&mut self, name: &str) {
         let c_string = CString::new(name).expect("Conversion to CString failed unexpectedly");
 
         unsafe {
@@ -1520,10 +1951,21 @@ impl Value {
     }
 }
 
-impl From<u64> for Value {
-    fn from(int: u64) -> Value {
+// Something the builder can use as a value, where plain numbers become i32s in the builder's context
+pub trait IntoValue: Copy {
+    fn into_value(self, context: LLVMContextRef) -> Value;
+}
+
+impl IntoValue for Value {
+    fn into_value(self, _context: LLVMContextRef) -> Value {
+        self
+    }
+}
+
+impl IntoValue for u64 {
+    fn into_value(self, context: LLVMContextRef) -> Value {
         unsafe {
-            Type::new(LLVMInt32Type()).const_int(int, false)
+            Type::new(LLVMInt32TypeInContext(context)).const_int(self, false)
         }
     }
 }
@@ -1568,6 +2010,7 @@ impl fmt::Debug for Value {
 // LLVMValueRef can be a function param
 // LLVMValueRef can be a comparison_op
 
+#[derive(Clone, Copy)]
 pub struct BasicBlock {
     basic_block: LLVMBasicBlockRef,
 }
@@ -1608,6 +2051,18 @@ impl BasicBlock {
         Some(BasicBlock::new(bb))
     }
 
+    pub fn get_first_instruction(&self) -> Option<Value> {
+        let value = unsafe {
+            LLVMGetFirstInstruction(self.basic_block)
+        };
+
+        if value.is_null() {
+            return None;
+        }
+
+        Some(Value::new(value))
+    }
+
     pub fn get_terminator(&self) -> Option<Value> {
         let value = unsafe {
             LLVMGetBasicBlockTerminator(self.basic_block)
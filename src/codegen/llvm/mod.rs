@@ -3,22 +3,115 @@ mod std;
 
 extern crate llvm_sys; // TODO: Remove
 
-use codegen::llvm::std::string::{print_function_definition, string_type};
-use self::core::{Builder, Context, Module, Type, Value, PassManager, ExecutionEngine};
+use codegen::llvm::std::string::{print_function_declaration, print_function_definition, string_type};
+use self::core::{BasicBlock, Builder, Context, FunctionValue, Module, Type, Value, PassManager, ExecutionEngine, TargetMachine};
 use self::llvm_sys::LLVMIntPredicate::*; // TODO: Remove
+use self::llvm_sys::LLVMLinkage::LLVMPrivateLinkage; // TODO: Remove
 use self::llvm_sys::LLVMRealPredicate::*; // TODO: Remove
 use self::llvm_sys::LLVMTypeKind::*; // TODO: Remove
+use self::llvm_sys::target_machine::LLVMCodeGenFileType; // TODO: Remove
+use std::cell::RefCell;
 use std::collections::HashMap;
-use syntax::expr::{Expr, ExprWrapper};
+use std::env;
+use std::fs::{self, File};
+use std::io::Write;
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::process::{self, Command};
+use std::str::FromStr;
+use lexical::types::Types;
+use syntax::expr::{method_name, Expr, ExprWrapper};
 use syntax::literals::Literals;
 use syntax::op::{InfixOp, UnaryOp};
 
+// Where `continue` and `break` branch to from inside a loop
+struct LoopContext {
+    continue_block: BasicBlock,
+    break_block: BasicBlock,
+}
+
+// How a class's objects are laid out: a pointer to the class's vtable, followed by
+// every field with the base class's fields first, so an object can be used as its base
+struct ClassLayout {
+    struct_type: Type,
+    fields: Vec<(String, Type)>,
+    // Functions in the vtable, in slot order. Overriding methods take their base's slot
+    methods: Vec<String>,
+    vtable: Option<Value>,
+}
+
+// The error being raised, if any, is kept in globals which every module shares: its
+// type's id (0 while nothing is being raised), its message, and what to print if it's never handled
+const ERROR_KIND: &'static str = "limonite.error_kind";
+const ERROR_MESSAGE: &'static str = "limonite.error_message";
+const ERROR_REPORT: &'static str = "limonite.error_report";
+
+// An error type's id, from a hash of its name so every module agrees on it without sharing a table
+fn error_id(name: &str) -> u64 {
+    let hash = name.bytes().fold(0xcbf29ce484222325u64, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x100000001b3));
+
+    // 0 means no error
+    if hash == 0 { 1 } else { hash }
+}
+
+// Whether SA found an expression to be a signed integer. Unchecked expressions are treated as unsigned
+fn is_signed(expr_wrapper: &ExprWrapper) -> bool {
+    expr_wrapper.get_type()
+                .and_then(|type_| type_.parse::<Types>().ok())
+                .map(|type_| type_.is_signed_integer())
+                .unwrap_or(false)
+}
+
+// Compiled alongside a program's objects to make an executable
+const RUNTIME_SOURCE: &'static str = include_str!("runtime.c");
+
+// What a program can be compiled to by `save_binary`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OutputKind {
+    Executable,
+    Object,
+    Assembly,
+    Bitcode,
+}
+
+impl OutputKind {
+    pub fn get_extension(&self) -> Option<&'static str> {
+        match *self {
+            OutputKind::Executable => None,
+            OutputKind::Object => Some("o"),
+            OutputKind::Assembly => Some("s"),
+            OutputKind::Bitcode => Some("bc"),
+        }
+    }
+}
+
+impl FromStr for OutputKind {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<OutputKind, ()> {
+        match s {
+            "exe"     => Ok(OutputKind::Executable),
+            "obj"     => Ok(OutputKind::Object),
+            "asm"     => Ok(OutputKind::Assembly),
+            "llvm-bc" => Ok(OutputKind::Bitcode),
+            _         => Err(())
+        }
+    }
+}
+
 /// WARNING: Drop order can be imporant, so context is placed last intentionally
 pub struct LLVMGenerator {
     builder: Builder,
+    loop_stack: RefCell<Vec<LoopContext>>, // Innermost loop last
+    classes: RefCell<HashMap<String, ClassLayout>>, // Laid out as their declarations are found
+    error_handlers: RefCell<Vec<BasicBlock>>, // Where errors raised in a `when` go, innermost last
+    file_name: String, // Shown by failing asserts
     main_module: Option<Module>, // REVIEW: Maybe modules: HashMap<module_name, (Module, PassManager)> instead?
+    imported_modules: Vec<(String, Module)>, // Linked into the execution engine alongside the main module
+    target_triple: Option<String>, // The machine the compiler's running on when not set
+    target_cpu: String,
+    opt_level: u32, // 0 to 3, as with `-O`
     execution_engine: Option<ExecutionEngine>,
-    pass_manager: Option<PassManager>,
     context: Context,
 }
 
@@ -29,13 +122,25 @@ impl LLVMGenerator {
 
         LLVMGenerator {
             builder: builder,
+            loop_stack: RefCell::new(Vec::new()),
+            classes: RefCell::new(HashMap::new()),
+            error_handlers: RefCell::new(Vec::new()),
+            file_name: "<unknown>".into(),
             context: context,
             execution_engine: None,
             main_module: None,
-            pass_manager: None,
+            imported_modules: Vec::new(),
+            target_triple: None,
+            target_cpu: "generic".into(),
+            opt_level: 0,
         }
     }
 
+    // The name of the file being compiled, for runtime error messages
+    pub fn set_file_name(&mut self, file_name: &str) {
+        self.file_name = file_name.to_string();
+    }
+
     pub fn add_module(&mut self, mut ast: ExprWrapper, as_main: bool, include_std: bool) {
         // TODO: Better non main module support. This should be split into add_main_module (required)
         // which is used to initialize the EE and add_module (optional) which will be added to the EE
@@ -53,26 +158,172 @@ impl LLVMGenerator {
         }
 
         if as_main {
-            ast = ExprWrapper::default(Expr::FnDecl("main".into(), vec![], None, ast));
+            ast = ExprWrapper::default(Expr::FnDecl("main".into(), vec![], false, None, ast));
         }
 
         if include_std {
             print_function_definition(&self.builder, &self.context, &main_module);
         }
 
+        // Imported modules refer to the main module's error globals
+        let i8_ptr_type = self.context.i8_type().ptr_type(0);
+        let i64_type = self.context.i64_type();
+
+        main_module.add_global(&i64_type, &Some(i64_type.const_null()), ERROR_KIND);
+        main_module.add_global(&i8_ptr_type, &Some(i8_ptr_type.const_null()), ERROR_MESSAGE);
+        main_module.add_global(&i8_ptr_type, &Some(i8_ptr_type.const_null()), ERROR_REPORT);
+
         self.generate_ir(&main_module, &ast, &mut HashMap::new());
 
         self.main_module = Some(main_module);
     }
 
+    // Add a module which only declares functions, for the main module (or other imported
+    // modules) to call. Modules need to be added after the modules they call into.
+    pub fn add_imported_module(&mut self, name: &str, ast: ExprWrapper) {
+        let module = self.context.create_module(name);
+
+        // The definition lives in the main module
+        print_function_declaration(&self.context, &module);
+
+        self.generate_ir(&module, &ast, &mut HashMap::new());
+
+        self.imported_modules.push((name.to_string(), module));
+    }
+
     pub fn dump_ir(&self) {
+        for &(_, ref module) in &self.imported_modules {
+            module.dump();
+        }
+
         if let Some(ref module) = self.main_module {
             module.dump();
         }
     }
 
-    pub fn save_binary(&self) -> () {
-        // TODO
+    // The IR generated for a function in the main module, ie to check what the optimizer made of it
+    pub fn get_function_ir(&self, fn_name: &str) -> Option<String> {
+        self.main_module.as_ref()
+                        .and_then(|module| module.get_function(fn_name))
+                        .map(|function| function.print_to_string())
+    }
+
+    // How hard to optimize, from 0 (not at all) to 3, as with `-O`
+    pub fn set_optimization_level(&mut self, opt_level: u32) {
+        self.opt_level = opt_level;
+    }
+
+    // The machine `save_binary` compiles for, ie `aarch64-unknown-linux-gnu`
+    pub fn set_target_triple(&mut self, triple: &str) {
+        self.target_triple = Some(triple.to_string());
+    }
+
+    // The CPU of that machine whose features can be used, ie `skylake`, rather than `generic`
+    pub fn set_target_cpu(&mut self, cpu: &str) {
+        self.target_cpu = cpu.to_string();
+    }
+
+    // Compile the program to a file rather than running it. Executables are linked by the
+    // system C compiler (or $CC) with every module. Otherwise each imported module gets a
+    // file of its own next to the main module's, named after it, ie `main.magic.o`.
+    pub fn save_binary(&self, path: &Path, output_kind: OutputKind) -> Result<(), String> {
+        let main_module = match self.main_module {
+            Some(ref module) => module,
+            None => return Err("LLVMGeneratorError: A main module was not created".into()),
+        };
+
+        for module in self.imported_modules.iter().map(|&(_, ref module)| module).chain(Some(main_module)) {
+            if !module.verify(true) {
+                return Err("LLVMGeneratorError: Generated an invalid module".into());
+            }
+        }
+
+        let triple = self.target_triple.clone().unwrap_or_else(TargetMachine::get_default_triple);
+        let target_machine = TargetMachine::new(&triple, &self.target_cpu, "", self.opt_level)?;
+
+        for module in self.imported_modules.iter().map(|&(_, ref module)| module).chain(Some(main_module)) {
+            module.set_data_layout(target_machine.get_data_layout());
+
+            self.optimize_module(module);
+        }
+
+        if output_kind != OutputKind::Executable {
+            for &(ref name, ref module) in &self.imported_modules {
+                self.save_module(&target_machine, module, &imported_module_path(path, name), output_kind)?;
+            }
+
+            return self.save_module(&target_machine, main_module, path, output_kind);
+        }
+
+        let build_dir = env::temp_dir().join(format!("limonite-{}", process::id()));
+
+        fs::create_dir_all(&build_dir).map_err(|e| format!("LLVMGeneratorError: Failed to create {}: {}", build_dir.display(), e))?;
+
+        let result = self.link_executable(&target_machine, main_module, &build_dir, path);
+
+        let _ = fs::remove_dir_all(&build_dir);
+
+        result
+    }
+
+    fn save_module(&self, target_machine: &TargetMachine, module: &Module, path: &Path, output_kind: OutputKind) -> Result<(), String> {
+        let path_str = path.to_string_lossy();
+
+        module.set_triple(&target_machine.get_triple());
+
+        match output_kind {
+            OutputKind::Bitcode => module.write_bitcode_to_file(&path_str),
+            OutputKind::Assembly => target_machine.write_to_file(module, LLVMCodeGenFileType::LLVMAssemblyFile, &path_str),
+            _ => target_machine.write_to_file(module, LLVMCodeGenFileType::LLVMObjectFile, &path_str),
+        }
+    }
+
+    fn link_executable(&self, target_machine: &TargetMachine, main_module: &Module, build_dir: &Path, path: &Path) -> Result<(), String> {
+        let mut inputs = Vec::new();
+
+        for &(ref name, ref module) in &self.imported_modules {
+            let object = build_dir.join(format!("{}.o", name));
+
+            self.save_module(target_machine, module, &object, OutputKind::Object)?;
+
+            inputs.push(object);
+        }
+
+        // The runtime's C main calls the program's, so it's moved out of the way while compiling
+        let mut main = match main_module.get_function("main") {
+            Some(function) => function.as_value(),
+            None => return Err("LLVMGeneratorError: Could not find main function to link".into()),
+        };
+
+        let object = build_dir.join("main.o");
+
+        main.set_name("limonite_main");
+
+        let result = self.save_module(target_machine, main_module, &object, OutputKind::Object);
+
+        main.set_name("main");
+
+        result?;
+
+        inputs.push(object);
+
+        let runtime = build_dir.join("runtime.c");
+
+        File::create(&runtime).and_then(|mut file| file.write_all(RUNTIME_SOURCE.as_bytes()))
+                              .map_err(|e| format!("LLVMGeneratorError: Failed to write {}: {}", runtime.display(), e))?;
+
+        inputs.push(runtime);
+
+        let cc = env::var("CC").unwrap_or_else(|_| "cc".into());
+
+        let output = Command::new(&cc).arg("-o").arg(path).args(&inputs).output()
+                                      .map_err(|e| format!("LLVMGeneratorError: Failed to run {}: {}", cc, e))?;
+
+        if !output.status.success() {
+            return Err(format!("LLVMGeneratorError: Linking with {} failed:\n{}", cc, String::from_utf8_lossy(&output.stderr)));
+        }
+
+        Ok(())
     }
 
     pub fn initialize(&mut self, jit_mode: bool) {
@@ -80,27 +331,92 @@ impl LLVMGenerator {
 
         assert!(main_module.verify(true)); // TODO: print param as cli flag
 
-        let execution_engine = match main_module.create_execution_engine(jit_mode) {
+        let mut execution_engine = match main_module.create_execution_engine(jit_mode) {
             Ok(ee) => ee,
             Err(s) => panic!("LLVMExecutionError: Failed to initialize execution_engine: {}", s),
         };
 
-        let pass_manager = main_module.create_function_pass_manager();
         let target_data = execution_engine.get_target_data();
         let data_layout = target_data.get_data_layout();
 
         main_module.set_data_layout(data_layout);
-        pass_manager.add_target_data(target_data);
 
-        // TODO: Add more passes here
-        // pass_manager.add_optimize_memcpy_pass();
+        for &(_, ref module) in &self.imported_modules {
+            assert!(module.verify(true));
 
-        pass_manager.initialize();
+            module.set_data_layout(target_data.get_data_layout());
+            execution_engine.add_module(module);
+        }
+
+        // Nothing's compiled until a function is first looked up, so the modules
+        // can still be optimized now that they know the target's data layout
+        for module in self.imported_modules.iter().map(|&(_, ref module)| module).chain(Some(main_module)) {
+            self.optimize_module(module);
+        }
 
-        self.pass_manager = Some(pass_manager);
         self.execution_engine = Some(execution_engine);
     }
 
+    // Runs the passes for the optimization level over a module. Each function is tidied up
+    // on its own first, then inlining and the loop passes run over the module as a whole.
+    fn optimize_module(&self, module: &Module) {
+        if self.opt_level == 0 {
+            return;
+        }
+
+        let function_pass_manager = module.create_function_pass_manager();
+
+        // Variables live on the stack until mem2reg, which most other passes rely on
+        function_pass_manager.add_promote_memory_to_register_pass();
+        function_pass_manager.add_instruction_combining_pass();
+        function_pass_manager.add_cfg_simplification_pass();
+
+        if self.opt_level >= 2 {
+            function_pass_manager.add_reassociate_pass();
+            function_pass_manager.add_gvn_pass();
+            function_pass_manager.add_dead_store_elimination_pass();
+            function_pass_manager.add_cfg_simplification_pass();
+        }
+
+        function_pass_manager.initialize();
+
+        for function in module.get_functions() {
+            function_pass_manager.run_on_function(&function);
+        }
+
+        function_pass_manager.finalize();
+
+        let module_pass_manager = PassManager::create_module_pass_manager();
+
+        if self.opt_level >= 2 {
+            module_pass_manager.add_function_inlining_pass();
+        } else {
+            module_pass_manager.add_always_inliner_pass();
+        }
+
+        // Inlining leaves more for the function passes to clean up, and loops
+        // need rotating into shape before the other loop passes can do much
+        module_pass_manager.add_promote_memory_to_register_pass();
+        module_pass_manager.add_instruction_combining_pass();
+        module_pass_manager.add_cfg_simplification_pass();
+        module_pass_manager.add_loop_rotate_pass();
+        module_pass_manager.add_licm_pass();
+        module_pass_manager.add_ind_var_simplify_pass();
+        module_pass_manager.add_loop_deletion_pass();
+
+        if self.opt_level >= 3 {
+            module_pass_manager.add_loop_unroll_pass();
+            module_pass_manager.add_loop_vectorize_pass();
+        }
+
+        module_pass_manager.add_gvn_pass();
+        module_pass_manager.add_instruction_combining_pass();
+        module_pass_manager.add_cfg_simplification_pass();
+        module_pass_manager.add_global_dce_pass();
+
+        module_pass_manager.run_on_module(module);
+    }
+
     pub fn get_function_address(&self, fn_name: &str) -> Result<u64, String> {
         if self.main_module.is_none() {
             return Err("LLVMGeneratorError: A main module was not created".into());
@@ -144,6 +460,20 @@ impl LLVMGenerator {
             &Expr::Block(ref exprs) => {
                 let mut last_value = None;
 
+                // Classes are laid out first, as functions may take their objects
+                for expr in exprs {
+                    if let &Expr::ClassDecl(ref name, ref base, ref fields, ref methods) = expr.get_expr() {
+                        self.declare_class(module, name, base, fields, methods);
+                    }
+                }
+
+                // Functions may be called before they're declared within a block
+                for expr in exprs {
+                    if let &Expr::FnDecl(ref name, ref arg_defs, var_args, ref return_type, _) = expr.get_expr() {
+                        self.declare_function(module, name, arg_defs, var_args, return_type);
+                    }
+                }
+
                 for expr in exprs {
                     last_value = self.generate_ir(module, expr, scoped_variables);
                 }
@@ -151,7 +481,7 @@ impl LLVMGenerator {
                 last_value
             },
             &Expr::FnCall(ref name, ref args) => {
-                let function = match module.get_function(name) {
+                let function = match self.get_or_import_function(module, name) {
                     Some(function) => function,
                     None => {
                         println!("LLVMGenError: Could not find function {}", name);
@@ -162,21 +492,24 @@ impl LLVMGenerator {
 
                 let num_params = function.count_params() as usize;
 
-                if num_params != args.len() {
+                // Varargs functions take any number of args past their declared ones
+                if num_params != args.len() && !(function.is_var_arg() && args.len() > num_params) {
                     println!("LLVMGenError: Function {} requires {} args. Found {}", name, args.len(), num_params);
 
                     return None; // REVIEW: panic?
                 }
 
-                let mut arg_values = Vec::with_capacity(num_params);
+                let arg_values: Vec<Value> = args.iter().enumerate()
+                                                 .map(|(i, arg)| self.generate_arg(module, &function, i, arg, scoped_variables))
+                                                 .collect();
 
-                for arg in args {
-                    let value = self.generate_ir(module, arg, scoped_variables).unwrap();
+                let value = self.builder.build_call(&function, &arg_values, name); // REVIEW: maybe tmp_ + name? Unclear if same name as fn is bad..
 
-                    arg_values.push(value);
+                if ast.can_raise() {
+                    self.build_error_check(module);
                 }
 
-                Some(self.builder.build_call(&function, &arg_values, name)) // REVIEW: maybe tmp_ + name? Unclear if same name as fn is bad..
+                Some(value)
             },
             &Expr::Literal(ref literal_type) => {
                 match literal_type {
@@ -205,6 +538,9 @@ impl LLVMGenerator {
 
                         let global_str = module.add_global(&i8_array_type, &Some(const_str_array), "global_str");
 
+                        // Private, so that each module's strings don't clash once they're linked together
+                        global_str.set_linkage(LLVMPrivateLinkage);
+
                         let stack_struct = self.builder.build_stack_allocation(&string_type, "string_struct");
 
                         let str_ptr = self.builder.build_gep(&stack_struct, &vec![0, 0], "str_ptr");
@@ -251,6 +587,10 @@ impl LLVMGenerator {
                     &Literals::_None => panic!("LLVMGenError: Unimplemented for NoneType")
                 }
             },
+            // `and` and `or` may not evaluate their rhs, so they get their own blocks
+            &Expr::InfixOp(ref op, ref lhs_exprwrapper, ref rhs_exprwrapper) if op.is_logical() => {
+                self.generate_logical_op(module, op, lhs_exprwrapper, rhs_exprwrapper, scoped_variables)
+            },
             &Expr::InfixOp(ref op, ref lhs_exprwrapper, ref rhs_exprwrapper) => {
                 let (mut lhs_val, mut rhs_val) = match (self.generate_ir(module, lhs_exprwrapper, scoped_variables), self.generate_ir(module, rhs_exprwrapper, scoped_variables)) {
                     (Some(val1), Some(val2)) => (val1, val2),
@@ -269,6 +609,8 @@ impl LLVMGenerator {
                     rhs_val = self.builder.build_load(&rhs_val, "deref"); // Think this is like Rust's deref trait...
                 }
 
+                let signed = is_signed(lhs_exprwrapper);
+
                 // REVIEW: Adding different types should never happen if SA is doing it's job, right?
                 match op {
                     &InfixOp::Add => {
@@ -310,7 +652,7 @@ impl LLVMGenerator {
                     },
                     &InfixOp::Div => {
                         let div = match (lhs_val.get_type_kind(), rhs_val.get_type_kind()) { // REVIEW: Not fully tested
-                            (LLVMIntegerTypeKind, LLVMIntegerTypeKind) => self.builder.build_int_div(&lhs_val, &rhs_val, "int_div"), // TODO: Signed support
+                            (LLVMIntegerTypeKind, LLVMIntegerTypeKind) => self.builder.build_int_div(&lhs_val, &rhs_val, signed, "int_div"),
                             (LLVMFloatTypeKind, LLVMFloatTypeKind) => self.builder.build_float_div(&lhs_val, &rhs_val, "f32_div"),
                             (LLVMDoubleTypeKind, LLVMDoubleTypeKind) => self.builder.build_float_div(&lhs_val, &rhs_val, "f64_div"),
                             (LLVMFP128TypeKind, LLVMFP128TypeKind) => self.builder.build_float_div(&lhs_val, &rhs_val, "f128_div"),
@@ -320,8 +662,17 @@ impl LLVMGenerator {
 
                         Some(div)
                     },
-                    &InfixOp::Mod => match (lhs_val, rhs_val) {
-                        _ => panic!("LLVMGenError: Unimplemented infix operator mod")
+                    &InfixOp::Mod => {
+                        let rem = match (lhs_val.get_type_kind(), rhs_val.get_type_kind()) { // REVIEW: Not fully tested
+                            (LLVMIntegerTypeKind, LLVMIntegerTypeKind) => self.builder.build_int_rem(&lhs_val, &rhs_val, signed, "int_rem"),
+                            (LLVMFloatTypeKind, LLVMFloatTypeKind) => self.builder.build_float_rem(&lhs_val, &rhs_val, "f32_rem"),
+                            (LLVMDoubleTypeKind, LLVMDoubleTypeKind) => self.builder.build_float_rem(&lhs_val, &rhs_val, "f64_rem"),
+                            (LLVMFP128TypeKind, LLVMFP128TypeKind) => self.builder.build_float_rem(&lhs_val, &rhs_val, "f128_rem"),
+                            (LLVMStructTypeKind, LLVMStructTypeKind) => panic!("LLVMGenError: Custom struct modulo not yet implemented."),
+                            (_, _) => panic!("LLVMGenError: Unsupported type modulo: {:?} % {:?}", lhs_val.get_name(), rhs_val.get_name()),
+                        };
+
+                        Some(rem)
                     },
                     &InfixOp::Pow => match (lhs_val, rhs_val) {
                         _ => panic!("LLVMGenError: Unimplemented infix operator pow")
@@ -338,9 +689,41 @@ impl LLVMGenerator {
 
                         Some(equ)
                     },
+                    &InfixOp::NotEqu => {
+                        let not_equ = match (lhs_val.get_type_kind(), rhs_val.get_type_kind()) { // REVIEW: Not fully tested
+                            (LLVMIntegerTypeKind, LLVMIntegerTypeKind) => self.builder.build_int_compare(LLVMIntNE, &lhs_val, &rhs_val, "int_not_equ"),
+                            (LLVMFloatTypeKind, LLVMFloatTypeKind) => self.builder.build_float_compare(LLVMRealONE, &lhs_val, &rhs_val, "f32_not_equ"),
+                            (LLVMDoubleTypeKind, LLVMDoubleTypeKind) => self.builder.build_float_compare(LLVMRealONE, &lhs_val, &rhs_val, "f64_not_equ"),
+                            (LLVMFP128TypeKind, LLVMFP128TypeKind) => self.builder.build_float_compare(LLVMRealONE, &lhs_val, &rhs_val, "f128_not_equ"),
+                            (LLVMStructTypeKind, LLVMStructTypeKind) => panic!("LLVMGenError: Custom struct inequality not yet implemented."),
+                            (_, _) => panic!("LLVMGenError: Unsupported type inequality: {:?} != {:?}", lhs_val.get_name(), rhs_val.get_name()),
+                        };
+
+                        Some(not_equ)
+                    },
+                    // Primitives are values, so identity is the same as equality for now. Objects are the same when they're at the same address
+                    &InfixOp::Is => {
+                        let is = match (lhs_val.get_type_kind(), rhs_val.get_type_kind()) { // REVIEW: Not fully tested
+                            (LLVMPointerTypeKind, LLVMPointerTypeKind) => {
+                                let rhs_val = self.builder.build_pointer_cast(&rhs_val, &lhs_val.get_type(), "rhs_object");
+
+                                self.builder.build_int_compare(LLVMIntEQ, &lhs_val, &rhs_val, "object_is")
+                            },
+                            (LLVMIntegerTypeKind, LLVMIntegerTypeKind) => self.builder.build_int_compare(LLVMIntEQ, &lhs_val, &rhs_val, "int_is"),
+                            (LLVMFloatTypeKind, LLVMFloatTypeKind) => self.builder.build_float_compare(LLVMRealOEQ, &lhs_val, &rhs_val, "f32_is"),
+                            (LLVMDoubleTypeKind, LLVMDoubleTypeKind) => self.builder.build_float_compare(LLVMRealOEQ, &lhs_val, &rhs_val, "f64_is"),
+                            (LLVMFP128TypeKind, LLVMFP128TypeKind) => self.builder.build_float_compare(LLVMRealOEQ, &lhs_val, &rhs_val, "f128_is"),
+                            (LLVMStructTypeKind, LLVMStructTypeKind) => panic!("LLVMGenError: Custom struct identity not yet implemented."),
+                            (_, _) => panic!("LLVMGenError: Unsupported type identity: {:?} is {:?}", lhs_val.get_name(), rhs_val.get_name()),
+                        };
+
+                        Some(is)
+                    },
+                    &InfixOp::And |
+                    &InfixOp::Or => unreachable!("LLVMGenError: Logical ops are generated separately"),
                     &InfixOp::Lt => {
                         let lt = match (lhs_val.get_type_kind(), rhs_val.get_type_kind()) { // REVIEW: Not fully tested
-                            (LLVMIntegerTypeKind, LLVMIntegerTypeKind) => self.builder.build_int_compare(LLVMIntULT, &lhs_val, &rhs_val, "int_lt"), // TODO: Signed compare
+                            (LLVMIntegerTypeKind, LLVMIntegerTypeKind) => self.builder.build_int_compare(if signed { LLVMIntSLT } else { LLVMIntULT }, &lhs_val, &rhs_val, "int_lt"),
                             (LLVMFloatTypeKind, LLVMFloatTypeKind) => self.builder.build_float_compare(LLVMRealOLT, &lhs_val, &rhs_val, "f32_lt"), // REVIEW: How is this different from LLVMRealULT??
                             (LLVMDoubleTypeKind, LLVMDoubleTypeKind) => self.builder.build_float_compare(LLVMRealOLT, &lhs_val, &rhs_val, "f64_lt"), // ^
                             (LLVMFP128TypeKind, LLVMFP128TypeKind) => self.builder.build_float_compare(LLVMRealOLT, &lhs_val, &rhs_val, "f128_lt"), // ^
@@ -352,7 +735,7 @@ impl LLVMGenerator {
                     },
                     &InfixOp::Lte => {
                         let lte = match (lhs_val.get_type_kind(), rhs_val.get_type_kind()) { // REVIEW: Not fully tested
-                            (LLVMIntegerTypeKind, LLVMIntegerTypeKind) => self.builder.build_int_compare(LLVMIntULE, &lhs_val, &rhs_val, "int_lte"), // TODO: Signed compare
+                            (LLVMIntegerTypeKind, LLVMIntegerTypeKind) => self.builder.build_int_compare(if signed { LLVMIntSLE } else { LLVMIntULE }, &lhs_val, &rhs_val, "int_lte"),
                             (LLVMFloatTypeKind, LLVMFloatTypeKind) => self.builder.build_float_compare(LLVMRealOLE, &lhs_val, &rhs_val, "f32_lte"), // REVIEW: How is this different from LLVMRealULE??
                             (LLVMDoubleTypeKind, LLVMDoubleTypeKind) => self.builder.build_float_compare(LLVMRealOLE, &lhs_val, &rhs_val, "f64_lte"), // ^
                             (LLVMFP128TypeKind, LLVMFP128TypeKind) => self.builder.build_float_compare(LLVMRealOLE, &lhs_val, &rhs_val, "f128_lte"), // ^
@@ -364,7 +747,7 @@ impl LLVMGenerator {
                     },
                     &InfixOp::Gt => {
                         let gt = match (lhs_val.get_type_kind(), rhs_val.get_type_kind()) { // REVIEW: Not fully tested
-                            (LLVMIntegerTypeKind, LLVMIntegerTypeKind) => self.builder.build_int_compare(LLVMIntUGT, &lhs_val, &rhs_val, "int_gt"), // TODO: Signed compare
+                            (LLVMIntegerTypeKind, LLVMIntegerTypeKind) => self.builder.build_int_compare(if signed { LLVMIntSGT } else { LLVMIntUGT }, &lhs_val, &rhs_val, "int_gt"),
                             (LLVMFloatTypeKind, LLVMFloatTypeKind) => self.builder.build_float_compare(LLVMRealOGT, &lhs_val, &rhs_val, "f32_gt"), // REVIEW: How is this different from LLVMRealUGT??
                             (LLVMDoubleTypeKind, LLVMDoubleTypeKind) => self.builder.build_float_compare(LLVMRealOGT, &lhs_val, &rhs_val, "f64_gt"), // ^
                             (LLVMFP128TypeKind, LLVMFP128TypeKind) => self.builder.build_float_compare(LLVMRealOGT, &lhs_val, &rhs_val, "f128_gt"), // ^
@@ -376,7 +759,7 @@ impl LLVMGenerator {
                     },
                     &InfixOp::Gte => {
                         let gte = match (lhs_val.get_type_kind(), rhs_val.get_type_kind()) { // REVIEW: Not fully tested
-                            (LLVMIntegerTypeKind, LLVMIntegerTypeKind) => self.builder.build_int_compare(LLVMIntUGE, &lhs_val, &rhs_val, "int_gte"), // TODO: Signed compare
+                            (LLVMIntegerTypeKind, LLVMIntegerTypeKind) => self.builder.build_int_compare(if signed { LLVMIntSGE } else { LLVMIntUGE }, &lhs_val, &rhs_val, "int_gte"),
                             (LLVMFloatTypeKind, LLVMFloatTypeKind) => self.builder.build_float_compare(LLVMRealOGE, &lhs_val, &rhs_val, "f32_gte"), // REVIEW: How is this different from LLVMRealUGE??
                             (LLVMDoubleTypeKind, LLVMDoubleTypeKind) => self.builder.build_float_compare(LLVMRealOGE, &lhs_val, &rhs_val, "f64_gte"), // ^
                             (LLVMFP128TypeKind, LLVMFP128TypeKind) => self.builder.build_float_compare(LLVMRealOGE, &lhs_val, &rhs_val, "f128_gte"), // ^
@@ -395,42 +778,62 @@ impl LLVMGenerator {
                     &UnaryOp::Not => self.generate_ir(module, expr, scoped_variables).map(|val| self.builder.build_not(&val, "not")),
                 }
             },
-            &Expr::FnDecl(ref name, ref arg_defs, ref return_type, ref body_expr) => {
+            &Expr::FnDecl(ref name, ref arg_defs, var_args, ref return_type, ref body_expr) => {
                 let mut fn_variable_scope = HashMap::new(); // REVIEW: This will exclude globals
-                let mut arg_types: Vec<Type> = arg_defs.iter().map(|&(_, ref type_string)| self.string_to_type(&type_string[..], &module).expect("Did not find specified type")).collect();
+                let function = self.declare_function(module, name, arg_defs, var_args, return_type);
 
-                // TODO: Support args types and return types
-                let return_type = match return_type {
-                    &Some(ref type_string) => self.string_to_type(&type_string[..], &module).expect("Did not find speficied type"),
-                    &None => self.context.void_type(),
-                };
+                // Generating the body moves the builder, so remember where we were, ie
+                // part way through main when a function is declared inside of it
+                let outer_block = self.builder.try_get_insert_block();
 
-                let function = module.add_function(name, return_type.fn_type(&mut arg_types, false));
+                let bb_enter = self.context.append_basic_block(&function, "enter");
 
-                let name_value_data = arg_defs.iter().map(|&(ref name, _)| name).zip(function.params());
+                self.builder.position_at_end(&bb_enter);
+
+                let name_value_data = arg_defs.iter().map(|&(ref name, _, _)| name).zip(function.params());
 
+                // Params are spilled to the stack so they can be assigned to like any other variable
                 for (name, mut param_value) in name_value_data {
                     param_value.set_name(&name);
-                    fn_variable_scope.insert(name.to_string(), param_value.as_value()); // REVIEW: Cow?
+
+                    let param_value = param_value.as_value();
+                    let alloca = self.builder.build_stack_allocation(&param_value.get_type(), name);
+                    self.builder.build_store(&param_value, &alloca);
+
+                    fn_variable_scope.insert(name.to_string(), alloca); // REVIEW: Cow?
                 }
 
-                let bb_enter = self.context.append_basic_block(&function, "enter");
+                // Errors raised in the function go to its caller rather than any `when` it's declared in
+                let outer_handlers = mem::replace(&mut *self.error_handlers.borrow_mut(), Vec::new());
 
-                self.builder.position_at_end(&bb_enter);
+                self.generate_ir(module, body_expr, &mut fn_variable_scope);
 
-                // REVIEW: This will return the last generated value... is that what we want?
-                // Or should it go back to the global scope after generating ir?
-                self.generate_ir(module, body_expr, &mut fn_variable_scope)
+                *self.error_handlers.borrow_mut() = outer_handlers;
+
+                // A function without a return type may fall off the end of its body. SA makes sure one
+                // with a return type returns on every path, so only a block nothing jumps to is left over
+                if self.builder.get_insert_block().get_terminator().is_none() {
+                    if return_type.is_some() {
+                        self.builder.build_unreachable();
+                    } else {
+                        self.builder.build_return(None);
+                    }
+                }
+
+                if let Some(outer_block) = outer_block {
+                    self.builder.position_at_end(&outer_block);
+                }
+
+                None
             },
             &Expr::Return(ref return_type_expr) => {
                 match return_type_expr {
                     &Some(ref return_type) => match self.generate_ir(module, return_type, scoped_variables) {
-                        Some(mut t) => {
+                        Some(t) => {
                             // REVIEW: I'm wondering if auto deref should be handled by semantic analysis
                             // and insert a "deref" expr
-                            if t.is_pointer() {
-                                t = self.builder.build_load(&t, "deref"); // Think this is like Rust's Deref Trait
-                            }
+                            let fn_return_type = self.builder.get_insert_block().get_parent().get_return_type();
+                            let t = self.load_as(t, &fn_return_type); // Think this is like Rust's Deref Trait
 
                             Some(self.builder.build_return(Some(t)))
                         }
@@ -448,9 +851,31 @@ impl LLVMGenerator {
             &Expr::VarDecl(_, ref name, ref val_type, ref expr) => {
                 assert!(val_type.is_some(), "LLVMGenError: Variable declaration not given a type by codegen phase");
 
+                // Objects live on the heap, so their variables hold a pointer to one
+                if let Some(object_type) = val_type.as_ref().and_then(|type_string| self.class_type(type_string)) {
+                    let object = match self.generate_ir(module, expr, scoped_variables) {
+                        Some(val) => self.load_as(val, &object_type),
+                        None => return None
+                    };
+
+                    let alloca = self.builder.build_stack_allocation(&object_type, name);
+                    self.builder.build_store(&object, &alloca);
+
+                    scoped_variables.insert(name.clone(), alloca);
+
+                    return Some(alloca);
+                }
+
                 // Assign to a literal
                 match self.generate_ir(module, expr, scoped_variables) {
                     Some(val) => {
+                        // Declaring from another variable copies its value rather than sharing its storage
+                        let val = if let Expr::Var(_) = *expr.get_expr() {
+                            self.builder.build_load(&val, "copy")
+                        } else {
+                            val
+                        };
+
                         let val = if !val.is_pointer() {
                             let alloca = self.builder.build_stack_allocation(&val.get_type(), "stored_ptr");
                             self.builder.build_store(&val, &alloca);
@@ -469,61 +894,59 @@ impl LLVMGenerator {
                 }
             },
             &Expr::If(ref cond_expr, ref body_expr, ref opt_else_expr) => {
-                let cond_val = match self.generate_ir(module, cond_expr, scoped_variables) {
+                let mut cond_val = match self.generate_ir(module, cond_expr, scoped_variables) {
                     Some(val) => val,
                     None => return None
                 };
 
-                let type_ = self.context.bool_type();
-
-                let zero = type_.const_int(0, false);
-                let op = LLVMIntEQ;
-
-                let block = self.builder.get_insert_block();
-
-                let cond_cmp = self.builder.build_int_compare(op, &cond_val, &zero, "ifcond");
+                if cond_val.is_pointer() {
+                    cond_val = self.builder.build_load(&cond_val, "deref");
+                }
 
-                let parent_fn = block.get_parent();
+                let parent_fn = self.builder.get_insert_block().get_parent();
 
                 let body_block = self.context.append_basic_block(&parent_fn, "if");
-                let else_block = self.context.append_basic_block(&parent_fn, "else");
+
+                // Without an else, a false condition skips straight to the merge
+                let else_block = match opt_else_expr {
+                    &Some(_) => Some(self.context.append_basic_block(&parent_fn, "else")),
+                    &None => None,
+                };
                 let merge_block = self.context.append_basic_block(&parent_fn, "merge");
 
-                // If the condition is true:
-                self.builder.build_conditional_branch(&cond_cmp, &body_block, &else_block);
+                self.builder.build_conditional_branch(&cond_val, &body_block, else_block.as_ref().unwrap_or(&merge_block));
                 self.builder.position_at_end(&body_block);
 
-                let mut body_val = match self.generate_ir(module, body_expr, scoped_variables) {
-                    Some(val) => val,
-                    None => return None
-                };
+                self.generate_ir(module, body_expr, scoped_variables);
 
-                // Merge into the above layer when done
-                self.builder.build_unconditional_branch(&merge_block);
+                // A branch ending in a return doesn't fall through to the merge
+                let mut merged = !opt_else_expr.is_some();
 
-                // Call else codegen if it exists
-                let mut body_end_block = self.builder.get_insert_block();
+                if self.builder.get_insert_block().get_terminator().is_none() {
+                    self.builder.build_unconditional_branch(&merge_block);
+                    merged = true;
+                }
 
-                self.builder.position_at_end(&else_block);
+                if let (&Some(ref else_expr), Some(else_block)) = (opt_else_expr, else_block) {
+                    self.builder.position_at_end(&else_block);
 
-                // Optional, doesn't need to return on None
-                let opt_else_val = match opt_else_expr {
-                    &Some(ref expr) => self.generate_ir(module, expr, scoped_variables),
-                    &None => None
-                };
+                    self.generate_ir(module, else_expr, scoped_variables);
 
-                let else_br = self.builder.build_unconditional_branch(&merge_block);
-                let mut else_end_block = self.builder.get_insert_block();
+                    if self.builder.get_insert_block().get_terminator().is_none() {
+                        self.builder.build_unconditional_branch(&merge_block);
+                        merged = true;
+                    }
+                }
 
-                // Finish up
                 self.builder.position_at_end(&merge_block);
 
-                let phi = self.builder.build_phi(&type_, "phi");
-
-                phi.add_incoming(&mut body_val, &mut body_end_block, 1);
-                phi.add_incoming(&mut opt_else_val.unwrap_or(else_br), &mut else_end_block, 1);
+                // Every branch returned, so nothing can reach the merge
+                if !merged {
+                    self.builder.build_unreachable();
+                }
 
-                Some(phi)
+                // REVIEW: If is a statement for now, so it has no value
+                None
             },
             &Expr::WhileLoop(ref condition, ref body) => {
                 let one = self.context.bool_type().const_int(1, false);
@@ -546,14 +969,103 @@ impl LLVMGenerator {
                 self.builder.build_conditional_branch(&cond_cmp, &loop_block, &end_block);
                 self.builder.position_at_end(&loop_block);
 
+                self.loop_stack.borrow_mut().push(LoopContext { continue_block: cond_check_block, break_block: end_block });
+
                 let body = self.generate_ir(module, body, scoped_variables);
 
-                self.builder.build_unconditional_branch(&cond_check_block);
+                self.loop_stack.borrow_mut().pop();
+
+                if self.builder.get_insert_block().get_terminator().is_none() {
+                    self.builder.build_unconditional_branch(&cond_check_block);
+                }
+
                 self.builder.position_at_end(&end_block);
 
                 body
             },
-            &Expr::Assign(ref lhs_exprwrapper, ref rhs_exprwrapper) => { // REVIEW: Should we assume SA would stop us from mutating an immutable?
+            &Expr::ForLoop(ref name, ref start_expr, ref end_expr, ref opt_step_expr, ref body) => {
+                // The bounds and step are only evaluated once, before the loop starts
+                let (mut start_val, mut end_val) = match (self.generate_ir(module, start_expr, scoped_variables), self.generate_ir(module, end_expr, scoped_variables)) {
+                    (Some(val1), Some(val2)) => (val1, val2),
+                    _ => unreachable!("LLVMGenError: ForLoop range is missing a bound")
+                };
+
+                if start_val.is_pointer() {
+                    start_val = self.builder.build_load(&start_val, "deref");
+                }
+
+                if end_val.is_pointer() {
+                    end_val = self.builder.build_load(&end_val, "deref");
+                }
+
+                let step_val = match opt_step_expr {
+                    &Some(ref step_expr) => match self.generate_ir(module, step_expr, scoped_variables) {
+                        Some(val) if val.is_pointer() => self.builder.build_load(&val, "deref"),
+                        Some(val) => val,
+                        None => unreachable!("LLVMGenError: ForLoop step has no value")
+                    },
+                    &None => start_val.get_type().const_int(1, false),
+                };
+
+                let signed = is_signed(start_expr);
+                let parent_fn = self.builder.get_insert_block().get_parent();
+
+                // SA rejects steps it knows aren't positive, the rest are checked before the loop
+                // starts since a step of 0 or less would never reach the end
+                let zero = step_val.get_type().const_int(0, false);
+                let step_cmp = self.builder.build_int_compare(if signed { LLVMIntSGT } else { LLVMIntNE }, &step_val, &zero, "step_cmp");
+                let bad_step_block = self.context.append_basic_block(&parent_fn, "for_bad_step");
+                let start_block = self.context.append_basic_block(&parent_fn, "for_start");
+
+                self.builder.build_conditional_branch(&step_cmp, &start_block, &bad_step_block);
+                self.builder.position_at_end(&bad_step_block);
+                self.build_exit_with_message(module, &format!("{}:{}: For loop step must be more than 0\n", self.file_name, ast.get_start_line()));
+                self.builder.position_at_end(&start_block);
+
+                // The loop variable lives on the stack like any other variable, so the body can read it
+                let induction_var = self.build_entry_allocation(&start_val.get_type(), name);
+                self.builder.build_store(&start_val, &induction_var);
+                scoped_variables.insert(name.clone(), induction_var);
+
+                let cond_check_block = self.context.append_basic_block(&parent_fn, "for_cond_check");
+                let loop_block = self.context.append_basic_block(&parent_fn, "for_loop");
+                let step_block = self.context.append_basic_block(&parent_fn, "for_step");
+                let end_block = self.context.append_basic_block(&parent_fn, "for_end");
+
+                self.builder.build_unconditional_branch(&cond_check_block);
+                self.builder.position_at_end(&cond_check_block);
+
+                let induction_val = self.builder.build_load(&induction_var, name);
+                let cond_cmp = self.builder.build_int_compare(if signed { LLVMIntSLT } else { LLVMIntULT }, &induction_val, &end_val, "for_cmp");
+
+                self.builder.build_conditional_branch(&cond_cmp, &loop_block, &end_block);
+                self.builder.position_at_end(&loop_block);
+
+                self.loop_stack.borrow_mut().push(LoopContext { continue_block: step_block, break_block: end_block });
+
+                self.generate_ir(module, body, scoped_variables);
+
+                self.loop_stack.borrow_mut().pop();
+
+                if self.builder.get_insert_block().get_terminator().is_none() {
+                    self.builder.build_unconditional_branch(&step_block);
+                }
+
+                self.builder.position_at_end(&step_block);
+
+                let induction_val = self.builder.build_load(&induction_var, name);
+                let next_val = self.builder.build_int_add(&induction_val, &step_val, "for_next");
+
+                self.builder.build_store(&next_val, &induction_var);
+                self.builder.build_unconditional_branch(&cond_check_block);
+                self.builder.position_at_end(&end_block);
+
+                // SA keeps the loop variable from being used after the loop
+                scoped_variables.remove(name);
+
+                None
+            },
+            &Expr::Assign(ref lhs_exprwrapper, ref rhs_exprwrapper) => { // SA rejects assignments to `def` constants
                 // REVIEW: Does it ever make sense for the lhs to be anything other than a string?
                 // We could just look it up in the hash table directly...
                 // if let &Expr::Var(ref string) = lhs_exprwrapper.get_expr() {
@@ -569,12 +1081,617 @@ impl LLVMGenerator {
 
                 // let lhs_val = self.builder.build_gep(&lhs_val, &vec![0], "gep");
 
+                let rhs_val = self.load_as(rhs_val, &lhs_val.get_type().get_element_type());
+
                 Some(self.builder.build_store(&rhs_val, &lhs_val))
             },
+            &Expr::Assert(ref cond_expr, ref opt_message, ref source_text) => {
+                let mut cond_val = match self.generate_ir(module, cond_expr, scoped_variables) {
+                    Some(val) => val,
+                    None => return None
+                };
+
+                if cond_val.is_pointer() {
+                    cond_val = self.builder.build_load(&cond_val, "deref");
+                }
+
+                let parent_fn = self.builder.get_insert_block().get_parent();
+                let fail_block = self.context.append_basic_block(&parent_fn, "assert_fail");
+                let pass_block = self.context.append_basic_block(&parent_fn, "assert_pass");
+
+                self.builder.build_conditional_branch(&cond_val, &pass_block, &fail_block);
+                self.builder.position_at_end(&fail_block);
+
+                let mut failure = format!("{}:{}: Assertion failed: {}", self.file_name, ast.get_start_line(), source_text);
+
+                if let &Some(ref message) = opt_message {
+                    failure.push_str(&format!(", {}", message));
+                }
+
+                failure.push('\n');
+
+                self.build_exit_with_message(module, &failure);
+                self.builder.position_at_end(&pass_block);
+
+                None
+            },
+            &Expr::Break | &Expr::Continue => {
+                let target_block = match (ast.get_expr(), self.loop_stack.borrow().last()) {
+                    (&Expr::Break, Some(loop_context)) => loop_context.break_block,
+                    (_, Some(loop_context)) => loop_context.continue_block,
+                    (_, None) => unreachable!("LLVMGenError: SA should have caught a break or continue outside of a loop"),
+                };
+
+                self.builder.build_unconditional_branch(&target_block);
+
+                // Anything after this point in the block is dead, but still needs somewhere to go
+                let parent_fn = self.builder.get_insert_block().get_parent();
+                let dead_block = self.context.append_basic_block(&parent_fn, "after_loop_control");
+
+                self.builder.position_at_end(&dead_block);
+
+                None
+            },
+            // The class was laid out by the block it's in, leaving its methods' bodies
+            &Expr::ClassDecl(_, _, _, ref methods) => {
+                for method in methods {
+                    self.generate_ir(module, method, scoped_variables);
+                }
+
+                None
+            },
+            &Expr::Construct(ref class_name, ref args) => {
+                let (struct_type, field_types, vtable) = {
+                    let classes = self.classes.borrow();
+                    let class = classes.get(class_name).expect("LLVMGenError: Unknown class was uncaught");
+                    let field_types: Vec<Type> = class.fields.iter().map(|&(_, field_type)| field_type).collect();
+
+                    (class.struct_type, field_types, class.vtable.expect("LLVMGenError: Class has no vtable"))
+                };
+
+                // REVIEW: Objects are never freed
+                let object = self.builder.build_heap_allocation(&struct_type, "object");
+
+                let vtable_ptr = self.builder.build_gep(&vtable, &vec![0, 0], "vtable_ptr");
+                let vtable_slot = self.builder.build_gep(&object, &vec![0, 0], "vtable_slot");
+
+                self.builder.build_store(&vtable_ptr, &vtable_slot);
+
+                // SA filled in the defaults of any fields left out
+                for (i, (arg, field_type)) in args.iter().zip(field_types).enumerate() {
+                    let value = match self.generate_ir(module, arg, scoped_variables) {
+                        Some(val) => self.load_as(val, &field_type),
+                        None => unreachable!("LLVMGenError: Field value has no value")
+                    };
+
+                    let field_ptr = self.builder.build_gep(&object, &vec![0, i as u64 + 1], "field_ptr");
+
+                    self.builder.build_store(&value, &field_ptr);
+                }
+
+                Some(object)
+            },
+            &Expr::FieldAccess(ref object_expr, ref field, ref opt_class) => {
+                let class_name = opt_class.as_ref().expect("LLVMGenError: Field access not given a class by SA");
+
+                let (object_type, index) = {
+                    let classes = self.classes.borrow();
+                    let class = classes.get(class_name).expect("LLVMGenError: Unknown class was uncaught");
+                    let index = class.fields.iter().position(|&(ref name, _)| name == field).expect("LLVMGenError: Unknown field was uncaught");
+
+                    (class.struct_type.ptr_type(0), index + 1) // After the vtable
+                };
+
+                let object = match self.generate_ir(module, object_expr, scoped_variables) {
+                    Some(val) => self.load_as(val, &object_type),
+                    None => unreachable!("LLVMGenError: Object has no value")
+                };
+
+                // Like a variable, a field is a pointer to where its value is kept
+                Some(self.builder.build_gep(&object, &vec![0, index as u64], field))
+            },
+            &Expr::MethodCall(ref object_expr, ref method, ref args, ref opt_class) => {
+                let class_name = opt_class.as_ref().expect("LLVMGenError: Method call not given a class by SA");
+
+                let (object_type, slot, function) = {
+                    let classes = self.classes.borrow();
+                    let class = classes.get(class_name).expect("LLVMGenError: Unknown class was uncaught");
+                    let slot = class.methods.iter().position(|name| method_name(name) == method).expect("LLVMGenError: Unknown method was uncaught");
+                    let function = module.get_function(&class.methods[slot]).expect("LLVMGenError: Method was not declared");
+
+                    (class.struct_type.ptr_type(0), slot, function)
+                };
+
+                let object = match self.generate_ir(module, object_expr, scoped_variables) {
+                    Some(val) => self.load_as(val, &object_type),
+                    None => unreachable!("LLVMGenError: Object has no value")
+                };
+
+                // The method is looked up in the object's own vtable, so a subclass's override
+                // is called even when the object is only known as its base class here
+                let vtable_slot = self.builder.build_gep(&object, &vec![0, 0], "vtable_slot");
+                let vtable = self.builder.build_load(&vtable_slot, "vtable");
+                let method_slot = self.builder.build_gep(&vtable, &vec![slot as u64], "method_slot");
+                let method_ptr = self.builder.build_load(&method_slot, "method_ptr");
+                let method_ptr = self.builder.build_pointer_cast(&method_ptr, &function.as_value().get_type(), method);
+
+                // The object is passed as `self`, cast to whichever class defines the method
+                let self_type = function.get_first_param().expect("LLVMGenError: Method has no self").as_value().get_type();
+                let mut arg_values = vec![self.load_as(object, &self_type)];
+
+                for (i, arg) in args.iter().enumerate() {
+                    arg_values.push(self.generate_arg(module, &function, i + 1, arg, scoped_variables));
+                }
+
+                let value = self.builder.build_indirect_call(&method_ptr, &arg_values, method);
+
+                if ast.can_raise() {
+                    self.build_error_check(module);
+                }
+
+                Some(value)
+            },
+            &Expr::Raise(ref error_type, ref opt_message) => {
+                let (kind, message, report) = self.error_globals(module);
+                let i64_type = self.context.i64_type();
+
+                let mut report_text = format!("{}:{}: Uncaught {}", self.file_name, ast.get_start_line(), error_type);
+
+                if let &Some(ref message) = opt_message {
+                    report_text.push_str(&format!(": {}", message));
+                }
+
+                report_text.push('\n');
+
+                let message_text = opt_message.as_ref().map(|message| &message[..]).unwrap_or("");
+                let message_val = self.build_c_string(module, message_text, "error_message");
+                let report_val = self.build_c_string(module, &report_text, "error_report");
+
+                self.builder.build_store(&i64_type.const_int(error_id(error_type), false), &kind);
+                self.builder.build_store(&message_val, &message);
+                self.builder.build_store(&report_val, &report);
+
+                self.build_propagate_error(module);
+
+                // Anything after this point in the block is dead, but still needs somewhere to go
+                let parent_fn = self.builder.get_insert_block().get_parent();
+                let dead_block = self.context.append_basic_block(&parent_fn, "after_raise");
+
+                self.builder.position_at_end(&dead_block);
+
+                None
+            },
+            &Expr::When(ref body_expr, ref handlers, ref opt_else_expr) => {
+                let (kind, message, _) = self.error_globals(module);
+                let i64_type = self.context.i64_type();
+
+                let parent_fn = self.builder.get_insert_block().get_parent();
+                let dispatch_block = self.context.append_basic_block(&parent_fn, "when_raised");
+                let handler_blocks: Vec<BasicBlock> = handlers.iter()
+                                                              .map(|_| self.context.append_basic_block(&parent_fn, "throws"))
+                                                              .collect();
+
+                // Without an else, finishing the body skips straight to the merge
+                let else_block = match opt_else_expr {
+                    &Some(_) => Some(self.context.append_basic_block(&parent_fn, "when_else")),
+                    &None => None,
+                };
+                let merge_block = self.context.append_basic_block(&parent_fn, "when_merge");
+
+                self.error_handlers.borrow_mut().push(dispatch_block);
+
+                self.generate_ir(module, body_expr, scoped_variables);
+
+                self.error_handlers.borrow_mut().pop();
+
+                let mut merged = false;
+
+                if self.builder.get_insert_block().get_terminator().is_none() {
+                    self.builder.build_unconditional_branch(else_block.as_ref().unwrap_or(&merge_block));
+                    merged = else_block.is_none();
+                }
+
+                // The first handler naming the error's type gets it
+                self.builder.position_at_end(&dispatch_block);
+
+                let kind_val = self.builder.build_load(&kind, "error_kind");
+
+                for (&(ref error_types, _, _), handler_block) in handlers.iter().zip(&handler_blocks) {
+                    for error_type in error_types {
+                        let id = i64_type.const_int(error_id(error_type), false);
+                        let is_type = self.builder.build_int_compare(LLVMIntEQ, &kind_val, &id, "is_error_type");
+                        let next_block = self.context.append_basic_block(&parent_fn, "when_next_type");
+
+                        self.builder.build_conditional_branch(&is_type, handler_block, &next_block);
+                        self.builder.position_at_end(&next_block);
+                    }
+                }
+
+                // Nothing here handles it, so it carries on to an outer handler
+                self.build_propagate_error(module);
+
+                for (&(_, ref binding, ref handler_expr), handler_block) in handlers.iter().zip(&handler_blocks) {
+                    self.builder.position_at_end(handler_block);
+                    self.builder.build_store(&i64_type.const_null(), &kind);
+
+                    // The error's message is bound as a string, sharing the raised message's bytes
+                    if let &Some(ref name) = binding {
+                        let string_struct = self.builder.build_stack_allocation(&string_type(&self.context), name);
+                        let message_val = self.builder.build_load(&message, "error_message");
+                        let len = self.builder.build_call(&self.libc_function(module, "strlen"), &vec![message_val], "len");
+
+                        let str_ptr = self.builder.build_gep(&string_struct, &vec![0, 0], "str_ptr");
+                        let len_ptr = self.builder.build_gep(&string_struct, &vec![0, 1], "len_ptr");
+                        let cap_ptr = self.builder.build_gep(&string_struct, &vec![0, 2], "cap_ptr");
+
+                        self.builder.build_store(&message_val, &str_ptr);
+                        self.builder.build_store(&len, &len_ptr);
+                        self.builder.build_store(&len, &cap_ptr);
+
+                        scoped_variables.insert(name.clone(), string_struct);
+                    }
+
+                    self.generate_ir(module, handler_expr, scoped_variables);
+
+                    if let &Some(ref name) = binding {
+                        scoped_variables.remove(name);
+                    }
+
+                    if self.builder.get_insert_block().get_terminator().is_none() {
+                        self.builder.build_unconditional_branch(&merge_block);
+                        merged = true;
+                    }
+                }
+
+                if let (&Some(ref else_expr), Some(else_block)) = (opt_else_expr, else_block) {
+                    self.builder.position_at_end(&else_block);
+
+                    self.generate_ir(module, else_expr, scoped_variables);
+
+                    if self.builder.get_insert_block().get_terminator().is_none() {
+                        self.builder.build_unconditional_branch(&merge_block);
+                        merged = true;
+                    }
+                }
+
+                self.builder.position_at_end(&merge_block);
+
+                // Every branch returned, so nothing can reach the merge
+                if !merged {
+                    self.builder.build_unreachable();
+                }
+
+                None
+            },
+            // Imports were already resolved into calls to the imported module's functions
+            &Expr::Use(..) => None,
             &Expr::NoOp => None,
         }
     }
 
+    // Allocates a variable in the entry block of the function being built, so it's allocated
+    // once however many times the code using it runs, and mem2reg can promote it to a register
+    fn build_entry_allocation(&self, type_: &Type, name: &str) -> Value {
+        let entry_block = self.builder.get_insert_block().get_parent().get_first_basic_block()
+                                      .expect("LLVMGenError: Function has no entry block");
+        let entry_builder = self.context.create_builder();
+
+        match entry_block.get_first_instruction() {
+            Some(instruction) => entry_builder.position_before(&instruction),
+            None => entry_builder.position_at_end(&entry_block),
+        }
+
+        entry_builder.build_stack_allocation(type_, name)
+    }
+
+    // Writes a message to stderr then exits with a non-zero status, ending the current block
+    fn build_exit_with_message(&self, module: &Module, message: &str) {
+        let i8_type = self.context.i8_type();
+        let i32_type = self.context.i32_type();
+        let i64_type = self.context.i64_type();
+        let i8_array_type = i8_type.array_type(message.len() as u32);
+
+        let write_fn = self.libc_function(module, "write");
+        let exit_fn = self.libc_function(module, "exit");
+
+        let chars = message.bytes().map(|chr| i8_type.const_int(chr as u64, false)).collect();
+        let const_str_array = i8_array_type.const_array(chars);
+        let global_str = module.add_global(&i8_array_type, &Some(const_str_array), "exit_message");
+
+        global_str.set_global_constant(1);
+        global_str.set_linkage(LLVMPrivateLinkage);
+
+        let str_ptr = self.builder.build_gep(&global_str, &vec![0, 0], "exit_message_ptr");
+        let stderr = i32_type.const_int(2, false);
+        let len = i64_type.const_int(message.len() as u64, false);
+
+        self.builder.build_call(&write_fn, &vec![stderr, str_ptr, len], "write");
+        self.builder.build_call(&exit_fn, &vec![i32_type.const_int(1, false)], "exit");
+        self.builder.build_unreachable();
+    }
+
+    // Declares one of the C library functions the generated code calls, if it isn't already
+    fn libc_function(&self, module: &Module, name: &str) -> FunctionValue {
+        if let Some(function) = module.get_function(name) {
+            return function;
+        }
+
+        let i8_ptr_type = self.context.i8_type().ptr_type(0);
+        let i32_type = self.context.i32_type();
+        let i64_type = self.context.i64_type();
+
+        let fn_type = match name {
+            "write" => i64_type.fn_type(&mut vec![i32_type, i8_ptr_type, i64_type], false),
+            "exit" => self.context.void_type().fn_type(&mut vec![i32_type], false),
+            "strlen" => i64_type.fn_type(&mut vec![i8_ptr_type], false),
+            _ => unreachable!("LLVMGenError: {} is not a known C library function", name),
+        };
+
+        module.add_function(name, fn_type)
+    }
+
+    // A pointer to a constant, nul terminated copy of some text
+    fn build_c_string(&self, module: &Module, text: &str, name: &str) -> Value {
+        let i8_type = self.context.i8_type();
+        let i8_array_type = i8_type.array_type(text.len() as u32 + 1);
+
+        let chars = text.bytes().chain(Some(0)).map(|chr| i8_type.const_int(chr as u64, false)).collect();
+        let global_str = module.add_global(&i8_array_type, &Some(i8_type.const_array(chars)), name);
+
+        global_str.set_global_constant(1);
+        global_str.set_linkage(LLVMPrivateLinkage);
+
+        self.builder.build_gep(&global_str, &vec![0, 0], name)
+    }
+
+    // The globals holding the error being raised: its type's id, message and uncaught report.
+    // The main module defines them, so other modules only declare them.
+    fn error_globals(&self, module: &Module) -> (Value, Value, Value) {
+        let i8_ptr_type = self.context.i8_type().ptr_type(0);
+        let i64_type = self.context.i64_type();
+
+        let global = |name, type_: &Type| module.get_global(name).unwrap_or_else(|| module.add_global(type_, &None, name));
+
+        (global(ERROR_KIND, &i64_type), global(ERROR_MESSAGE, &i8_ptr_type), global(ERROR_REPORT, &i8_ptr_type))
+    }
+
+    // After a call which SA found can raise, a raised error is sent on before carrying on
+    fn build_error_check(&self, module: &Module) {
+        let (kind, _, _) = self.error_globals(module);
+
+        let kind_val = self.builder.build_load(&kind, "error_kind");
+        let raised = self.builder.build_int_compare(LLVMIntNE, &kind_val, &self.context.i64_type().const_null(), "raised");
+
+        let parent_fn = self.builder.get_insert_block().get_parent();
+        let raised_block = self.context.append_basic_block(&parent_fn, "raised");
+        let ok_block = self.context.append_basic_block(&parent_fn, "not_raised");
+
+        self.builder.build_conditional_branch(&raised, &raised_block, &ok_block);
+        self.builder.position_at_end(&raised_block);
+
+        self.build_propagate_error(module);
+
+        self.builder.position_at_end(&ok_block);
+    }
+
+    // Sends the error being raised to the innermost `when` around it. Outside of one the
+    // function returns, leaving its caller to handle it, and an error leaving main ends the
+    // program with its report. Ends the current block.
+    fn build_propagate_error(&self, module: &Module) {
+        if let Some(dispatch_block) = self.error_handlers.borrow().last() {
+            self.builder.build_unconditional_branch(dispatch_block);
+            return;
+        }
+
+        let function = self.builder.get_insert_block().get_parent();
+
+        if function.as_value().get_name().to_bytes() == b"main" {
+            let (_, _, report) = self.error_globals(module);
+            let i32_type = self.context.i32_type();
+
+            let report_val = self.builder.build_load(&report, "error_report");
+            let len = self.builder.build_call(&self.libc_function(module, "strlen"), &vec![report_val], "len");
+            let stderr = i32_type.const_int(2, false);
+
+            self.builder.build_call(&self.libc_function(module, "write"), &vec![stderr, report_val, len], "write");
+            self.builder.build_call(&self.libc_function(module, "exit"), &vec![i32_type.const_int(1, false)], "exit");
+            self.builder.build_unreachable();
+
+            return;
+        }
+
+        // The caller only looks at the error, so what's returned doesn't matter
+        let return_type = function.get_return_type();
+
+        match return_type.get_kind() {
+            LLVMVoidTypeKind => self.builder.build_return(None),
+            _ => self.builder.build_return(Some(return_type.get_undef(&return_type))),
+        };
+    }
+
+    // Short circuits `and` and `or`: the rhs is only evaluated when the lhs
+    // doesn't already decide the result
+    fn generate_logical_op(&self, module: &Module, op: &InfixOp, lhs_exprwrapper: &ExprWrapper, rhs_exprwrapper: &ExprWrapper, scoped_variables: &mut HashMap<String, Value>) -> Option<Value> {
+        let mut lhs_val = match self.generate_ir(module, lhs_exprwrapper, scoped_variables) {
+            Some(val) => val,
+            None => unreachable!("LLVMGenError: InfixOp has no LHS value")
+        };
+
+        if lhs_val.is_pointer() {
+            lhs_val = self.builder.build_load(&lhs_val, "deref");
+        }
+
+        let mut lhs_block = self.builder.get_insert_block();
+        let parent_fn = lhs_block.get_parent();
+
+        let rhs_block = self.context.append_basic_block(&parent_fn, "logical_rhs");
+        let end_block = self.context.append_basic_block(&parent_fn, "logical_end");
+
+        // The value of the whole expression if the rhs is skipped
+        let mut short_circuit_val = match op {
+            &InfixOp::And => {
+                self.builder.build_conditional_branch(&lhs_val, &rhs_block, &end_block);
+                self.context.bool_type().const_int(0, false)
+            },
+            &InfixOp::Or => {
+                self.builder.build_conditional_branch(&lhs_val, &end_block, &rhs_block);
+                self.context.bool_type().const_int(1, false)
+            },
+            _ => unreachable!("LLVMGenError: {:?} is not a logical op", op)
+        };
+
+        self.builder.position_at_end(&rhs_block);
+
+        let mut rhs_val = match self.generate_ir(module, rhs_exprwrapper, scoped_variables) {
+            Some(val) => val,
+            None => unreachable!("LLVMGenError: InfixOp has no RHS value")
+        };
+
+        if rhs_val.is_pointer() {
+            rhs_val = self.builder.build_load(&rhs_val, "deref");
+        }
+
+        // The rhs may have added blocks of its own
+        let mut rhs_end_block = self.builder.get_insert_block();
+
+        self.builder.build_unconditional_branch(&end_block);
+        self.builder.position_at_end(&end_block);
+
+        let phi = self.builder.build_phi(&self.context.bool_type(), "logical");
+
+        phi.add_incoming(&mut short_circuit_val, &mut lhs_block, 1);
+        phi.add_incoming(&mut rhs_val, &mut rhs_end_block, 1);
+
+        Some(phi)
+    }
+
+    // Generate a call's arg, loading variables until it matches the param it's passed as.
+    // Varargs past the declared params are passed by value.
+    fn generate_arg(&self, module: &Module, function: &FunctionValue, index: usize, arg: &ExprWrapper, scoped_variables: &mut HashMap<String, Value>) -> Value {
+        let value = match self.generate_ir(module, arg, scoped_variables) {
+            Some(val) => val,
+            None => unreachable!("LLVMGenError: Arg has no value")
+        };
+
+        match function.get_nth_param(index as u32) {
+            Some(param) => self.load_as(value, &param.as_value().get_type()),
+            None if value.is_pointer() => self.builder.build_load(&value, "deref"),
+            None => value,
+        }
+    }
+
+    // Load through pointers until a value is of the given type, ie the value in a variable.
+    // An object is cast instead when it's used as a class it inherits from.
+    fn load_as(&self, mut value: Value, type_: &Type) -> Value {
+        while value.get_type() != *type_ && value.is_pointer() {
+            if let (LLVMStructTypeKind, LLVMPointerTypeKind) = (value.get_type().get_element_type().get_kind(), type_.get_kind()) {
+                return self.builder.build_pointer_cast(&value, type_, "upcast");
+            }
+
+            value = self.builder.build_load(&value, "deref");
+        }
+
+        value
+    }
+
+    // Lay out a class's objects and declare its methods. Its vtable holds the methods it
+    // defines or overrides, and those of its base class otherwise.
+    fn declare_class(&self, module: &Module, name: &str, base: &Option<String>, fields: &[ExprWrapper], methods: &[ExprWrapper]) {
+        let i8_ptr_type = self.context.i8_type().ptr_type(0);
+
+        let (mut class_fields, mut class_methods) = match base {
+            &Some(ref base) => {
+                let classes = self.classes.borrow();
+                let base = classes.get(base).expect("LLVMGenError: Base class was not declared before its subclass");
+
+                (base.fields.clone(), base.methods.clone())
+            },
+            &None => (Vec::new(), Vec::new()),
+        };
+
+        for field in fields {
+            if let &Expr::VarDecl(_, ref field_name, Some(ref type_string), _) = field.get_expr() {
+                let field_type = self.string_to_type(type_string, module).expect("Did not find specified type");
+
+                class_fields.push((field_name.clone(), field_type));
+            }
+        }
+
+        let mut struct_fields = vec![i8_ptr_type.ptr_type(0)];
+        struct_fields.extend(class_fields.iter().map(|&(_, field_type)| field_type));
+
+        let struct_type = self.context.struct_type(struct_fields);
+
+        // Registered before the methods are declared, as they take the class as `self`
+        self.classes.borrow_mut().insert(name.to_string(), ClassLayout {
+            struct_type: struct_type,
+            fields: class_fields,
+            methods: Vec::new(),
+            vtable: None,
+        });
+
+        for method in methods {
+            if let &Expr::FnDecl(ref fn_name, ref arg_defs, var_args, ref return_type, _) = method.get_expr() {
+                self.declare_function(module, fn_name, arg_defs, var_args, return_type);
+
+                match class_methods.iter().position(|slot| method_name(slot) == method_name(fn_name)) {
+                    Some(slot) => class_methods[slot] = fn_name.clone(),
+                    None => class_methods.push(fn_name.clone()),
+                }
+            }
+        }
+
+        let entries = class_methods.iter()
+                                   .map(|fn_name| module.get_function(fn_name).expect("LLVMGenError: Method was not declared"))
+                                   .map(|function| function.as_value().const_bit_cast(&i8_ptr_type))
+                                   .collect();
+        let vtable_type = i8_ptr_type.array_type(class_methods.len() as u32);
+        let vtable = module.add_global(&vtable_type, &Some(i8_ptr_type.const_array(entries)), &format!("{}.vtable", name));
+
+        vtable.set_global_constant(1);
+
+        if let Some(class) = self.classes.borrow_mut().get_mut(name) {
+            class.methods = class_methods;
+            class.vtable = Some(vtable);
+        }
+    }
+
+    // The type of a variable holding an object of a class
+    fn class_type(&self, name: &str) -> Option<Type> {
+        self.classes.borrow().get(name).map(|class| class.struct_type.ptr_type(0))
+    }
+
+    // Find a function in the module, or declare one from an imported module so it can be called
+    fn get_or_import_function(&self, module: &Module, name: &str) -> Option<FunctionValue> {
+        if let Some(function) = module.get_function(name) {
+            return Some(function);
+        }
+
+        self.imported_modules.iter()
+                             .filter_map(|&(_, ref imported_module)| imported_module.get_function(name))
+                             .next()
+                             .map(|function| module.add_function(name, function.get_type()))
+    }
+
+    // Add a function's signature to the module, unless its body is yet to be generated
+    fn declare_function(&self, module: &Module, name: &str, arg_defs: &[(String, String, Option<ExprWrapper>)],
+                        var_args: bool, return_type: &Option<String>) -> FunctionValue {
+        if let Some(function) = module.get_function(name) {
+            if function.get_first_basic_block().is_none() {
+                return function;
+            }
+        }
+
+        let mut arg_types: Vec<Type> = arg_defs.iter().map(|&(_, ref type_string, _)| self.string_to_type(&type_string[..], &module).expect("Did not find specified type")).collect();
+
+        let return_type = match return_type {
+            &Some(ref type_string) => self.string_to_type(&type_string[..], &module).expect("Did not find speficied type"),
+            &None => self.context.void_type(),
+        };
+
+        module.add_function(name, return_type.fn_type(&mut arg_types, var_args))
+    }
+
     fn string_to_type(&self, name: &str, module: &Module) -> Option<Type> {
         match name {
             "bool" => Some(self.context.bool_type()),
@@ -592,7 +1709,18 @@ impl LLVMGenerator {
             "i128" => Some(self.context.i128_type()),
             "u128" => Some(self.context.i128_type()),
             "void" => Some(self.context.void_type()), // TODO: Not use name "void"
-            _ => module.get_type(name),
+            _ => self.class_type(name).or_else(|| module.get_type(name)),
         }
     }
 }
+
+// Where an imported module is saved beside the main module's file, ie `main.magic.o` for `main.o`
+fn imported_module_path(path: &Path, name: &str) -> PathBuf {
+    let stem = path.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+    let file_name = match path.extension() {
+        Some(extension) => format!("{}.{}.{}", stem, name, extension.to_string_lossy()),
+        None => format!("{}.{}", stem, name),
+    };
+
+    path.with_file_name(file_name)
+}
@@ -0,0 +1,10 @@
+// The entry point of compiled limonite programs. The program's top level is
+// compiled into `limonite_main`, which is called once C has set everything up.
+
+void limonite_main(void);
+
+int main(void) {
+    limonite_main();
+
+    return 0;
+}
@@ -1,6 +1,6 @@
 extern crate llvm_sys;
 
-use codegen::llvm::core::{Builder, Context, Module, Type};
+use codegen::llvm::core::{Builder, Context, FunctionValue, Module, Type};
 use self::llvm_sys::LLVMIntPredicate; // TODO: Remove
 
 // TODO: Change to put string def in module if not already there
@@ -15,20 +15,26 @@ pub fn string_type(context: &Context) -> Type {
     context.struct_type(field_types)
 }
 
-// TODO: Move out of the string file:
-pub fn print_function_definition(builder: &Builder, context: &Context, module: &Module) {
-    // Types
+// Declares print without a body, for modules which call the one defined in another module
+pub fn print_function_declaration(context: &Context, module: &Module) -> FunctionValue {
     let void = context.void_type();
-    let i32_type = context.i32_type();
-    let i64_type = context.i64_type();
-    let i32_ptr_type = i32_type.ptr_type(0);
     let string_type_ptr = string_type(context).ptr_type(0);
 
     let mut args = vec![string_type_ptr];
 
     let fn_type = void.fn_type(&mut args, false);
 
-    let print_fn = module.add_function("print", fn_type);
+    module.add_function("print", fn_type)
+}
+
+// TODO: Move out of the string file:
+pub fn print_function_definition(builder: &Builder, context: &Context, module: &Module) {
+    // Types
+    let i32_type = context.i32_type();
+    let i64_type = context.i64_type();
+    let i32_ptr_type = i32_type.ptr_type(0);
+
+    let print_fn = print_function_declaration(context, module);
 
     let mut param = print_fn.get_first_param().expect("Print function should have at least one param");
 
@@ -0,0 +1,128 @@
+use std::fmt;
+
+use lexical::span::Span;
+
+#[derive(Copy, Debug, PartialEq, Clone)]
+pub enum Severity {
+    Error,
+    Warning,
+    Note,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", match *self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Note => "note",
+        })
+    }
+}
+
+// Marks a range of the source, with an optional message shown alongside it.
+// Primary labels point at the problem itself, secondary ones at related code.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Label {
+    span: Span,
+    message: Option<String>,
+    primary: bool,
+}
+
+impl Label {
+    pub fn new(span: Span, message: Option<String>, primary: bool) -> Label {
+        Label {
+            span: span,
+            message: message,
+            primary: primary,
+        }
+    }
+
+    pub fn get_span(&self) -> &Span {
+        &self.span
+    }
+
+    pub fn get_message(&self) -> Option<&str> {
+        self.message.as_ref().map(|s| &s[..])
+    }
+
+    pub fn is_primary(&self) -> bool {
+        self.primary
+    }
+}
+
+// Everything needed to tell the user about one problem in their code,
+// independent of how it ends up being displayed
+#[derive(Debug, PartialEq, Clone)]
+pub struct Diagnostic {
+    severity: Severity,
+    message: String,
+    labels: Vec<Label>,
+    notes: Vec<String>,
+    help: Vec<String>,
+}
+
+impl Diagnostic {
+    pub fn new(severity: Severity, message: String) -> Diagnostic {
+        Diagnostic {
+            severity: severity,
+            message: message,
+            labels: Vec::new(),
+            notes: Vec::new(),
+            help: Vec::new(),
+        }
+    }
+
+    pub fn error(message: String) -> Diagnostic {
+        Diagnostic::new(Severity::Error, message)
+    }
+
+    // Point at the offending code
+    pub fn with_primary_label(mut self, span: Span, message: Option<String>) -> Diagnostic {
+        self.labels.push(Label::new(span, message, true));
+        self
+    }
+
+    // Point at some other code which explains the problem, ie an earlier declaration
+    pub fn with_secondary_label(mut self, span: Span, message: Option<String>) -> Diagnostic {
+        self.labels.push(Label::new(span, message, false));
+        self
+    }
+
+    pub fn with_note(mut self, note: String) -> Diagnostic {
+        self.notes.push(note);
+        self
+    }
+
+    pub fn with_help(mut self, help: String) -> Diagnostic {
+        self.help.push(help);
+        self
+    }
+
+    pub fn get_severity(&self) -> Severity {
+        self.severity
+    }
+
+    pub fn get_message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn get_labels(&self) -> &[Label] {
+        &self.labels
+    }
+
+    pub fn get_notes(&self) -> &[String] {
+        &self.notes
+    }
+
+    pub fn get_help(&self) -> &[String] {
+        &self.help
+    }
+
+    // Where the problem is, which is the first primary label (or any label)
+    pub fn get_primary_span(&self) -> Option<&Span> {
+        self.labels.iter()
+            .find(|label| label.is_primary())
+            .or(self.labels.first())
+            .map(|label| label.get_span())
+    }
+}
@@ -0,0 +1,173 @@
+use std::cmp;
+
+use diagnostics::diagnostic::{Diagnostic, Label, Severity};
+
+// Tabs are shown as this many spaces so carets line up with the source
+const TAB_WIDTH: usize = 4;
+
+const RESET: &'static str = "\x1b[0m";
+const BOLD: &'static str = "\x1b[1m";
+const RED: &'static str = "\x1b[1;31m";
+const YELLOW: &'static str = "\x1b[1;33m";
+const GREEN: &'static str = "\x1b[1;32m";
+const BLUE: &'static str = "\x1b[1;34m";
+
+// Renders diagnostics against the source they came from, in the style of rustc:
+//
+// error: Incomplete while expression
+//  --> main.lim:1:9
+//   |
+// 1 | while a b
+//   |         ^ expected a comma ','
+//   |
+//   = help: Conditions end with a comma, ie `while a,`
+pub struct Emitter<'a> {
+    file_name: &'a str,
+    source: &'a str,
+    colour: bool,
+}
+
+impl<'a> Emitter<'a> {
+    pub fn new(file_name: &'a str, source: &'a str) -> Emitter<'a> {
+        Emitter {
+            file_name: file_name,
+            source: source,
+            colour: false,
+        }
+    }
+
+    // Highlight output with ANSI escape codes
+    pub fn set_colour(&mut self, colour: bool) {
+        self.colour = colour;
+    }
+
+    // Print a diagnostic to stderr
+    pub fn emit(&self, diagnostic: &Diagnostic) {
+        eprintln!("{}", self.render(diagnostic));
+    }
+
+    pub fn render(&self, diagnostic: &Diagnostic) -> String {
+        let mut output = String::new();
+        let severity_colour = match diagnostic.get_severity() {
+            Severity::Error => RED,
+            Severity::Warning => YELLOW,
+            Severity::Note => GREEN,
+        };
+
+        output.push_str(&self.paint(&diagnostic.get_severity().to_string(), severity_colour));
+        output.push_str(&self.paint(&format!(": {}", diagnostic.get_message()), BOLD));
+        output.push('\n');
+
+        // Wide enough for the biggest line number shown
+        let gutter_width = diagnostic.get_labels().iter()
+            .map(|label| label.get_span().start.line.to_string().len())
+            .max()
+            .unwrap_or(0);
+        let gutter = " ".repeat(gutter_width);
+        let bar = self.paint("|", BLUE);
+
+        if let Some(span) = diagnostic.get_primary_span() {
+            output.push_str(&format!("{}{} {}:{}:{}\n", gutter, self.paint("-->", BLUE),
+                                     self.file_name, span.start.line, span.start.column));
+            output.push_str(&format!("{} {}\n", gutter, bar));
+
+            let mut lines: Vec<u64> = diagnostic.get_labels().iter()
+                .map(|label| label.get_span().start.line)
+                .collect();
+            lines.sort();
+            lines.dedup();
+
+            for line in lines {
+                let text = self.get_line(line);
+                let line_number = format!("{:>1$}", line, gutter_width);
+
+                output.push_str(&format!("{} {} {}", self.paint(&line_number, BLUE), bar, expand_tabs(text))
+                                .trim_end());
+                output.push('\n');
+
+                let labels = diagnostic.get_labels().iter()
+                    .filter(|label| label.get_span().start.line == line);
+
+                for label in labels {
+                    let colour = if label.is_primary() { severity_colour } else { BLUE };
+
+                    output.push_str(&format!("{} {} {}\n", gutter, bar, self.underline(text, label, colour)));
+                }
+            }
+        }
+
+        if !diagnostic.get_notes().is_empty() || !diagnostic.get_help().is_empty() {
+            if diagnostic.get_primary_span().is_some() {
+                output.push_str(&format!("{} {}\n", gutter, bar));
+            }
+
+            for note in diagnostic.get_notes() {
+                output.push_str(&format!("{} {} {}\n", gutter, self.paint("= note:", BOLD), note));
+            }
+
+            for help in diagnostic.get_help() {
+                output.push_str(&format!("{} {} {}\n", gutter, self.paint("= help:", BOLD), help));
+            }
+        }
+
+        output
+    }
+
+    // Text of a (1 based) line, without its line ending
+    fn get_line(&self, line: u64) -> &'a str {
+        self.source.lines().nth((line as usize).saturating_sub(1)).unwrap_or("")
+    }
+
+    // Spaces up to the label followed by its marker and message, ie `    ^^^ expected a type`
+    fn underline(&self, text: &str, label: &Label, colour: &str) -> String {
+        let span = label.get_span();
+        let start = (span.start.column as usize).saturating_sub(1);
+
+        // Spans running onto later lines are underlined to the end of this one
+        let end = if span.end.line == span.start.line {
+            (span.end.column as usize).saturating_sub(1)
+        } else {
+            text.chars().count()
+        };
+
+        let padding = display_width(text, 0, start);
+        let length = cmp::max(display_width(text, start, end), 1);
+        let marker = if label.is_primary() { "^" } else { "-" };
+
+        let mut result = " ".repeat(padding);
+        let mut marked = marker.repeat(length);
+
+        if let Some(message) = label.get_message() {
+            marked.push(' ');
+            marked.push_str(message);
+        }
+
+        result.push_str(&self.paint(&marked, colour));
+        result
+    }
+
+    fn paint(&self, text: &str, colour: &str) -> String {
+        if self.colour {
+            format!("{}{}{}", colour, text, RESET)
+        } else {
+            text.to_string()
+        }
+    }
+}
+
+fn expand_tabs(text: &str) -> String {
+    text.replace('\t', &" ".repeat(TAB_WIDTH))
+}
+
+// How many columns the chars between `start` and `end` take up once tabs are expanded.
+// Anything past the end of the text, ie EOF, counts as a single column each.
+fn display_width(text: &str, start: usize, end: usize) -> usize {
+    let length = text.chars().count();
+    let within = text.chars()
+        .skip(start)
+        .take(end.saturating_sub(start))
+        .map(|ch| if ch == '\t' { TAB_WIDTH } else { 1 })
+        .sum::<usize>();
+
+    within + end.saturating_sub(cmp::max(start, length))
+}
@@ -0,0 +1,41 @@
+use std::fmt;
+
+use diagnostics::diagnostic::Diagnostic;
+use lexical::span::Span;
+
+// Something in the input which couldn't be turned into a token.
+// The lexer records these and carries on, handing out an `Error` token
+// in place of the bad input.
+#[derive(Debug, PartialEq, Clone)]
+pub struct LexError {
+    message: String,
+    span: Span,
+}
+
+impl LexError {
+    pub fn new(message: String, span: Span) -> LexError {
+        LexError {
+            message: message,
+            span: span,
+        }
+    }
+
+    pub fn get_message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn get_span(&self) -> &Span {
+        &self.span
+    }
+
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        Diagnostic::error(self.message.clone())
+            .with_primary_label(self.span, None)
+    }
+}
+
+impl fmt::Display for LexError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
@@ -14,6 +14,8 @@ pub enum Keywords {
     Else,
     If,
     For,
+    In,
+    By,
     While,
     Return,
     Equals,
@@ -22,6 +24,7 @@ pub enum Keywords {
     As,
     When,
     Throws,
+    Raise,
     Break,
     Continue,
     Pass
@@ -44,6 +47,8 @@ impl FromStr for Keywords {
             "else"     => Ok(Keywords::Else),
             "if"       => Ok(Keywords::If),
             "for"      => Ok(Keywords::For),
+            "in"       => Ok(Keywords::In),
+            "by"       => Ok(Keywords::By),
             "while"    => Ok(Keywords::While),
             "return"   => Ok(Keywords::Return),
             "equals"   => Ok(Keywords::Equals),
@@ -52,6 +57,7 @@ impl FromStr for Keywords {
             "as"       => Ok(Keywords::As),
             "when"     => Ok(Keywords::When),
             "throws"   => Ok(Keywords::Throws),
+            "raise"    => Ok(Keywords::Raise),
             "break"    => Ok(Keywords::Break),
             "continue" => Ok(Keywords::Continue),
             "pass"     => Ok(Keywords::Pass),
@@ -1,7 +1,9 @@
 use std::iter;
 use std::str;
 
-use lexical::tokens::Tokens;
+use lexical::error::LexError;
+use lexical::span::{Position, Span};
+use lexical::tokens::{Token, Tokens};
 use lexical::tokens::Tokens::*;
 use lexical::types::Types;
 use lexical::keywords::Keywords;
@@ -10,9 +12,12 @@ use lexical::symbols::Symbols;
 // A Lexer that keeps track of the current line and column position
 // as well as the position in the char input stream.
 pub struct Lexer<'a> {
-    line_number: usize,
-    column_number: usize,
+    source: &'a str,
+    line_number: u64,
+    column_number: u64,
+    byte_offset: usize,
     input: iter::Peekable<str::CharIndices<'a>>,
+    errors: Vec<LexError>,
 }
 
 
@@ -20,16 +25,25 @@ impl<'a> Lexer<'a> {
     // Create a new lexer instance
     pub fn new(input: &'a str) -> Lexer<'a> {
         Lexer {
+            source: input,
             line_number: 1,
             column_number: 1,
+            byte_offset: 0,
             input: input.char_indices().peekable(),
+            errors: Vec::new(),
         }
     }
 
+    // Every error found so far, in the order they were found
+    pub fn get_errors(&self) -> &[LexError] {
+        &self.errors
+    }
+
     // Gets the next char and sets the position forward in the buffer
     fn consume_char(&mut self) -> Option<char> {
         if let Some(result) = self.input.next() {
-            let (_, chr) = result;
+            let (index, chr) = result;
+            self.byte_offset = index + chr.len_utf8();
             self.column_number += 1;
 
             if chr == '\n' {
@@ -43,6 +57,11 @@ impl<'a> Lexer<'a> {
         None
     }
 
+    // The location of the next char to be consumed
+    fn current_position(&self) -> Position {
+        Position::new(self.line_number, self.column_number, self.byte_offset)
+    }
+
     fn next_char(&mut self) -> Option<char> {
         if let Some(result) = self.input.peek() {
             let &(_, chr) = result;
@@ -52,6 +71,11 @@ impl<'a> Lexer<'a> {
         None
     }
 
+    // Looks one char past `next_char`, ie to tell `1.5` from `1..5`
+    fn next_next_char(&self) -> Option<char> {
+        self.input.clone().nth(1).map(|(_, chr)| chr)
+    }
+
     fn consume_while<F: FnMut(char) -> bool>(&mut self, test: &mut F) -> String {
         let mut result = String::new();
 
@@ -67,6 +91,12 @@ impl<'a> Lexer<'a> {
         result
     }
 
+    // Skip what's left of a malformed word, ie the `zz` of `0xzz`, so it
+    // doesn't get picked up as a token of its own
+    fn skip_word(&mut self) {
+        self.consume_while(&mut |ch| ch.is_alphanumeric() || ch == '_');
+    }
+
     // Single and multi char symbols: *, -, +=, -=, ...
     fn symbols_token(&self, punc: &str) -> Tokens {
         Symbol(match punc.parse::<Symbols>() {
@@ -158,7 +188,8 @@ impl<'a> Lexer<'a> {
         let mut suffix = String::new();
 
         match self.next_char() {
-            Some('0') => {
+            // A leading 0 is only special for hex and binary, otherwise its a decimal like 0 or 0.5
+            Some('0') if self.next_next_char() == Some('x') || self.next_next_char() == Some('b') => {
                 self.consume_char();
                 match self.next_char() {
                     Some('x') => {
@@ -247,7 +278,7 @@ impl<'a> Lexer<'a> {
                             _ => ()
                         };
                     },
-                    _ => return Error(format!("Invalid number type {:?}", self.next_char())),
+                    _ => unreachable!("Lexer: Checked for a hex or binary prefix"),
                 }
             },
             _ => {
@@ -260,8 +291,8 @@ impl<'a> Lexer<'a> {
                 }));
 
                 match self.next_char() {
-                    // Float decimal point:
-                    Some('.') => {
+                    // Float decimal point, but not a range, ie 1..5:
+                    Some('.') if self.next_next_char() != Some('.') => {
                         number.push(self.consume_char().unwrap());
 
                         let fractional = self.consume_while(&mut |ch| match ch {
@@ -439,15 +470,29 @@ impl<'a> Lexer<'a> {
         };
 
         // Get the final '
-        match self.consume_char() {
-            Some('\'') => CharLiteral(ch),
-            _          => Error("Char literal was not closed with a '".to_string())
+        match self.next_char() {
+            Some('\'') => {
+                self.consume_char();
+
+                CharLiteral(ch)
+            },
+            _ => {
+                // Skip up to a closing ' on the same line, ie the rest of 'abc'
+                let rest = self.consume_while(&mut |ch| ch != '\'' && ch != '\n');
+
+                if !rest.is_empty() && self.next_char() == Some('\'') {
+                    self.consume_char();
+                }
+
+                Error("Char literal was not closed with a '".to_string())
+            }
         }
     }
 
     // This is currently set up to accept multi line strings
     fn consume_string_literal(&mut self) -> Tokens {
         let mut result = String::new();
+        let mut error = None;
 
         // Consume first "
         self.consume_char();
@@ -461,15 +506,21 @@ impl<'a> Lexer<'a> {
                 // Handle Escape chars
                 Some('\\') => {
                     if let Some(ch) = self.consume_char() {
+                        // Finish off the string so its contents aren't lexed as code
                         match Lexer::escape_char(ch) {
                             Ok(ch) => result.push(ch),
-                            Err(e) => return Error(e)
+                            Err(e) => if error.is_none() {
+                                error = Some(e)
+                            }
                         }
                     }
                 },
 
                 // End at a closing "
-                Some('\"') => return StrLiteral(result),
+                Some('\"') => return match error {
+                    Some(e) => Error(e),
+                    None    => StrLiteral(result)
+                },
                 Some(ch)   => result.push(ch),
                 None       => break
             };
@@ -480,25 +531,35 @@ impl<'a> Lexer<'a> {
 }
 
 impl<'a> Iterator for Lexer<'a> {
-    type Item = Tokens;
+    type Item = Token;
 
     // Parse the file where it left off and return the next token
     fn next(&mut self) -> Option<Self::Item> {
         self.consume_whitespace();
 
+        let start = self.current_position();
+
         let tok = match self.next_char() {
             // Find Keywords and Identifiers
             Some(a) if a.is_alphabetic() || a == '_' => self.consume_identifier(),
 
             // Find ints, floats, hex, and bin numeric values
-            Some(n) if n.is_digit(10) => self.consume_numeric(),
+            Some(n) if n.is_digit(10) => {
+                let tok = self.consume_numeric();
+
+                if let Error(_) = tok {
+                    self.skip_word();
+                }
+
+                tok
+            },
 
             // Count tabs: \n\t*
             Some('\n') => self.consume_tabs(),
 
             // Error: Found tabs without preceeding newline
             Some('\t') => {
-                self.consume_char().unwrap();
+                self.consume_while(&mut |ch| ch == '\t');
 
                 Error("Found an out of place tab.".to_string())
             },
@@ -507,7 +568,6 @@ impl<'a> Iterator for Lexer<'a> {
             Some('(') | Some(')') |
             Some('[') | Some(']') |
             Some('{') | Some('}') |
-            Some('.') |
             Some(',') |
             Some(':') |
             Some('^') |
@@ -518,6 +578,17 @@ impl<'a> Iterator for Lexer<'a> {
                 self.symbols_token(&punc)
             },
 
+            // Find ., .. or ... symbols
+            Some('.') => {
+                let mut punc = self.consume_char().unwrap().to_string();
+
+                while punc.len() < 3 && self.next_char() == Some('.') {
+                    punc.push(self.consume_char().unwrap());
+                }
+
+                self.symbols_token(&punc)
+            },
+
             // Find multi-char(+=, -=, ..) or the single-char version
             Some('+') |
             Some('*') |
@@ -579,7 +650,11 @@ impl<'a> Iterator for Lexer<'a> {
             // Find string literals, "String"
             Some('\"') => self.consume_string_literal(),
 
-            Some(ch) => Error(format!("Unknown character ({}).", ch)),
+            Some(ch) => {
+                self.consume_char();
+
+                Error(format!("Unknown character ({}).", ch))
+            },
 
             None => EOF
         };
@@ -588,10 +663,33 @@ impl<'a> Iterator for Lexer<'a> {
             return None;
         }
 
-        Some(tok)
+        let span = Span::new(start, self.current_position());
+
+        if let Error(ref msg) = tok {
+            self.errors.push(LexError::new(msg.clone(), span));
+        }
+
+        Some(Token::new(tok, span))
     }
 }
 
-pub trait Tokenizer {}
+// A source of tokens which know where in the input they came from
+pub trait Tokenizer {
+    // Get the next token and its span, or None once the input is exhausted
+    fn next_token(&mut self) -> Option<Token>;
 
-impl<'a> Tokenizer for Lexer<'a> {}
+    // The input a span covers, if the tokenizer still has it
+    fn get_source_text(&self, _span: &Span) -> Option<&str> {
+        None
+    }
+}
+
+impl<'a> Tokenizer for Lexer<'a> {
+    fn next_token(&mut self) -> Option<Token> {
+        self.next()
+    }
+
+    fn get_source_text(&self, span: &Span) -> Option<&str> {
+        self.source.get(span.start.offset..span.end.offset)
+    }
+}
@@ -1,5 +1,7 @@
+pub mod error;
 pub mod keywords;
 pub mod lexer;
+pub mod span;
 pub mod symbols;
 pub mod tokens;
 pub mod types;
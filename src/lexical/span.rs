@@ -0,0 +1,40 @@
+// A single location in the input. Lines and columns start at 1,
+// while the offset is the number of bytes from the start of the input.
+#[derive(Copy, Debug, Default, PartialEq, Clone)]
+pub struct Position {
+    pub line: u64,
+    pub column: u64,
+    pub offset: usize,
+}
+
+impl Position {
+    pub fn new(line: u64, column: u64, offset: usize) -> Position {
+        Position {
+            line: line,
+            column: column,
+            offset: offset,
+        }
+    }
+}
+
+// A range of the input. The start is inclusive and the end is exclusive,
+// so a single char token at 1:1 spans 1:1 to 1:2.
+#[derive(Copy, Debug, Default, PartialEq, Clone)]
+pub struct Span {
+    pub start: Position,
+    pub end: Position,
+}
+
+impl Span {
+    pub fn new(start: Position, end: Position) -> Span {
+        Span {
+            start: start,
+            end: end,
+        }
+    }
+
+    // Create a span covering from the start of this span to the end of another
+    pub fn to(&self, other: &Span) -> Span {
+        Span::new(self.start, other.end)
+    }
+}
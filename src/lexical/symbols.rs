@@ -9,6 +9,8 @@ pub enum Symbols {
     CBracketOpen,
     CBracketClose,
     Period,
+    DoublePeriod,
+    Ellipsis,
     Comma,
     Colon,
     Caret,
@@ -63,6 +65,8 @@ impl FromStr for Symbols {
             "/=" => Ok(Symbols::SlashEquals),
             "%=" => Ok(Symbols::PercentEquals),
             "->" => Ok(Symbols::RightThinArrow),
+            ".." => Ok(Symbols::DoublePeriod),
+            "..." => Ok(Symbols::Ellipsis),
             s    => Err(format!("Invalid Symbol Token match: {}", s))
         }
     }
@@ -1,4 +1,5 @@
 use lexical::keywords::Keywords;
+use lexical::span::Span;
 use lexical::symbols::Symbols;
 use lexical::types::Types;
 
@@ -33,3 +34,31 @@ impl Tokens {
         *self == token
     }
 }
+
+// A token along with the span of input it was created from
+#[derive(Debug, PartialEq, Clone)]
+pub struct Token {
+    token: Tokens,
+    span: Span,
+}
+
+impl Token {
+    pub fn new(token: Tokens, span: Span) -> Token {
+        Token {
+            token: token,
+            span: span,
+        }
+    }
+
+    pub fn get_token(&self) -> &Tokens {
+        &self.token
+    }
+
+    pub fn get_span(&self) -> &Span {
+        &self.span
+    }
+
+    pub fn into_token(self) -> Tokens {
+        self.token
+    }
+}
@@ -18,6 +18,23 @@ pub enum Types {
     NoneType
 }
 
+impl Types {
+    pub fn is_integer(&self) -> bool {
+        match *self {
+            Types::Int8Bit | Types::Int16Bit | Types::Int32Bit | Types::Int64Bit |
+            Types::UInt8Bit | Types::UInt16Bit | Types::UInt32Bit | Types::UInt64Bit => true,
+            _ => false
+        }
+    }
+
+    pub fn is_signed_integer(&self) -> bool {
+        match *self {
+            Types::Int8Bit | Types::Int16Bit | Types::Int32Bit | Types::Int64Bit => true,
+            _ => false
+        }
+    }
+}
+
 impl FromStr for Types {
     type Err = ();
 
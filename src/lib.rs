@@ -2,7 +2,9 @@
 extern crate log;
 extern crate env_logger;
 
+pub mod diagnostics;
 pub mod lexical;
 pub mod syntax;
 pub mod semantic;
 pub mod codegen;
+pub mod modules;
@@ -7,41 +7,65 @@ extern crate rustc_serialize;
 extern crate log;
 extern crate env_logger;
 
+use std::env;
 use std::io::{BufReader, Read};
 use std::fs::File;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use docopt::Docopt;
 
-use lexical::lexer::Lexer;
-use syntax::parser::Parser;
+use diagnostics::diagnostic::Diagnostic;
+use diagnostics::emitter::Emitter;
+use modules::loader::ModuleLoader;
 use semantic::analyzer::SemanticAnalyzer;
 use semantic::analyzer_trait::ASTAnalyzer;
 #[cfg(feature="llvm-backend")]
-use codegen::llvm::LLVMGenerator;
+use codegen::llvm::{LLVMGenerator, OutputKind};
 
+pub mod diagnostics;
 pub mod lexical;
 pub mod syntax;
 pub mod semantic;
 pub mod codegen;
+pub mod modules;
 
 static USAGE: &'static str = "\
-Usage: limonite <file>
-       limonite (-d | --dump) <file>
-       limonite (-s | --stdin)
+Usage: limonite [--colour] [-O <level>] [-o <output>] [--emit=<kind>] [--target=<triple>] [--cpu=<cpu>] <file>
+       limonite [--colour] (-d | --dump) [-O <level>] [-o <output>] [--emit=<kind>] [--target=<triple>] [--cpu=<cpu>] <file>
+       limonite [--colour] (-s | --stdin) [-O <level>] [-o <output>] [--emit=<kind>] [--target=<triple>] [--cpu=<cpu>]
        limonite (-v | --version)
 
+Imported modules are looked for next to <file>, then in each directory
+listed in the LIMONITE_PATH environment variable.
+
+The program is run straight away unless -o or --emit is given. Executables
+are linked by the system C compiler, or the one in the CC environment variable.
+They're compiled for the machine limonite is running on unless --target is given.
+
 Options:
-    -d, --dump      Dumps backend IR
-    -h, --help      Display this message
-    -s, --stdin     Read input from stdin
-    -v, --version   Displays current version
+    -c, --colour       Colour error messages
+    --cpu=<cpu>        Compile for this CPU of the target, ie skylake, rather than generic
+    -d, --dump         Dumps backend IR
+    --emit=<kind>      Compile to a file of this kind: exe, obj, asm or llvm-bc
+    -h, --help         Display this message
+    -o <output>        Compile to this file, named after <file> by default
+    -O, --opt-level=<level>
+                       Optimize from 0 (not at all, the default) to 3, ie -O2
+    -s, --stdin        Read input from stdin
+    --target=<triple>  Compile for this target, ie aarch64-unknown-linux-gnu
+    -v, --version      Displays current version
 ";
 
 #[derive(RustcDecodable)]
 struct Args {
     pub arg_file: String,
+    pub flag_colour: bool,
+    pub flag_cpu: Option<String>,
     pub flag_dump: bool,
+    pub flag_emit: Option<String>,
+    pub flag_o: Option<String>,
+    pub flag_opt_level: Option<String>,
     pub flag_stdin: bool,
+    pub flag_target: Option<String>,
     pub flag_version: bool
 }
 
@@ -58,6 +82,12 @@ fn main() {
         return println!("limonite {}", version);
     }
 
+    let file_name = if !args.flag_stdin {
+        &args.arg_file[..]
+    } else {
+        "<stdin>"
+    };
+
     let input_string = if !args.flag_stdin {
         let ref file_name = &args.arg_file;
         let path = Path::new(file_name);
@@ -71,27 +101,126 @@ fn main() {
         readable_to_string(std::io::stdin())
     };
 
-    // Tokanize the input
-    let lexer = Lexer::new(&input_string);
+    let mut search_paths = match Path::new(file_name).parent() {
+        Some(dir) if !args.flag_stdin && dir != Path::new("") => vec![dir.to_path_buf()],
+        _ => vec![PathBuf::from(".")],
+    };
+
+    if let Some(paths) = env::var_os("LIMONITE_PATH") {
+        search_paths.extend(env::split_paths(&paths));
+    }
+
+    // Tokenize & parse the input, along with every module it imports
+    let mut loader = ModuleLoader::new(search_paths);
 
-    // Parse & Build an AST
-    let mut parser = Parser::new(lexer);
+    let mut modules = match loader.load(file_name, input_string) {
+        Ok(modules) => modules,
+        Err(errors) => {
+            for error in errors {
+                emit(&loader, error.get_file_name(), args.flag_colour, &error.to_diagnostic());
+            }
 
-    let mut ast_root = match parser.parse() {
-        Some(ast) => ast,
-        None => return,
+            std::process::exit(1);
+        },
     };
 
-    // TODO: Semantic Analysis
-    let mut semantic_analyzer = SemanticAnalyzer::new();
-    semantic_analyzer.analyze(&mut ast_root);
+    // Semantic Analysis, where each module can call the functions of the modules it imports
+    let mut found_errors = false;
+
+    for i in 0..modules.len() {
+        let mut semantic_analyzer = SemanticAnalyzer::new();
+
+        for import in modules[i].get_imports() {
+            if let Some(imported) = modules.iter().find(|module| module.get_name() == import) {
+                semantic_analyzer.declare_module(imported.get_ast());
+            }
+        }
+
+        if let Err(errors) = semantic_analyzer.analyze(modules[i].get_mut_ast()) {
+            for error in errors {
+                emit(&loader, modules[i].get_file_name(), args.flag_colour, &error.to_diagnostic());
+            }
+
+            found_errors = true;
+        }
+    }
+
+    if found_errors {
+        std::process::exit(1);
+    }
 
     // Run Code Gen
     #[cfg(feature="llvm-backend")]
     {
         let mut generator = LLVMGenerator::new();
 
-        generator.add_module(ast_root, true, true);
+        match args.flag_opt_level.as_ref().map(|level| level.parse()) {
+            Some(Ok(level)) if level <= 3 => generator.set_optimization_level(level),
+            Some(_) => {
+                eprintln!("Unknown optimization level, expected 0, 1, 2 or 3");
+                std::process::exit(1);
+            },
+            None => (),
+        }
+
+        if let Some(ref triple) = args.flag_target {
+            generator.set_target_triple(triple);
+        }
+
+        if let Some(ref cpu) = args.flag_cpu {
+            generator.set_target_cpu(cpu);
+        }
+
+        // The entry file is loaded last, after everything it imports
+        let main_module = modules.pop().expect("The entry file should always be loaded");
+
+        for module in modules {
+            let name = module.get_name().to_string();
+
+            generator.set_file_name(module.get_file_name());
+            generator.add_imported_module(&name, module.into_ast());
+        }
+
+        generator.set_file_name(file_name);
+        generator.add_module(main_module.into_ast(), true, true);
+
+        if args.flag_o.is_some() || args.flag_emit.is_some() {
+            let output_kind = match args.flag_emit {
+                Some(ref kind) => kind.parse().unwrap_or_else(|_| {
+                    eprintln!("Unknown --emit kind {}, expected one of exe, obj, asm or llvm-bc", kind);
+                    std::process::exit(1);
+                }),
+                None => OutputKind::Executable,
+            };
+
+            let output = match args.flag_o {
+                Some(ref output) => PathBuf::from(output),
+                None => {
+                    let mut output = match Path::new(file_name).file_stem() {
+                        Some(stem) if !args.flag_stdin => PathBuf::from(stem),
+                        _ => PathBuf::from("main"),
+                    };
+
+                    if let Some(extension) = output_kind.get_extension() {
+                        output.set_extension(extension);
+                    }
+
+                    output
+                },
+            };
+
+            if args.flag_dump {
+                generator.dump_ir();
+            }
+
+            if let Err(msg) = generator.save_binary(&output, output_kind) {
+                eprintln!("{}", msg);
+                std::process::exit(1);
+            }
+
+            return;
+        }
+
         generator.initialize(false);
 
         if args.flag_dump {
@@ -102,6 +231,13 @@ fn main() {
     }
 }
 
+// Show a diagnostic against the source of the file it was found in
+fn emit(loader: &ModuleLoader, file_name: &str, colour: bool, diagnostic: &Diagnostic) {
+    let mut emitter = Emitter::new(file_name, loader.get_source(file_name).unwrap_or(""));
+    emitter.set_colour(colour);
+    emitter.emit(diagnostic);
+}
+
 fn readable_to_string<R: Read>(mut readable: R) -> String {
     let mut input_string = String::new();
 
@@ -0,0 +1,95 @@
+use std::fmt;
+
+use diagnostics::diagnostic::Diagnostic;
+use lexical::span::Span;
+use syntax::error::ParseError;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum ModuleErrorKind {
+    // No file on the search path matches an imported module
+    NotFound { name: String, searched: Vec<String> },
+    // The imported module's file exists, but couldn't be read
+    Unreadable { name: String, reason: String },
+    // A module which ends up importing itself
+    CircularImport { name: String },
+    // Something other than a function or import at the top of an imported module
+    TopLevelStatement { name: String },
+    // A module which didn't parse
+    Parse(ParseError),
+}
+
+impl fmt::Display for ModuleErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", match *self {
+            ModuleErrorKind::NotFound { .. } => "Module not found",
+            ModuleErrorKind::Unreadable { .. } => "Module could not be read",
+            ModuleErrorKind::CircularImport { .. } => "Circular import",
+            ModuleErrorKind::TopLevelStatement { .. } => "Statement outside of a function",
+            ModuleErrorKind::Parse(ref error) => return write!(f, "{}", error.get_kind()),
+        })
+    }
+}
+
+// An error found while loading a module, in the file it was found in
+#[derive(Debug, PartialEq, Clone)]
+pub struct ModuleError {
+    kind: ModuleErrorKind,
+    file_name: String,
+    span: Span,
+}
+
+impl ModuleError {
+    pub fn new(kind: ModuleErrorKind, file_name: String, span: Span) -> ModuleError {
+        ModuleError {
+            kind: kind,
+            file_name: file_name,
+            span: span,
+        }
+    }
+
+    pub fn get_kind(&self) -> &ModuleErrorKind {
+        &self.kind
+    }
+
+    // The file the error is in, which isn't always the module that failed to load
+    pub fn get_file_name(&self) -> &str {
+        &self.file_name
+    }
+
+    pub fn get_span(&self) -> &Span {
+        &self.span
+    }
+
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        let label = match self.kind {
+            ModuleErrorKind::Parse(ref error) => return error.to_diagnostic(),
+            ModuleErrorKind::NotFound { .. } | ModuleErrorKind::Unreadable { .. } => Some("imported here".to_string()),
+            ModuleErrorKind::CircularImport { .. } => Some("imported again here".to_string()),
+            ModuleErrorKind::TopLevelStatement { .. } => None,
+        };
+
+        let diagnostic = Diagnostic::error(self.to_string()).with_primary_label(self.span, label);
+
+        match self.kind {
+            ModuleErrorKind::NotFound { ref searched, .. } => {
+                searched.iter().fold(diagnostic, |diagnostic, path| diagnostic.with_note(format!("looked for {}", path)))
+            },
+            ModuleErrorKind::TopLevelStatement { .. } => {
+                diagnostic.with_help("Only functions and imports can be at the top of an imported module, so classes have to be declared in the file being run".to_string())
+            },
+            _ => diagnostic,
+        }
+    }
+}
+
+impl fmt::Display for ModuleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.kind {
+            ModuleErrorKind::Parse(ref error) => write!(f, "{}", error),
+            ModuleErrorKind::NotFound { ref name, .. } => write!(f, "Could not find module {}", name),
+            ModuleErrorKind::Unreadable { ref name, ref reason } => write!(f, "Could not read module {}: {}", name, reason),
+            ModuleErrorKind::CircularImport { ref name } => write!(f, "Module {} imports itself", name),
+            ModuleErrorKind::TopLevelStatement { ref name } => write!(f, "Module {} has a statement outside of a function", name),
+        }
+    }
+}
@@ -0,0 +1,315 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use lexical::lexer::Lexer;
+use lexical::span::Span;
+use modules::error::{ModuleError, ModuleErrorKind};
+use syntax::expr::{Expr, ExprWrapper};
+use syntax::parser::Parser;
+
+// Imports under this name are built into the compiler, so there's no file to load
+const BUILTIN_MODULE: &'static str = "std";
+
+// A parsed file, with its function names made unique across the whole program
+pub struct SourceModule {
+    name: String,
+    file_name: String,
+    ast: ExprWrapper,
+    imports: Vec<String>,
+}
+
+impl SourceModule {
+    // The dotted path the module was imported by, or the entry file's name without its extension
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_file_name(&self) -> &str {
+        &self.file_name
+    }
+
+    pub fn get_ast(&self) -> &ExprWrapper {
+        &self.ast
+    }
+
+    pub fn get_mut_ast(&mut self) -> &mut ExprWrapper {
+        &mut self.ast
+    }
+
+    pub fn into_ast(self) -> ExprWrapper {
+        self.ast
+    }
+
+    // Names of the modules this one uses
+    pub fn get_imports(&self) -> &[String] {
+        &self.imports
+    }
+}
+
+// Finds, parses and resolves every module a program imports.
+//
+// `use path.to.module` looks for `path/to/module.lim` in each of the search
+// paths in turn. Functions declared in an imported module are renamed after
+// the module, ie `fly` in `magical_butterflies.lim` becomes `magical_butterflies.fly`,
+// and calls through the name a module was imported as, ie `magic.fly()` after
+// `use magical_butterflies as magic`, are pointed at the renamed function.
+pub struct ModuleLoader {
+    search_paths: Vec<PathBuf>,
+    // Loaded modules, after the modules they import
+    modules: Vec<SourceModule>,
+    // Modules part way through loading, to catch circular imports
+    loading: Vec<String>,
+    sources: HashMap<String, String>,
+    errors: Vec<ModuleError>,
+}
+
+impl ModuleLoader {
+    pub fn new(search_paths: Vec<PathBuf>) -> ModuleLoader {
+        ModuleLoader {
+            search_paths: search_paths,
+            modules: Vec::new(),
+            loading: Vec::new(),
+            sources: HashMap::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    // Load a program from its entry file, returning every module it needs with
+    // each one after the modules it imports, so the entry file comes last
+    pub fn load(&mut self, file_name: &str, source: String) -> Result<Vec<SourceModule>, Vec<ModuleError>> {
+        let name = Path::new(file_name).file_stem()
+                                       .map(|stem| stem.to_string_lossy().into_owned())
+                                       .unwrap_or_else(|| file_name.to_string());
+
+        self.load_module(name, file_name.to_string(), source, true);
+
+        if self.errors.is_empty() {
+            Ok(self.modules.drain(..).collect())
+        } else {
+            Err(self.errors.drain(..).collect())
+        }
+    }
+
+    // The source of a file which has been loaded, for showing errors in it
+    pub fn get_source(&self, file_name: &str) -> Option<&str> {
+        self.sources.get(file_name).map(|source| &source[..])
+    }
+
+    fn load_module(&mut self, name: String, file_name: String, source: String, entry: bool) {
+        let (mut ast, parse_errors) = Parser::new(Lexer::new(&source)).parse_partial();
+
+        self.sources.insert(file_name.clone(), source);
+
+        if !parse_errors.is_empty() {
+            for error in parse_errors {
+                let span = *error.get_span();
+
+                self.errors.push(ModuleError::new(ModuleErrorKind::Parse(error), file_name.clone(), span));
+            }
+
+            return;
+        }
+
+        self.loading.push(name.clone());
+
+        // The name each import is referred to by, and the module it refers to
+        let mut bindings = HashMap::new();
+        let mut imports = Vec::new();
+
+        if let Expr::Block(ref exprs) = *ast.get_expr() {
+            for expr_wrapper in exprs {
+                match *expr_wrapper.get_expr() {
+                    Expr::Use(ref path, ref alias) => {
+                        if path[0] == BUILTIN_MODULE {
+                            continue;
+                        }
+
+                        let import_name = path.join(".");
+                        let binding = alias.clone().unwrap_or_else(|| path[path.len() - 1].clone());
+
+                        bindings.insert(binding, import_name.clone());
+                        imports.push(import_name.clone());
+
+                        self.load_import(import_name, path, &file_name, *expr_wrapper.get_span());
+                    },
+                    Expr::FnDecl(..) | Expr::NoOp => (),
+                    // Including classes, which other modules can't use yet as their layouts and vtables
+                    // are only known to the module declaring them
+                    _ if !entry => {
+                        self.errors.push(ModuleError::new(ModuleErrorKind::TopLevelStatement { name: name.clone() },
+                                                          file_name.clone(), *expr_wrapper.get_span()));
+                    },
+                    _ => (),
+                }
+            }
+        }
+
+        // The entry file's functions keep their names, so `main` can still be found
+        let prefix = if entry { None } else { Some(&name[..]) };
+
+        resolve_names(&mut ast, prefix, &bindings);
+
+        self.loading.pop();
+        self.modules.push(SourceModule {
+            name: name,
+            file_name: file_name,
+            ast: ast,
+            imports: imports,
+        });
+    }
+
+    fn load_import(&mut self, name: String, path: &[String], importer: &str, span: Span) {
+        if self.loading.contains(&name) {
+            self.errors.push(ModuleError::new(ModuleErrorKind::CircularImport { name: name },
+                                              importer.to_string(), span));
+            return;
+        }
+
+        let mut relative_path: PathBuf = path.iter().collect();
+        relative_path.set_extension("lim");
+
+        let candidates: Vec<PathBuf> = self.search_paths.iter().map(|dir| dir.join(&relative_path)).collect();
+
+        let found = match candidates.iter().find(|candidate| candidate.is_file()) {
+            Some(found) => found.clone(),
+            None => {
+                let searched = candidates.iter().map(|candidate| candidate.display().to_string()).collect();
+
+                self.errors.push(ModuleError::new(ModuleErrorKind::NotFound { name: name, searched: searched },
+                                                  importer.to_string(), span));
+                return;
+            }
+        };
+
+        let file_name = found.display().to_string();
+
+        // Already loaded, or failed to, for another module
+        if self.sources.contains_key(&file_name) {
+            return;
+        }
+
+        let mut source = String::new();
+
+        if let Err(e) = File::open(&found).and_then(|mut file| file.read_to_string(&mut source)) {
+            self.errors.push(ModuleError::new(ModuleErrorKind::Unreadable { name: name, reason: e.to_string() },
+                                              importer.to_string(), span));
+            return;
+        }
+
+        self.load_module(name, file_name, source, false);
+    }
+}
+
+// Prefix the names of a module's functions with the module's name, and point
+// calls at the function they refer to once every module's functions are renamed
+fn resolve_names(ast: &mut ExprWrapper, prefix: Option<&str>, bindings: &HashMap<String, String>) {
+    let mut local_functions = HashSet::new();
+
+    if let Some(prefix) = prefix {
+        if let Expr::Block(ref mut exprs) = *ast.get_mut_expr() {
+            for expr_wrapper in exprs {
+                if let Expr::FnDecl(ref mut name, ..) = *expr_wrapper.get_mut_expr() {
+                    local_functions.insert(name.clone());
+                    *name = format!("{}.{}", prefix, name);
+                }
+            }
+        }
+    }
+
+    resolve_calls(ast, prefix, &local_functions, bindings);
+}
+
+fn resolve_calls(expr_wrapper: &mut ExprWrapper, prefix: Option<&str>, local_functions: &HashSet<String>,
+                 bindings: &HashMap<String, String>) {
+    let mut resolve = |expr_wrapper: &mut ExprWrapper| resolve_calls(expr_wrapper, prefix, local_functions, bindings);
+
+    match *expr_wrapper.get_mut_expr() {
+        Expr::FnCall(ref mut name, ref mut args) => {
+            if let (Some(prefix), true) = (prefix, local_functions.contains(name)) {
+                *name = format!("{}.{}", prefix, name);
+            } else if let Some(dot) = name.rfind('.') {
+                // ie `magic.fly` where `magic` is an imported module
+                let resolved = bindings.get(&name[..dot]).map(|module| format!("{}{}", module, &name[dot..]));
+
+                if let Some(resolved) = resolved {
+                    *name = resolved;
+                }
+            }
+
+            for arg in args {
+                resolve(arg);
+            }
+        },
+        Expr::FnDecl(_, ref mut args, _, _, ref mut body) => {
+            for &mut (_, _, ref mut default) in args.iter_mut() {
+                if let Some(ref mut default) = *default {
+                    resolve(default);
+                }
+            }
+
+            resolve(body);
+        },
+        Expr::Block(ref mut exprs) |
+        Expr::Construct(_, ref mut exprs) => {
+            for expr in exprs {
+                resolve(expr);
+            }
+        },
+        Expr::ClassDecl(_, _, ref mut fields, ref mut methods) => {
+            for expr in fields.iter_mut().chain(methods.iter_mut()) {
+                resolve(expr);
+            }
+        },
+        Expr::MethodCall(ref mut object, _, ref mut args, _) => {
+            resolve(object);
+
+            for arg in args {
+                resolve(arg);
+            }
+        },
+        Expr::InfixOp(_, ref mut lhs, ref mut rhs) |
+        Expr::Assign(ref mut lhs, ref mut rhs) |
+        Expr::WhileLoop(ref mut lhs, ref mut rhs) => {
+            resolve(lhs);
+            resolve(rhs);
+        },
+        Expr::UnaryOp(_, ref mut expr) |
+        Expr::VarDecl(_, _, _, ref mut expr) |
+        Expr::Assert(ref mut expr, _, _) |
+        Expr::Return(Some(ref mut expr)) |
+        Expr::FieldAccess(ref mut expr, _, _) => resolve(expr),
+        Expr::ForLoop(_, ref mut start, ref mut end, ref mut step, ref mut body) => {
+            resolve(start);
+            resolve(end);
+
+            if let Some(ref mut step) = *step {
+                resolve(step);
+            }
+
+            resolve(body);
+        },
+        Expr::If(ref mut cond, ref mut body, ref mut else_body) => {
+            resolve(cond);
+            resolve(body);
+
+            if let Some(ref mut else_body) = *else_body {
+                resolve(else_body);
+            }
+        },
+        Expr::When(ref mut body, ref mut handlers, ref mut else_body) => {
+            resolve(body);
+
+            for &mut (_, _, ref mut handler) in handlers.iter_mut() {
+                resolve(handler);
+            }
+
+            if let Some(ref mut else_body) = *else_body {
+                resolve(else_body);
+            }
+        },
+        Expr::Literal(_) | Expr::Var(_) | Expr::Return(None) | Expr::Break | Expr::Continue |
+        Expr::Use(..) | Expr::Raise(..) | Expr::NoOp => (),
+    }
+}
@@ -1,20 +1,65 @@
-use syntax::expr::ExprWrapper;
+use syntax::expr::{Expr, ExprWrapper};
 use semantic::analyzer_trait::ASTAnalyzer;
-use semantic::type_checker::TypeChecker;
+use semantic::error::SemanticError;
+use semantic::symbol_table::Function;
+use semantic::type_checker::{raising_functions, TypeChecker};
 
-pub struct SemanticAnalyzer;
+pub struct SemanticAnalyzer {
+    errors: Vec<SemanticError>,
+    // Declared elsewhere, but callable from the code being analyzed
+    external_functions: Vec<Function>,
+    // Those of them which can raise an error
+    external_raising: Vec<String>,
+}
 
 impl SemanticAnalyzer {
     pub fn new() -> SemanticAnalyzer {
-        SemanticAnalyzer
+        SemanticAnalyzer {
+            errors: Vec::new(),
+            external_functions: Vec::new(),
+            external_raising: Vec::new(),
+        }
+    }
+
+    // Make the functions at the top of another module's AST callable, ie for an import
+    pub fn declare_module(&mut self, module_ast: &ExprWrapper) {
+        if let Expr::Block(ref exprs) = *module_ast.get_expr() {
+            self.external_functions.extend(exprs.iter().filter_map(Function::from_decl));
+
+            // The module has been checked already, so its calls to other modules are known to raise or not
+            self.external_raising.extend(raising_functions(exprs, &|_| false));
+        }
     }
 }
 
-impl ASTAnalyzer<()> for SemanticAnalyzer {
-    fn analyze(&mut self, ast_root: &mut ExprWrapper) {
+impl ASTAnalyzer<Result<(), Vec<SemanticError>>> for SemanticAnalyzer {
+    fn analyze(&mut self, ast_root: &mut ExprWrapper) -> Result<(), Vec<SemanticError>> {
         let mut type_checker = TypeChecker::new();
         // More stages
 
+        for function in self.external_functions.drain(..) {
+            type_checker.declare_function(function);
+        }
+
+        for name in self.external_raising.drain(..) {
+            type_checker.declare_raising(name);
+        }
+
         type_checker.analyze(ast_root);
+        self.errors.extend(type_checker.take_errors());
+
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(self.take_errors())
+        }
+    }
+
+    fn get_errors(&self) -> &Vec<SemanticError> {
+        &self.errors
+    }
+
+    fn get_mut_errors(&mut self) -> &mut Vec<SemanticError> {
+        &mut self.errors
     }
 }
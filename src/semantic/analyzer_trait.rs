@@ -1,6 +1,21 @@
+use lexical::span::Span;
 use syntax::expr::ExprWrapper;
+use semantic::error::{SemanticError, SemanticErrorKind};
 
 pub trait ASTAnalyzer<T> {
     fn analyze(&mut self, ast_root: &mut ExprWrapper) -> T;
-    // Data member with a Vec<Error>?
+
+    // Where the analyzer keeps the errors it has found
+    fn get_errors(&self) -> &Vec<SemanticError>;
+    fn get_mut_errors(&mut self) -> &mut Vec<SemanticError>;
+
+    // Record an error, leaving the analyzer free to carry on checking
+    fn write_error(&mut self, kind: SemanticErrorKind, message: String, span: Span) {
+        self.get_mut_errors().push(SemanticError::new(kind, message, span));
+    }
+
+    // Hand over every error found so far
+    fn take_errors(&mut self) -> Vec<SemanticError> {
+        self.get_mut_errors().drain(..).collect()
+    }
 }
@@ -0,0 +1,182 @@
+use std::fmt;
+
+use diagnostics::diagnostic::Diagnostic;
+use lexical::span::Span;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum SemanticErrorKind {
+    // Two types which should agree, ie either side of an assignment, don't
+    TypeMismatch { expected: String, found: String },
+    // A condition which doesn't evaluate to a bool
+    NonBoolCondition { found: String },
+    // A function or block returning something other than it should
+    ReturnTypeMismatch { expected: Option<String>, found: Option<String> },
+    // A type the checker doesn't understand (yet), ie custom types
+    UnsupportedType { name: String },
+    // Use of a name which isn't declared in any visible scope
+    UndeclaredVariable { name: String },
+    // A name declared twice in the same scope, or a function declared twice anywhere
+    Redeclaration { name: String, previous: Span },
+    // A name declared in a nested block which is already visible from the enclosing function
+    Shadowing { name: String, previous: Span },
+    // Assigning to a name declared with `def`
+    AssignToConstant { name: String, declared_at: Span },
+    // A range bound or step which isn't an integer
+    NonIntegerRange { found: String },
+    // A range step of 0 or less, which would never reach the end of the range
+    NonPositiveStep { found: i64 },
+    // A `break` or `continue` which isn't inside a loop
+    OutsideOfLoop { statement: String },
+    // A call to a function which isn't declared anywhere
+    UndeclaredFunction { name: String },
+    // A param or field default which isn't a constant, so can't be filled in wherever it's needed
+    NonConstantDefault { name: String },
+    // A call with more or fewer args than the function takes
+    ArgumentCountMismatch { name: String, expected: usize, found: usize, var_args: bool },
+    // Inheriting from a class which isn't declared (yet)
+    UndeclaredClass { name: String },
+    // A field or method which the object's class doesn't have
+    UnknownMember { class: String, member: String },
+    // A field or method of something which isn't an object
+    NotAnObject { found: String },
+    // A method overriding one from a base class, but with a different signature
+    OverrideMismatch { class: String, method: String },
+    // Raising or handling an error which is neither built in nor a declared class
+    UnknownErrorType { name: String },
+}
+
+impl fmt::Display for SemanticErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", match *self {
+            SemanticErrorKind::TypeMismatch { .. } => "Mismatched types",
+            SemanticErrorKind::NonBoolCondition { .. } => "Condition is not a bool",
+            SemanticErrorKind::ReturnTypeMismatch { .. } => "Mismatched return type",
+            SemanticErrorKind::UnsupportedType { .. } => "Unsupported type",
+            SemanticErrorKind::UndeclaredVariable { .. } => "Undeclared variable",
+            SemanticErrorKind::Redeclaration { .. } => "Redeclared variable",
+            SemanticErrorKind::Shadowing { .. } => "Shadowed variable",
+            SemanticErrorKind::AssignToConstant { .. } => "Assignment to a constant",
+            SemanticErrorKind::NonIntegerRange { .. } => "Range is not over integers",
+            SemanticErrorKind::NonPositiveStep { .. } => "Range step is not positive",
+            SemanticErrorKind::OutsideOfLoop { .. } => "Loop control outside of a loop",
+            SemanticErrorKind::UndeclaredFunction { .. } => "Undeclared function",
+            SemanticErrorKind::NonConstantDefault { .. } => "Default is not a constant",
+            SemanticErrorKind::ArgumentCountMismatch { .. } => "Wrong number of arguments",
+            SemanticErrorKind::UndeclaredClass { .. } => "Undeclared class",
+            SemanticErrorKind::UnknownMember { .. } => "Unknown field or method",
+            SemanticErrorKind::NotAnObject { .. } => "Not an object",
+            SemanticErrorKind::OverrideMismatch { .. } => "Mismatched override",
+            SemanticErrorKind::UnknownErrorType { .. } => "Unknown error type",
+        })
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct SemanticError {
+    kind: SemanticErrorKind,
+    message: String,
+    span: Span,
+}
+
+impl SemanticError {
+    pub fn new(kind: SemanticErrorKind, message: String, span: Span) -> SemanticError {
+        SemanticError {
+            kind: kind,
+            message: message,
+            span: span,
+        }
+    }
+
+    pub fn get_kind(&self) -> &SemanticErrorKind {
+        &self.kind
+    }
+
+    pub fn get_message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn get_span(&self) -> &Span {
+        &self.span
+    }
+
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        let label = match self.kind {
+            SemanticErrorKind::TypeMismatch { ref expected, ref found } => {
+                Some(format!("expected {}, but found {}", expected, found))
+            },
+            SemanticErrorKind::NonBoolCondition { ref found } => Some(format!("expected bool, but found {}", found)),
+            SemanticErrorKind::ReturnTypeMismatch { ref expected, ref found } => {
+                Some(format!("expected {}, but found {}", type_name(expected), type_name(found)))
+            },
+            SemanticErrorKind::UnsupportedType { .. } => None,
+            SemanticErrorKind::UndeclaredVariable { .. } => Some("not found in this scope".to_string()),
+            SemanticErrorKind::Redeclaration { ref name, .. } => Some(format!("`{}` redeclared here", name)),
+            SemanticErrorKind::Shadowing { ref name, .. } => Some(format!("`{}` shadowed here", name)),
+            SemanticErrorKind::AssignToConstant { ref name, .. } => Some(format!("cannot assign to `{}` twice", name)),
+            SemanticErrorKind::NonIntegerRange { ref found } => Some(format!("expected an integer, but found {}", found)),
+            SemanticErrorKind::NonPositiveStep { ref found } => Some(format!("expected a step of at least 1, but found {}", found)),
+            SemanticErrorKind::OutsideOfLoop { ref statement } => Some(format!("`{}` can only be used inside a loop", statement)),
+            SemanticErrorKind::UndeclaredFunction { .. } => Some("not found in this program".to_string()),
+            SemanticErrorKind::NonConstantDefault { ref name } => Some(format!("the default for `{}` isn't a constant", name)),
+            SemanticErrorKind::ArgumentCountMismatch { ref expected, ref found, ref var_args, .. } => {
+                Some(format!("expected {}{} args, but found {}", if *var_args { "at least " } else { "" }, expected, found))
+            },
+            SemanticErrorKind::UndeclaredClass { .. } => Some("not found in this program".to_string()),
+            SemanticErrorKind::UnknownMember { ref class, ref member } => Some(format!("`{}` is not a member of {}", member, class)),
+            SemanticErrorKind::NotAnObject { ref found } => Some(format!("expected an object, but found {}", found)),
+            SemanticErrorKind::OverrideMismatch { ref method, .. } => {
+                Some(format!("`{}` takes different args or returns a different type than the method it overrides", method))
+            },
+            SemanticErrorKind::UnknownErrorType { ref name } => Some(format!("`{}` is not a built in error or a class", name)),
+        };
+
+        let diagnostic = Diagnostic::error(self.to_string()).with_primary_label(self.span, label);
+
+        match self.kind {
+            // Built-ins aren't declared anywhere in the source
+            SemanticErrorKind::Redeclaration { ref name, ref previous } if *previous == Span::default() => {
+                diagnostic.with_help(format!("`{}` is built in, try another name", name))
+            },
+            SemanticErrorKind::Redeclaration { ref name, ref previous } => {
+                diagnostic.with_secondary_label(*previous, Some(format!("`{}` first declared here", name)))
+            },
+            SemanticErrorKind::Shadowing { ref name, ref previous } => {
+                diagnostic.with_secondary_label(*previous, Some(format!("`{}` first declared here", name)))
+                          .with_help("Names can't be reused within a function, try another name".to_string())
+            },
+            SemanticErrorKind::AssignToConstant { ref name, ref declared_at } => {
+                diagnostic.with_secondary_label(*declared_at, Some(format!("`{}` declared as a constant here", name)))
+                          .with_help(format!("Declare `{}` with `var` to make it mutable", name))
+            },
+            SemanticErrorKind::NonConstantDefault { .. } => {
+                diagnostic.with_help("Defaults are filled in by each call, so they can only be literals and operators on them".to_string())
+            },
+            SemanticErrorKind::UndeclaredClass { .. } => {
+                diagnostic.with_help("Base classes need to be declared before the classes which inherit from them".to_string())
+            },
+            SemanticErrorKind::UnknownErrorType { .. } => {
+                diagnostic.with_help("Errors of your own are declared as classes, ie `class TooBig` with a `pass` body".to_string())
+            },
+            _ => diagnostic,
+        }
+    }
+}
+
+// Functions without a return type are shown as returning None
+fn type_name(type_: &Option<String>) -> &str {
+    match *type_ {
+        Some(ref name) => name,
+        None => "None",
+    }
+}
+
+impl fmt::Display for SemanticError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Fall back on the kind when there's no more specific reason
+        if self.message.is_empty() {
+            write!(f, "{}", self.kind)
+        } else {
+            write!(f, "{}", self.message)
+        }
+    }
+}
@@ -1,3 +1,5 @@
 pub mod analyzer;
 pub mod analyzer_trait;
+pub mod error;
+pub mod symbol_table;
 pub mod type_checker;
@@ -0,0 +1,295 @@
+use std::collections::{HashMap, HashSet};
+
+use lexical::span::Span;
+use syntax::expr::{method_name, Expr, ExprWrapper};
+
+// Everything known about a declared name
+#[derive(Debug, PartialEq, Clone)]
+pub struct Symbol {
+    name: String,
+    type_: Option<String>,
+    const_: bool,
+    span: Span,
+}
+
+impl Symbol {
+    pub fn new(name: String, type_: Option<String>, const_: bool, span: Span) -> Symbol {
+        Symbol {
+            name: name,
+            type_: type_,
+            const_: const_,
+            span: span,
+        }
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    // None when the type couldn't be worked out, ie after an error
+    pub fn get_type(&self) -> Option<&String> {
+        self.type_.as_ref()
+    }
+
+    pub fn is_const(&self) -> bool {
+        self.const_
+    }
+
+    // Where the name was declared
+    pub fn get_span(&self) -> &Span {
+        &self.span
+    }
+}
+
+// Everything a caller needs to know about a declared function
+#[derive(Debug, PartialEq, Clone)]
+pub struct Function {
+    name: String,
+    args: Vec<(String, String, Option<ExprWrapper>)>,
+    var_args: bool,
+    return_type: Option<String>,
+    span: Span,
+}
+
+impl Function {
+    pub fn new(name: String, args: Vec<(String, String, Option<ExprWrapper>)>, var_args: bool,
+               return_type: Option<String>, span: Span) -> Function {
+        Function {
+            name: name,
+            args: args,
+            var_args: var_args,
+            return_type: return_type,
+            span: span,
+        }
+    }
+
+    // The signature of a function declaration, or None for any other expression
+    pub fn from_decl(expr_wrapper: &ExprWrapper) -> Option<Function> {
+        match *expr_wrapper.get_expr() {
+            Expr::FnDecl(ref name, ref args, var_args, ref return_type, _) => {
+                Some(Function::new(name.clone(), args.clone(), var_args, return_type.clone(), *expr_wrapper.get_span()))
+            },
+            _ => None,
+        }
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    // Each arg's name, type and default value, if it has one
+    pub fn get_args(&self) -> &[(String, String, Option<ExprWrapper>)] {
+        &self.args
+    }
+
+    // Whether any number of args may follow the declared ones
+    pub fn is_var_args(&self) -> bool {
+        self.var_args
+    }
+
+    pub fn get_return_type(&self) -> Option<&String> {
+        self.return_type.as_ref()
+    }
+
+    // Where the function was declared
+    pub fn get_span(&self) -> &Span {
+        &self.span
+    }
+}
+
+// Everything known about a declared class, including what it inherits
+#[derive(Debug, PartialEq, Clone)]
+pub struct Class {
+    name: String,
+    base: Option<String>,
+    fields: Vec<(Symbol, ExprWrapper)>,
+    methods: Vec<Function>,
+    span: Span,
+}
+
+impl Class {
+    pub fn new(name: String, base: Option<String>, fields: Vec<(Symbol, ExprWrapper)>, methods: Vec<Function>,
+               span: Span) -> Class {
+        Class {
+            name: name,
+            base: base,
+            fields: fields,
+            methods: methods,
+            span: span,
+        }
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_base(&self) -> Option<&String> {
+        self.base.as_ref()
+    }
+
+    // Each field and its default value, with the base class's fields first
+    pub fn get_fields(&self) -> &[(Symbol, ExprWrapper)] {
+        &self.fields
+    }
+
+    pub fn get_field(&self, name: &str) -> Option<&Symbol> {
+        self.fields.iter().map(|&(ref field, _)| field).find(|field| field.get_name() == name)
+    }
+
+    // Methods, including inherited ones, named after the class which defines them, ie `Shape.area`
+    pub fn get_methods(&self) -> &[Function] {
+        &self.methods
+    }
+
+    pub fn get_method(&self, name: &str) -> Option<&Function> {
+        self.methods.iter().find(|method| method_name(method.get_name()) == name)
+    }
+
+    // The constructor takes each field in order, defaulting to the field's initial value,
+    // which SA only allows to be a constant as it's filled in by each construction
+    pub fn get_constructor(&self) -> Function {
+        let args = self.fields.iter().filter_map(|&(ref field, ref default)| {
+            field.get_type().map(|type_| (field.get_name().to_string(), type_.clone(), Some(default.clone())))
+        }).collect();
+
+        Function::new(self.name.clone(), args, false, Some(self.name.clone()), self.span)
+    }
+
+    // Where the class was declared
+    pub fn get_span(&self) -> &Span {
+        &self.span
+    }
+}
+
+struct Scope {
+    symbols: HashMap<String, Symbol>,
+    // Function bodies can't see the names declared outside of them
+    function: bool,
+}
+
+// Names visible at some point in the program, as a stack of nested scopes.
+// Blocks (and so loop and if bodies) open a new scope, while function
+// scopes also hide everything declared outside of the function, the same
+// as codegen which gives each function its own set of variables.
+pub struct SymbolTable {
+    scopes: Vec<Scope>,
+    // Functions are visible everywhere, no matter where they're declared
+    functions: HashMap<String, Function>,
+    // As are classes, once they're declared
+    classes: HashMap<String, Class>,
+    // The functions (and methods, as `Class.method`) which can raise an error
+    raising: HashSet<String>,
+}
+
+impl SymbolTable {
+    pub fn new() -> SymbolTable {
+        SymbolTable {
+            scopes: vec![Scope { symbols: HashMap::new(), function: false }],
+            functions: HashMap::new(),
+            classes: HashMap::new(),
+            raising: HashSet::new(),
+        }
+    }
+
+    pub fn push_scope(&mut self) {
+        self.scopes.push(Scope { symbols: HashMap::new(), function: false });
+    }
+
+    // Start a function's scope, which holds its parameters
+    pub fn push_function_scope(&mut self) {
+        self.scopes.push(Scope { symbols: HashMap::new(), function: true });
+    }
+
+    pub fn pop_scope(&mut self) {
+        // The outermost scope lives as long as the table
+        if self.scopes.len() > 1 {
+            self.scopes.pop();
+        }
+    }
+
+    // Add a name to the innermost scope, returning what it replaced in that scope
+    pub fn declare(&mut self, symbol: Symbol) -> Option<Symbol> {
+        let scope = self.scopes.last_mut().expect("Symbol table has no scopes");
+
+        scope.symbols.insert(symbol.name.clone(), symbol)
+    }
+
+    // Find the innermost declaration of a name visible from the current scope
+    pub fn lookup(&self, name: &str) -> Option<&Symbol> {
+        for scope in self.scopes.iter().rev() {
+            if let Some(symbol) = scope.symbols.get(name) {
+                return Some(symbol);
+            }
+
+            if scope.function {
+                break;
+            }
+        }
+
+        None
+    }
+
+    // Add a function, returning any earlier function of the same name
+    pub fn declare_function(&mut self, function: Function) -> Option<Function> {
+        self.functions.insert(function.name.clone(), function)
+    }
+
+    pub fn lookup_function(&self, name: &str) -> Option<&Function> {
+        self.functions.get(name)
+    }
+
+    // Add a class, returning any earlier class of the same name
+    pub fn declare_class(&mut self, class: Class) -> Option<Class> {
+        self.classes.insert(class.name.clone(), class)
+    }
+
+    pub fn lookup_class(&self, name: &str) -> Option<&Class> {
+        self.classes.get(name)
+    }
+
+    // Whether objects of a class can be used as the other, being the same class or inheriting from it
+    pub fn is_subclass(&self, name: &str, ancestor: &str) -> bool {
+        let mut current = self.classes.get(name);
+
+        while let Some(class) = current {
+            if class.name == ancestor {
+                return true;
+            }
+
+            current = class.base.as_ref().and_then(|base| self.classes.get(base));
+        }
+
+        false
+    }
+
+    pub fn declare_raising(&mut self, name: String) {
+        self.raising.insert(name);
+    }
+
+    pub fn can_raise(&self, name: &str) -> bool {
+        self.raising.contains(name)
+    }
+
+    // Whether calling a method on an object of a class can raise. The object may really be of a
+    // subclass overriding the method, or the method may be inherited, so both are looked at
+    pub fn can_raise_method(&self, class: &str, method: &str) -> bool {
+        self.raising.iter().any(|name| match name.rfind('.') {
+            Some(dot) if &name[dot + 1..] == method => {
+                let owner = &name[..dot];
+
+                self.is_subclass(owner, class) || self.is_subclass(class, owner)
+            },
+            _ => false,
+        })
+    }
+
+    // Whether only the outermost scope is open, ie at the start of the program
+    pub fn is_global_scope(&self) -> bool {
+        self.scopes.len() == 1
+    }
+
+    // Find a name only if it was declared in the innermost scope
+    pub fn lookup_current_scope(&self, name: &str) -> Option<&Symbol> {
+        self.scopes.last().and_then(|scope| scope.symbols.get(name))
+    }
+}
@@ -1,38 +1,359 @@
 #![allow(unused_variables)]
+use std::collections::HashSet;
+use std::mem;
+
+use lexical::span::Span;
 use lexical::types::Types;
-use syntax::expr::ExprWrapper;
+use syntax::expr::{method_name, Expr, ExprWrapper};
 use syntax::expr::Expr::*;
+use syntax::literals::Literals;
+use syntax::op::UnaryOp;
 use semantic::analyzer_trait::ASTAnalyzer;
+use semantic::error::{SemanticError, SemanticErrorKind};
+use semantic::symbol_table::{Class, Function, Symbol, SymbolTable};
+
+// Errors which can be raised and handled without being declared first
+const BUILTIN_ERRORS: &'static [&'static str] = &["Error", "TypeError", "ValueError", "KeyError", "IndexError", "KeyboardInterrupt"];
 
-pub struct TypeChecker;
+pub struct TypeChecker {
+    errors: Vec<SemanticError>,
+    symbol_table: SymbolTable,
+    // How many loops the current expression is nested in, within its function
+    loop_depth: u32,
+}
 
 impl TypeChecker {
     pub fn new() -> TypeChecker {
-        TypeChecker
+        let mut symbol_table = SymbolTable::new();
+
+        // Built-ins which codegen provides
+        symbol_table.declare_function(Function::new("print".into(), vec![("str".into(), "str".into(), None)], false,
+                                                    None, Span::default()));
+
+        TypeChecker {
+            errors: Vec::new(),
+            symbol_table: symbol_table,
+            loop_depth: 0,
+        }
     }
 
-    fn cmp_lhs_rhs(lhs_str: String, rhs_str: String) -> Option<String> {
+    // Types which aren't known, ie after an earlier error, are not compared
+    // so that one mistake isn't reported over and over
+    fn cmp_lhs_rhs(&mut self, lhs: Option<String>, rhs: Option<String>, span: Span) -> Option<String> {
+        let (lhs_str, rhs_str) = match (lhs, rhs) {
+            (Some(lhs_str), Some(rhs_str)) => (lhs_str, rhs_str),
+            _ => return None,
+        };
+
         match (lhs_str.parse::<Types>(), rhs_str.parse::<Types>()) {
             (Ok(lhs_type), Ok(rhs_type)) => {
                 if lhs_type != rhs_type {
-                    panic!("Error goes here"); // FIXME: Better errors
+                    self.write_error(SemanticErrorKind::TypeMismatch { expected: lhs_str.clone(), found: rhs_str.clone() },
+                                     format!("Mismatched types {} and {}", lhs_str, rhs_str), span);
+                    return None;
                 }
 
                 Some(lhs_str)
             },
             (Err(()), Err(())) => {
-                // TODO: Custom type comparison. May or may not be an error.
-                panic!("Found a custom type references");
+                // Objects can stand in for any class they inherit from
+                if self.symbol_table.is_subclass(&rhs_str, &lhs_str) {
+                    return Some(lhs_str);
+                }
+
+                if self.symbol_table.lookup_class(&lhs_str).is_some() && self.symbol_table.lookup_class(&rhs_str).is_some() {
+                    self.write_error(SemanticErrorKind::TypeMismatch { expected: lhs_str.clone(), found: rhs_str.clone() },
+                                     format!("Mismatched types {} and {}", lhs_str, rhs_str), span);
+                    return None;
+                }
+
+                // TODO: Other custom type comparison. May or may not be an error.
+                self.write_error(SemanticErrorKind::UnsupportedType { name: lhs_str.clone() },
+                                 format!("Found a reference to the custom type {}", lhs_str), span);
+                None
             },
             // Found a builtin type and a custom type -> error
-            _ => panic!("Error goes here") // FIXME: Better errors
+            _ => {
+                self.write_error(SemanticErrorKind::TypeMismatch { expected: lhs_str.clone(), found: rhs_str.clone() },
+                                 format!("Mismatched types {} and {}", lhs_str, rhs_str), span);
+                None
+            }
+        }
+    }
+
+    // Add a name to the current scope, so long as it doesn't clash with one already visible.
+    //
+    // Declaring a name twice in one scope is an error, and so is shadowing a name from an
+    // enclosing block of the same function, since codegen keeps a single set of variables
+    // per function and the inner declaration would overwrite the outer one. A function may
+    // reuse names declared outside of it, as it can't see them anyway.
+    fn declare(&mut self, symbol: Symbol) {
+        let name = symbol.get_name().to_string();
+
+        if let Some(previous) = self.symbol_table.lookup_current_scope(&name).map(|previous| *previous.get_span()) {
+            self.write_error(SemanticErrorKind::Redeclaration { name: name.clone(), previous: previous },
+                             format!("Variable {} is already declared in this scope", name), *symbol.get_span());
+            return;
+        }
+
+        if let Some(previous) = self.symbol_table.lookup(&name).map(|previous| *previous.get_span()) {
+            self.write_error(SemanticErrorKind::Shadowing { name: name.clone(), previous: previous },
+                             format!("Variable {} shadows an earlier declaration", name), *symbol.get_span());
+            return;
+        }
+
+        self.symbol_table.declare(symbol);
+    }
+
+    // Make a function callable from the code being checked, ie one from an imported module
+    pub fn declare_function(&mut self, function: Function) {
+        self.symbol_table.declare_function(function);
+    }
+
+    // Mark a function declared elsewhere as one which can raise an error
+    pub fn declare_raising(&mut self, name: String) {
+        self.symbol_table.declare_raising(name);
+    }
+
+    // Check a call's args against the function's params, past the first `skip` params
+    // which the call fills in some other way (ie a method's `self`), giving the call's type
+    fn check_call(&mut self, function: &Function, skip: usize, args: &mut Vec<ExprWrapper>, span: Span) -> Option<String> {
+        let name = function.get_name();
+        let params = &function.get_args()[skip..];
+
+        // Trailing args left out of the call take the declaration's defaults
+        let defaults: Vec<ExprWrapper> = params.iter()
+                                               .skip(args.len())
+                                               .take_while(|&&(_, _, ref default)| default.is_some())
+                                               .filter_map(|&(_, _, ref default)| default.clone())
+                                               .collect();
+        let given = args.len();
+        args.extend(defaults);
+
+        let expected = params.len();
+
+        if args.len() < expected || (args.len() > expected && !function.is_var_args()) {
+            self.write_error(SemanticErrorKind::ArgumentCountMismatch { name: name.to_string(), expected: expected,
+                                                                        found: args.len(), var_args: function.is_var_args() },
+                             format!("Function {} takes {} args, but was given {}", name, expected, args.len()), span);
+        }
+
+        // Args past the declared ones (varargs) can be of any type. Defaults were already
+        // checked where they were declared, so only the args the call was given are
+        for (i, arg) in args.iter_mut().enumerate().take(given) {
+            let arg_type = self.analyze(arg);
+
+            if let Some(&(_, ref param_type, _)) = params.get(i) {
+                self.cmp_lhs_rhs(Some(param_type.clone()), arg_type, *arg.get_span());
+            }
+        }
+
+        function.get_return_type().cloned()
+    }
+
+    // Split `a.b.c` into the object `a.b` and its member `c`, so long as `a` is a variable.
+    // Any other dotted name is left alone, ie a function from an imported module.
+    fn split_member(&self, name: &str, span: Span) -> Option<(ExprWrapper, String)> {
+        let dot = match name.rfind('.') {
+            Some(dot) => dot,
+            None => return None,
+        };
+
+        let mut path = name[..dot].split('.');
+        let variable = path.next().unwrap_or("");
+
+        if self.symbol_table.lookup(variable).is_none() {
+            return None;
+        }
+
+        let object = path.fold(ExprWrapper::new(Var(variable.to_string()), span), |object, field| {
+            ExprWrapper::new(FieldAccess(object, field.to_string(), None), span)
+        });
+
+        Some((object, name[dot + 1..].to_string()))
+    }
+
+    // The class of an object whose members are being used. Unknown types are let through.
+    fn expect_object(&mut self, type_: Option<String>, span: Span) -> Option<Class> {
+        let type_ = match type_ {
+            Some(type_) => type_,
+            None => return None,
+        };
+
+        match self.symbol_table.lookup_class(&type_).cloned() {
+            Some(class) => Some(class),
+            None => {
+                self.write_error(SemanticErrorKind::NotAnObject { found: type_.clone() },
+                                 format!("Expected an object, but found {}", type_), span);
+                None
+            }
+        }
+    }
+
+    // Add a class's fields and methods, along with those it inherits, so that it can be
+    // used from anywhere after its declaration. Method bodies are checked separately.
+    fn declare_class(&mut self, expr_wrapper: &mut ExprWrapper) {
+        let span = *expr_wrapper.get_span();
+
+        if let ClassDecl(ref name, ref base, ref mut fields, ref methods) = *expr_wrapper.get_mut_expr() {
+            let (mut class_fields, mut class_methods) = match *base {
+                Some(ref base) => match self.symbol_table.lookup_class(base) {
+                    Some(base) => (base.get_fields().to_vec(), base.get_methods().to_vec()),
+                    None => {
+                        self.write_error(SemanticErrorKind::UndeclaredClass { name: base.clone() },
+                                         format!("Class {} inherits from the undeclared class {}", name, base), span);
+                        (Vec::new(), Vec::new())
+                    }
+                },
+                None => (Vec::new(), Vec::new()),
+            };
+
+            // Field defaults can't see any variables, the same as a function body
+            self.symbol_table.push_function_scope();
+
+            for field in fields.iter_mut() {
+                // They're passed to the constructor as param defaults, so the same goes for them
+                let constant = match *field.get_expr() {
+                    VarDecl(_, ref field_name, _, ref default) => self.expect_constant_default(field_name, default),
+                    _ => true,
+                };
+
+                if constant {
+                    self.analyze(field);
+                }
+
+                if let VarDecl(const_, ref field_name, ref type_, ref default) = *field.get_expr() {
+                    let field_span = *field.get_span();
+                    let inherited = class_fields.iter().find(|&&(ref symbol, _)| symbol.get_name() == field_name)
+                                                       .map(|&(ref symbol, _)| *symbol.get_span());
+
+                    match inherited {
+                        Some(previous) => {
+                            self.write_error(SemanticErrorKind::Redeclaration { name: field_name.clone(), previous: previous },
+                                             format!("Field {} is already declared by a base class", field_name), field_span);
+                        },
+                        None => class_fields.push((Symbol::new(field_name.clone(), type_.clone(), const_, field_span), default.clone())),
+                    }
+                }
+            }
+
+            self.symbol_table.pop_scope();
+
+            // Overriding methods take the place of the base class's in the vtable
+            for method in methods.iter().filter_map(Function::from_decl) {
+                let overridden = class_methods.iter().position(|base_method| method_name(base_method.get_name()) == method_name(method.get_name()));
+
+                match overridden {
+                    Some(slot) => {
+                        let same_signature = {
+                            let base_method = &class_methods[slot];
+                            let types = |function: &Function| function.get_args().iter().skip(1).map(|&(_, ref type_, _)| type_.clone()).collect::<Vec<_>>();
+
+                            types(base_method) == types(&method) && base_method.is_var_args() == method.is_var_args() &&
+                                base_method.get_return_type() == method.get_return_type()
+                        };
+
+                        if !same_signature {
+                            let short_name = method_name(method.get_name()).to_string();
+
+                            self.write_error(SemanticErrorKind::OverrideMismatch { class: name.clone(), method: short_name.clone() },
+                                             format!("Method {} of class {} doesn't match the method it overrides", short_name, name),
+                                             *method.get_span());
+                        }
+
+                        class_methods[slot] = method;
+                    },
+                    None => class_methods.push(method),
+                }
+            }
+
+            self.symbol_table.declare_class(Class::new(name.clone(), base.clone(), class_fields, class_methods, span));
+        }
+    }
+
+    // Unknown types are let through, but anything other than an integer is an error
+    fn expect_integer(&mut self, type_: Option<String>, span: Span) -> Option<String> {
+        match type_ {
+            Some(ref type_) if !type_.parse::<Types>().map(|t| t.is_integer()).unwrap_or(false) => {
+                self.write_error(SemanticErrorKind::NonIntegerRange { found: type_.clone() },
+                                 format!("Expected an integer range, but found {}", type_), span);
+                None
+            },
+            type_ => type_,
+        }
+    }
+
+    // A step written as a number, ie `by 0` or `by -2`, has to be positive. Steps
+    // only known when the program runs are checked then instead
+    fn expect_positive_step(&mut self, step: &ExprWrapper) {
+        fn constant(expr: &Expr) -> Option<i64> {
+            match *expr {
+                Expr::Literal(Literals::I8Num(n)) => Some(n as i64),
+                Expr::Literal(Literals::I16Num(n)) => Some(n as i64),
+                Expr::Literal(Literals::I32Num(n)) => Some(n as i64),
+                Expr::Literal(Literals::I64Num(n)) => Some(n),
+                Expr::Literal(Literals::U8Num(n)) => Some(n as i64),
+                Expr::Literal(Literals::U16Num(n)) => Some(n as i64),
+                Expr::Literal(Literals::U32Num(n)) => Some(n as i64),
+                // Any step too big for an i64 is positive, so there's nothing to check
+                Expr::Literal(Literals::U64Num(n)) if n <= i64::max_value() as u64 => Some(n as i64),
+                Expr::UnaryOp(UnaryOp::Negate, ref expr_wrapper) => constant(expr_wrapper.get_expr()).and_then(|n| n.checked_neg()),
+                _ => None,
+            }
+        }
+
+        match constant(step.get_expr()) {
+            Some(found) if found <= 0 => {
+                self.write_error(SemanticErrorKind::NonPositiveStep { found: found },
+                                 format!("Expected a positive range step, but found {}", found), *step.get_span());
+            },
+            _ => (),
+        }
+    }
+
+    // Defaults are copied into each call that leaves them out, so they can't depend on
+    // what's visible where they're declared, ie `var` names, or they'd mean something else
+    fn expect_constant_default(&mut self, name: &str, default: &ExprWrapper) -> bool {
+        if is_constant(default.get_expr()) {
+            return true;
+        }
+
+        self.write_error(SemanticErrorKind::NonConstantDefault { name: name.to_string() },
+                         format!("The default for {} isn't a constant", name), *default.get_span());
+        false
+    }
+
+    // Errors are either built in or declared as a class, so that a misspelt one is caught
+    fn expect_error_type(&mut self, name: &str, span: Span) {
+        if BUILTIN_ERRORS.contains(&name) || self.symbol_table.lookup_class(name).is_some() {
+            return;
+        }
+
+        self.write_error(SemanticErrorKind::UnknownErrorType { name: name.to_string() },
+                         format!("Unknown error type {}", name), span);
+    }
+
+    // `break` and `continue` have no loop to leave from outside of one
+    fn expect_in_loop(&mut self, statement: &str, span: Span) {
+        if self.loop_depth == 0 {
+            self.write_error(SemanticErrorKind::OutsideOfLoop { statement: statement.to_string() },
+                             format!("Found `{}` outside of a loop", statement), span);
+        }
+    }
+
+    fn expect_bool(&mut self, condition: Option<String>, span: Span) {
+        match condition {
+            Some(ref type_) if type_ != "bool" => {
+                self.write_error(SemanticErrorKind::NonBoolCondition { found: type_.clone() },
+                                 format!("Expected a bool condition, but found {}", type_), span);
+            },
+            _ => (),
         }
     }
 }
 
 impl ASTAnalyzer<Option<String>> for TypeChecker {
     fn analyze(&mut self, ast_root: &mut ExprWrapper) -> Option<String> {
-        // TODO: Handle errors, Result<ExprWrapper, String>?
         // How to get Expr's type?
         // Basics:
         // Literal -> Builtin Type (Done) or Custom Type
@@ -45,16 +366,92 @@ impl ASTAnalyzer<Option<String>> for TypeChecker {
         // if rust style let i = if a {} else {}
         // If needs lookup all returns in all possible code blocks, assert they're the same type
 
-        match *ast_root.get_mut_expr() {
+        let span = *ast_root.get_span();
+
+        // Dotted names on objects are fields and methods, and calling a class constructs an object
+        let member_access = match *ast_root.get_mut_expr() {
+            Var(ref name) => self.split_member(name, span).map(|(object, field)| FieldAccess(object, field, None)),
+            FnCall(ref name, ref mut args) if self.symbol_table.lookup_class(name).is_some() => {
+                Some(Construct(name.clone(), mem::replace(args, Vec::new())))
+            },
+            FnCall(ref name, ref mut args) => match self.split_member(name, span) {
+                Some((object, method)) => Some(MethodCall(object, method, mem::replace(args, Vec::new()), None)),
+                None => None,
+            },
+            _ => None,
+        };
+
+        if let Some(expr) = member_access {
+            *ast_root.get_mut_expr() = expr;
+        }
+
+        // Whether a call can raise, so that codegen only checks for an error after those which can
+        let mut raises = false;
+
+        let type_ = match *ast_root.get_mut_expr() {
             Assign(ref mut var_name_expr_wrapper, ref mut rhs_expr_wrapper) => {
-                let lhs_type = self.analyze(var_name_expr_wrapper).unwrap();
-                let rhs_type = self.analyze(rhs_expr_wrapper).unwrap();
+                if let Var(ref name) = *var_name_expr_wrapper.get_expr() {
+                    let declared_at = match self.symbol_table.lookup(name) {
+                        Some(symbol) if symbol.is_const() => Some(*symbol.get_span()),
+                        _ => None,
+                    };
+
+                    if let Some(declared_at) = declared_at {
+                        self.write_error(SemanticErrorKind::AssignToConstant { name: name.clone(), declared_at: declared_at },
+                                         format!("Cannot assign to the constant {}", name), span);
+                    }
+                }
+
+                let lhs_type = self.analyze(var_name_expr_wrapper);
+
+                // `def` fields are only set by the constructor
+                if let FieldAccess(_, ref field, Some(ref class)) = *var_name_expr_wrapper.get_expr() {
+                    let declared_at = match self.symbol_table.lookup_class(class).and_then(|class| class.get_field(field)) {
+                        Some(symbol) if symbol.is_const() => Some(*symbol.get_span()),
+                        _ => None,
+                    };
+
+                    if let Some(declared_at) = declared_at {
+                        self.write_error(SemanticErrorKind::AssignToConstant { name: field.clone(), declared_at: declared_at },
+                                         format!("Cannot assign to the constant field {}", field), span);
+                    }
+                }
 
-                TypeChecker::cmp_lhs_rhs(lhs_type, rhs_type)
+                let rhs_type = self.analyze(rhs_expr_wrapper);
+
+                self.cmp_lhs_rhs(lhs_type, rhs_type, span)
             },
             Block(ref mut vec) => {
                 let mut last_seen_type: Option<String> = None;
 
+                // Calls may come before the functions they call, so every function which can raise is
+                // found before anything else in the program is checked
+                if self.symbol_table.is_global_scope() {
+                    let raising = raising_functions(vec, &|name| self.symbol_table.can_raise(name));
+
+                    for name in raising {
+                        self.symbol_table.declare_raising(name);
+                    }
+                }
+
+                self.symbol_table.push_scope();
+
+                // Classes are usable from anywhere after their declaration, including
+                // functions declared earlier, so they're declared before anything else
+                for expr_wrapper in vec.iter_mut() {
+                    if let ClassDecl(..) = *expr_wrapper.get_expr() {
+                        self.declare_class(expr_wrapper);
+                    }
+                }
+
+                // Functions may be called before they're declared within a block. A name which is
+                // already taken keeps its first function, and is reported when the new one is checked
+                for function in vec.iter().filter_map(Function::from_decl) {
+                    if self.symbol_table.lookup_function(function.get_name()).is_none() {
+                        self.symbol_table.declare_function(function);
+                    }
+                }
+
                 for expr_wrapper in vec {
                     debug!("Looping over expr {:?}!", expr_wrapper);
 
@@ -66,7 +463,10 @@ impl ASTAnalyzer<Option<String>> for TypeChecker {
                         match (&last_seen_type, &opt_current_type) {
                             (&Some(ref last_type), &Some(ref current_type)) => {
                                 if last_type != current_type {
-                                    panic!("Found different types in block") // FIXME: Better errors
+                                    self.write_error(SemanticErrorKind::ReturnTypeMismatch { expected: Some(last_type.clone()),
+                                                                                             found: Some(current_type.clone()) },
+                                                     "Found different return types in block".to_string(),
+                                                     *expr_wrapper.get_span());
                                 }
 
                                 set_last_seen_type = true;
@@ -83,18 +483,86 @@ impl ASTAnalyzer<Option<String>> for TypeChecker {
                     };
                 }
 
-                None // FIXME?
+                self.symbol_table.pop_scope();
+
+                last_seen_type
             },
-            FnCall(ref fn_name, ref args) => None, // FIXME: Compare to fn declaration?
-            FnDecl(ref fn_name, ref args, ref mut ret_type, ref mut body_expr_wrapper) => {
-                for &(ref name, ref _type) in args {
+            FnCall(ref fn_name, ref mut args) => {
+                match self.symbol_table.lookup_function(fn_name).cloned() {
+                    Some(function) => {
+                        raises = self.symbol_table.can_raise(fn_name);
+                        self.check_call(&function, 0, args, span)
+                    },
+                    None => {
+                        self.write_error(SemanticErrorKind::UndeclaredFunction { name: fn_name.clone() },
+                                         format!("Call to undeclared function {}", fn_name), span);
+
+                        for arg in args.iter_mut() {
+                            self.analyze(arg);
+                        }
+
+                        None
+                    }
+                }
+            },
+            FnDecl(ref fn_name, ref mut args, var_args, ref mut ret_type, ref mut body_expr_wrapper) => {
+                for &mut (ref name, ref _type, ref mut opt_default) in args.iter_mut() {
+                    if let Some(ref mut default) = *opt_default {
+                        if self.expect_constant_default(name, default) {
+                            let default_type = self.analyze(default);
+                            self.cmp_lhs_rhs(Some(_type.clone()), default_type, *default.get_span());
+                        }
+                    }
+                }
+
+                // Declared before the body so that it can call itself
+                let function = Function::new(fn_name.clone(), args.clone(), var_args, ret_type.clone(), span);
+
+                if let Some(previous) = self.symbol_table.declare_function(function.clone()) {
+                    // Usually this function, from the block it's in declaring it up front
+                    if previous != function {
+                        let message = if *previous.get_span() == Span::default() {
+                            format!("Function {} is built in", fn_name)
+                        } else {
+                            format!("Function {} is already declared", fn_name)
+                        };
+
+                        self.write_error(SemanticErrorKind::Redeclaration { name: fn_name.clone(), previous: *previous.get_span() },
+                                         message, span);
+                        self.symbol_table.declare_function(previous);
+                    }
+                }
+
+                self.symbol_table.push_function_scope();
+
+                // A loop around a function declaration can't be left from inside it
+                let outer_loop_depth = self.loop_depth;
+                self.loop_depth = 0;
+
+                for &(ref name, ref _type, _) in args.iter() {
                     if let Err(()) = _type.parse::<Types>() {
                         // TODO: Custom type found. Figure out if it is valid
                     }
+
+                    // FIXME: Args don't have spans of their own yet
+                    self.declare(Symbol::new(name.clone(), Some(_type.clone()), false, span));
+                }
+
+                let body_type = self.analyze(body_expr_wrapper);
+
+                self.loop_depth = outer_loop_depth;
+                self.symbol_table.pop_scope();
+
+                // FIXME: Bodies whose return type isn't known can't be checked yet
+                if body_type.is_some() && *ret_type != body_type {
+                    self.write_error(SemanticErrorKind::ReturnTypeMismatch { expected: ret_type.clone(), found: body_type },
+                                     format!("Function {} doesn't return its declared type", fn_name), span);
                 }
 
-                if ret_type != &self.analyze(body_expr_wrapper) {
-                    panic!("Error goes here") // FIXME: Better errors
+                // Falling off the end would leave nothing to return
+                if ret_type.is_some() && !always_returns(body_expr_wrapper.get_expr()) {
+                    self.write_error(SemanticErrorKind::ReturnTypeMismatch { expected: ret_type.clone(), found: None },
+                                     format!("Function {} doesn't return on every path", fn_name), span);
                 }
 
                 ret_type.clone() // Better way than to clone?
@@ -102,17 +570,67 @@ impl ASTAnalyzer<Option<String>> for TypeChecker {
             If(ref mut cond_expr_wrapper, ref mut body_expr_wrapper, ref mut opt_else_expr_wrapper) => {
                 // First arg should be Opt<>? for else {} clause? Or Else could eval this to True
                 // if let Some(cond_expr_wrapper) = opt_cond_expr_wrapper {
-                if self.analyze(cond_expr_wrapper) != Some("bool".into()) {
-                    panic!("Error goes here, not a bool") // FIXME: Better errors
+                let cond_type = self.analyze(cond_expr_wrapper);
+                self.expect_bool(cond_type, *cond_expr_wrapper.get_span());
+
+                self.analyze(body_expr_wrapper);
+
+                // An `else if` is just a nested `If`
+                if let Some(ref mut else_expr_wrapper) = *opt_else_expr_wrapper {
+                    self.analyze(else_expr_wrapper);
                 }
 
                 None // FIXME
             },
+            ForLoop(ref name, ref mut start_expr_wrapper, ref mut end_expr_wrapper, ref mut opt_step_expr_wrapper, ref mut body_expr_wrapper) => {
+                let start_type = self.analyze(start_expr_wrapper);
+                let start_type = self.expect_integer(start_type, *start_expr_wrapper.get_span());
+                let end_type = self.analyze(end_expr_wrapper);
+                let end_type = self.expect_integer(end_type, *end_expr_wrapper.get_span());
+
+                let mut range_type = self.cmp_lhs_rhs(start_type, end_type, span);
+
+                if let Some(ref mut step_expr_wrapper) = *opt_step_expr_wrapper {
+                    let step_type = self.analyze(step_expr_wrapper);
+                    let step_type = self.expect_integer(step_type, *step_expr_wrapper.get_span());
+
+                    self.expect_positive_step(step_expr_wrapper);
+
+                    range_type = self.cmp_lhs_rhs(range_type, step_type, *step_expr_wrapper.get_span());
+                }
+
+                // The loop variable is only visible inside the loop
+                self.symbol_table.push_scope();
+                self.declare(Symbol::new(name.clone(), range_type, false, span));
+
+                self.loop_depth += 1;
+                self.analyze(body_expr_wrapper);
+                self.loop_depth -= 1;
+
+                self.symbol_table.pop_scope();
+
+                None
+            },
             InfixOp(ref op, ref mut lhs_expr_wrapper, ref mut rhs_expr_wrapper) => {
-                let lhs_type = self.analyze(lhs_expr_wrapper).unwrap();
-                let rhs_type = self.analyze(rhs_expr_wrapper).unwrap();
+                let lhs_type = self.analyze(lhs_expr_wrapper);
+                let rhs_type = self.analyze(rhs_expr_wrapper);
 
-                TypeChecker::cmp_lhs_rhs(lhs_type, rhs_type)
+                // `and` and `or` only work on bools
+                if op.is_logical() {
+                    self.expect_bool(lhs_type, *lhs_expr_wrapper.get_span());
+                    self.expect_bool(rhs_type, *rhs_expr_wrapper.get_span());
+
+                    Some("bool".into())
+                } else {
+                    let operand_type = self.cmp_lhs_rhs(lhs_type, rhs_type, span);
+
+                    // Comparisons are a bool no matter what's being compared
+                    if op.is_comparison() {
+                        Some("bool".into())
+                    } else {
+                        operand_type
+                    }
+                }
             },
             Literal(ref literal) => Some(literal.to_string()), // Done?
             Return(ref mut opt_ret_type) => match *opt_ret_type { // Done?
@@ -120,28 +638,344 @@ impl ASTAnalyzer<Option<String>> for TypeChecker {
                 None => Some("None".into()) // None type?
             },
             UnaryOp(ref op, ref mut expr_wrapper) => self.analyze(expr_wrapper),
-            Var(ref name) => unimplemented!(), // FIXME: Lookup VarDecl type
+            Var(ref name) => match self.symbol_table.lookup(name) {
+                Some(symbol) => symbol.get_type().cloned(),
+                None => {
+                    self.write_error(SemanticErrorKind::UndeclaredVariable { name: name.clone() },
+                                     format!("Use of undeclared variable {}", name), span);
+                    None
+                }
+            },
             VarDecl(ref const_, ref name, ref mut opt_type, ref mut expr_wrapper) => {
-                let rhs_type = self.analyze(expr_wrapper).unwrap();
+                // The rhs is checked first, so `var a = a` can't see itself
+                let rhs_type = self.analyze(expr_wrapper);
 
-                match *opt_type {
-                    Some(ref lhs_type) => TypeChecker::cmp_lhs_rhs(lhs_type.clone(), rhs_type),  // No way to not clone?
+                let result = match *opt_type {
+                    Some(ref lhs_type) => self.cmp_lhs_rhs(Some(lhs_type.clone()), rhs_type, span),  // No way to not clone?
                     None => {
-                        *opt_type = Some(rhs_type); // Done?
+                        *opt_type = rhs_type; // Done?
 
                         None
                     }
-                }
+                };
+
+                self.declare(Symbol::new(name.clone(), opt_type.clone(), *const_, span));
+
+                result
             },
             WhileLoop(ref mut cond_expr_wrapper, ref mut body_expr_wrapper) => {
-                if self.analyze(cond_expr_wrapper) != Some("bool".into()) {
-                    panic!("Error goes here, not a bool") // FIXME: Better errors
-                }
+                let cond_type = self.analyze(cond_expr_wrapper);
+                self.expect_bool(cond_type, *cond_expr_wrapper.get_span());
+
+                self.loop_depth += 1;
 
                 // WhileLoop's type, if any, is the type of the body
-                self.analyze(body_expr_wrapper)
+                let body_type = self.analyze(body_expr_wrapper);
+
+                self.loop_depth -= 1;
+
+                body_type
+            },
+            Assert(ref mut cond_expr_wrapper, _, _) => {
+                let cond_type = self.analyze(cond_expr_wrapper);
+                self.expect_bool(cond_type, *cond_expr_wrapper.get_span());
+
+                None
+            },
+            Break => {
+                self.expect_in_loop("break", span);
+                None
+            },
+            Continue => {
+                self.expect_in_loop("continue", span);
+                None
+            },
+            ClassDecl(_, _, _, ref mut methods) => {
+                // The class itself was declared by the block it's in
+                for method in methods.iter_mut() {
+                    self.analyze(method);
+                }
+
+                None
+            },
+            Construct(ref class_name, ref mut args) => {
+                let constructor = self.symbol_table.lookup_class(class_name).map(Class::get_constructor);
+
+                match constructor {
+                    Some(constructor) => self.check_call(&constructor, 0, args, span),
+                    None => {
+                        self.write_error(SemanticErrorKind::UndeclaredClass { name: class_name.clone() },
+                                         format!("Construction of the undeclared class {}", class_name), span);
+                        None
+                    }
+                }
             },
+            FieldAccess(ref mut object, ref field, ref mut opt_class) => {
+                let object_type = self.analyze(object);
+
+                match self.expect_object(object_type, *object.get_span()) {
+                    Some(class) => {
+                        *opt_class = Some(class.get_name().to_string());
+
+                        match class.get_field(field) {
+                            Some(symbol) => symbol.get_type().cloned(),
+                            None => {
+                                self.write_error(SemanticErrorKind::UnknownMember { class: class.get_name().to_string(), member: field.clone() },
+                                                 format!("Class {} has no field {}", class.get_name(), field), span);
+                                None
+                            }
+                        }
+                    },
+                    None => None,
+                }
+            },
+            MethodCall(ref mut object, ref member, ref mut args, ref mut opt_class) => {
+                let object_type = self.analyze(object);
+                let class = self.expect_object(object_type, *object.get_span());
+
+                let method = match class {
+                    Some(ref class) => {
+                        *opt_class = Some(class.get_name().to_string());
+
+                        match class.get_method(member).cloned() {
+                            Some(method) => Some(method),
+                            None => {
+                                self.write_error(SemanticErrorKind::UnknownMember { class: class.get_name().to_string(), member: member.clone() },
+                                                 format!("Class {} has no method {}", class.get_name(), member), span);
+                                None
+                            }
+                        }
+                    },
+                    None => None,
+                };
+
+                match method {
+                    // The object is passed as the method's `self`
+                    Some(method) => {
+                        raises = class.map_or(true, |class| self.symbol_table.can_raise_method(class.get_name(), member));
+                        self.check_call(&method, 1, args, span)
+                    },
+                    None => {
+                        for arg in args.iter_mut() {
+                            self.analyze(arg);
+                        }
+
+                        None
+                    }
+                }
+            },
+            When(ref mut body_expr_wrapper, ref mut handlers, ref mut opt_else_expr_wrapper) => {
+                self.analyze(body_expr_wrapper);
+
+                for &mut (ref error_types, ref binding, ref mut handler_expr_wrapper) in handlers.iter_mut() {
+                    // FIXME: Handlers don't have spans of their own yet
+                    for error_type in error_types {
+                        self.expect_error_type(error_type, span);
+                    }
+
+                    // The error's message is only visible inside its handler
+                    self.symbol_table.push_scope();
+
+                    if let Some(ref name) = *binding {
+                        self.declare(Symbol::new(name.clone(), Some("str".into()), true, span));
+                    }
+
+                    self.analyze(handler_expr_wrapper);
+                    self.symbol_table.pop_scope();
+                }
+
+                if let Some(ref mut else_expr_wrapper) = *opt_else_expr_wrapper {
+                    self.analyze(else_expr_wrapper);
+                }
+
+                None
+            },
+            Raise(ref error_type, _) => {
+                self.expect_error_type(error_type, span);
+                None
+            },
+            Use(..) => None,
             NoOp => None,
+        };
+
+        // Codegen needs some of what SA works out, ie whether integers are compared signed
+        ast_root.set_type(type_.clone());
+        ast_root.set_can_raise(raises);
+
+        type_
+    }
+
+    fn get_errors(&self) -> &Vec<SemanticError> {
+        &self.errors
+    }
+
+    fn get_mut_errors(&mut self) -> &mut Vec<SemanticError> {
+        &mut self.errors
+    }
+}
+
+// Literals, and operators on them, which mean the same thing wherever they're used
+fn is_constant(expr: &Expr) -> bool {
+    match *expr {
+        Expr::Literal(_) => true,
+        Expr::UnaryOp(_, ref expr_wrapper) => is_constant(expr_wrapper.get_expr()),
+        Expr::InfixOp(_, ref lhs, ref rhs) => is_constant(lhs.get_expr()) && is_constant(rhs.get_expr()),
+        _ => false,
+    }
+}
+
+// Whether running the expression always leaves the function, through a return or a raised error
+fn always_returns(expr: &Expr) -> bool {
+    match *expr {
+        Expr::Return(_) | Expr::Raise(..) => true,
+        Expr::Block(ref exprs) => exprs.iter().any(|expr_wrapper| always_returns(expr_wrapper.get_expr())),
+        Expr::If(_, ref body, Some(ref else_body)) => {
+            always_returns(body.get_expr()) && always_returns(else_body.get_expr())
+        },
+        // The else block only runs if the body finished without raising, and any handler may run instead
+        Expr::When(ref body, ref handlers, ref opt_else) => {
+            let finishes = always_returns(body.get_expr()) ||
+                           opt_else.as_ref().map_or(false, |else_body| always_returns(else_body.get_expr()));
+
+            finishes && handlers.iter().all(|&(_, _, ref handler)| always_returns(handler.get_expr()))
+        },
+        // `while True` never finishes unless it's left with a break
+        Expr::WhileLoop(ref condition, ref body) => {
+            *condition.get_expr() == Expr::Literal(Literals::Bool(true)) && !breaks(body.get_expr())
+        },
+        // Other loops may run zero times, or be left with a break
+        _ => false,
+    }
+}
+
+// Whether the expression can break out of the loop it's in, rather than one nested inside it
+fn breaks(expr: &Expr) -> bool {
+    match *expr {
+        Expr::Break => true,
+        Expr::Block(ref exprs) => exprs.iter().any(|expr_wrapper| breaks(expr_wrapper.get_expr())),
+        Expr::If(_, ref body, ref opt_else) => {
+            breaks(body.get_expr()) || opt_else.as_ref().map_or(false, |else_body| breaks(else_body.get_expr()))
+        },
+        Expr::When(ref body, ref handlers, ref opt_else) => {
+            breaks(body.get_expr()) ||
+            handlers.iter().any(|&(_, _, ref handler)| breaks(handler.get_expr())) ||
+            opt_else.as_ref().map_or(false, |else_body| breaks(else_body.get_expr()))
+        },
+        _ => false,
+    }
+}
+
+// A function's part in working out which functions can raise: whether it raises an error
+// itself, and the names of the functions it calls
+struct CallInfo {
+    name: String,
+    raises: bool,
+    callees: Vec<String>,
+}
+
+// The functions declared in some code which can raise an error, because they raise one themselves
+// or call a function which can. `known` says whether a function declared elsewhere can.
+pub fn raising_functions(exprs: &[ExprWrapper], known: &Fn(&str) -> bool) -> HashSet<String> {
+    let mut functions = Vec::new();
+    let mut raises = false;
+    let mut callees = Vec::new();
+
+    // Only the functions found matter, not the code around them
+    for expr_wrapper in exprs {
+        find_calls(expr_wrapper, &mut raises, &mut callees, &mut functions);
+    }
+
+    let mut raising: HashSet<String> = functions.iter().filter(|info| info.raises).map(|info| info.name.clone()).collect();
+
+    // Calls can go through any number of functions, so keep going until nothing else can raise
+    loop {
+        let found: Vec<String> = functions.iter()
+                                          .filter(|info| !raising.contains(&info.name))
+                                          .filter(|info| info.callees.iter().any(|callee| {
+                                              known(callee) || raising.iter().any(|name| may_call(callee, name))
+                                          }))
+                                          .map(|info| info.name.clone())
+                                          .collect();
+
+        if found.is_empty() {
+            return raising;
         }
+
+        raising.extend(found);
+    }
+}
+
+// Whether a call by name can end up in a function. Which class a method is called on isn't known
+// before SA, so methods (and dotted names, which may be methods) only go by the method's name
+fn may_call(callee: &str, function: &str) -> bool {
+    callee == function || (callee.contains('.') && function.contains('.') && method_name(callee) == method_name(function))
+}
+
+fn find_calls(expr_wrapper: &ExprWrapper, raises: &mut bool, callees: &mut Vec<String>, functions: &mut Vec<CallInfo>) {
+    match *expr_wrapper.get_expr() {
+        Raise(..) => *raises = true,
+        FnCall(ref name, ref args) => {
+            // Calls SA has already checked, ie in an imported module, are known to raise or not
+            *raises |= expr_wrapper.can_raise();
+            callees.push(name.clone());
+
+            for arg in args {
+                find_calls(arg, raises, callees, functions);
+            }
+        },
+        MethodCall(ref object, ref method, ref args, _) => {
+            *raises |= expr_wrapper.can_raise();
+            callees.push(format!(".{}", method));
+
+            for expr_wrapper in Some(object).into_iter().chain(args) {
+                find_calls(expr_wrapper, raises, callees, functions);
+            }
+        },
+        FnDecl(ref name, _, _, _, ref body) => {
+            let mut info = CallInfo { name: name.clone(), raises: false, callees: Vec::new() };
+
+            find_calls(body, &mut info.raises, &mut info.callees, functions);
+            functions.push(info);
+        },
+        InfixOp(_, ref lhs, ref rhs) | WhileLoop(ref lhs, ref rhs) | Assign(ref lhs, ref rhs) => {
+            find_calls(lhs, raises, callees, functions);
+            find_calls(rhs, raises, callees, functions);
+        },
+        UnaryOp(_, ref expr) | VarDecl(_, _, _, ref expr) | Assert(ref expr, ..) | FieldAccess(ref expr, ..) => {
+            find_calls(expr, raises, callees, functions);
+        },
+        ForLoop(_, ref start, ref end, ref opt_step, ref body) => {
+            for expr_wrapper in vec![start, end, body].into_iter().chain(opt_step) {
+                find_calls(expr_wrapper, raises, callees, functions);
+            }
+        },
+        If(ref cond, ref body, ref opt_else) => {
+            for expr_wrapper in vec![cond, body].into_iter().chain(opt_else) {
+                find_calls(expr_wrapper, raises, callees, functions);
+            }
+        },
+        When(ref body, ref handlers, ref opt_else) => {
+            let handler_bodies = handlers.iter().map(|&(_, _, ref handler)| handler);
+
+            for expr_wrapper in Some(body).into_iter().chain(handler_bodies).chain(opt_else) {
+                find_calls(expr_wrapper, raises, callees, functions);
+            }
+        },
+        Block(ref exprs) | Construct(_, ref exprs) => {
+            for expr_wrapper in exprs {
+                find_calls(expr_wrapper, raises, callees, functions);
+            }
+        },
+        ClassDecl(_, _, ref fields, ref methods) => {
+            for expr_wrapper in fields.iter().chain(methods) {
+                find_calls(expr_wrapper, raises, callees, functions);
+            }
+        },
+        Return(ref opt_expr) => {
+            if let Some(ref expr) = *opt_expr {
+                find_calls(expr, raises, callees, functions);
+            }
+        },
+        Literal(_) | Var(_) | Break | Continue | Use(..) | NoOp => (),
     }
 }
@@ -0,0 +1,123 @@
+use std::fmt;
+
+use diagnostics::diagnostic::Diagnostic;
+use lexical::span::Span;
+use lexical::tokens::Tokens;
+
+#[derive(Copy, Debug, PartialEq, Clone)]
+pub enum ParseErrorKind {
+    // Found a token other than the one that was expected
+    UnexpectedToken,
+    // Found nothing where an expression was required
+    MissingExpression,
+    // A line is indented more or less than its block allows
+    InvalidIndentation,
+    // A statement began on a line which already had one
+    MultipleStatementsPerLine,
+    // A keyword that the parser doesn't handle (yet)
+    UnsupportedKeyword,
+    // The lexer could not make sense of some input
+    Lexical,
+}
+
+impl fmt::Display for ParseErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", match *self {
+            ParseErrorKind::UnexpectedToken => "Unexpected token",
+            ParseErrorKind::MissingExpression => "Missing expression",
+            ParseErrorKind::InvalidIndentation => "Invalid indentation",
+            ParseErrorKind::MultipleStatementsPerLine => "Multiple statements on one line",
+            ParseErrorKind::UnsupportedKeyword => "Unsupported keyword",
+            ParseErrorKind::Lexical => "Invalid input",
+        })
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct ParseError {
+    kind: ParseErrorKind,
+    message: String,
+    span: Span,
+    expected: Option<String>,
+    found: Option<Tokens>,
+    hint: Option<String>,
+}
+
+impl ParseError {
+    pub fn new(kind: ParseErrorKind, message: String, span: Span, expected: Option<String>,
+               found: Option<Tokens>, hint: Option<String>) -> ParseError {
+        ParseError {
+            kind: kind,
+            message: message,
+            span: span,
+            expected: expected,
+            found: found,
+            hint: hint,
+        }
+    }
+
+    pub fn get_kind(&self) -> ParseErrorKind {
+        self.kind
+    }
+
+    pub fn get_message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn get_span(&self) -> &Span {
+        &self.span
+    }
+
+    pub fn get_expected(&self) -> Option<&str> {
+        self.expected.as_ref().map(|s| &s[..])
+    }
+
+    pub fn get_found(&self) -> Option<&Tokens> {
+        self.found.as_ref()
+    }
+
+    pub fn get_hint(&self) -> Option<&str> {
+        self.hint.as_ref().map(|s| &s[..])
+    }
+
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        let message = if self.message.is_empty() {
+            self.kind.to_string()
+        } else {
+            self.message.clone()
+        };
+
+        // What was expected goes under the offending token
+        let label = match (&self.expected, &self.found) {
+            (&Some(ref expected), &Some(ref found)) => Some(format!("expected {}, but found {:?}", expected, found)),
+            (&Some(ref expected), &None) => Some(format!("expected {}", expected)),
+            (&None, &Some(ref found)) => Some(format!("found {:?}", found)),
+            (&None, &None) => None,
+        };
+
+        let diagnostic = Diagnostic::error(message).with_primary_label(self.span, label);
+
+        match self.hint {
+            Some(ref hint) => diagnostic.with_help(hint.clone()),
+            None => diagnostic,
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Fall back on the kind when there's no more specific reason
+        if self.message.is_empty() {
+            write!(f, "{}", self.kind)?;
+        } else {
+            write!(f, "{}", self.message)?;
+        }
+
+        match (&self.expected, &self.found) {
+            (&Some(ref expected), &Some(ref found)) => write!(f, ". Expected {}, but found {:?}", expected, found),
+            (&Some(ref expected), &None) => write!(f, ". Expected {}", expected),
+            (&None, &Some(ref found)) => write!(f, ". Found {:?}", found),
+            (&None, &None) => Ok(()),
+        }
+    }
+}
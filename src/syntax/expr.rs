@@ -1,36 +1,36 @@
 use std::fmt;
 
+use lexical::span::Span;
 use syntax::literals::*;
 use syntax::op::*;
 
-#[derive(PartialEq)]
+#[derive(Clone)]
 pub struct ExprWrapper {
     expr: Box<Expr>,
-    start_line: u64,
-    start_column: u64,
-    end_line: u64,
-    end_column: u64
+    span: Span,
+    // The expression's type, once SA has worked it out
+    type_: Option<String>,
+    // Whether a call can raise an error, which SA also works out
+    raises: bool,
 }
 
 impl ExprWrapper {
-    // Create an associated expression with start and end positions
-    pub fn new(expr: Expr, startl: u64, startc: u64, endl: u64, endc: u64) -> ExprWrapper {
+    // Create an associated expression with the span of source it was parsed from
+    pub fn new(expr: Expr, span: Span) -> ExprWrapper {
         ExprWrapper {
             expr: Box::new(expr),
-            start_line: startl,
-            start_column: startc,
-            end_line: endl,
-            end_column: endc
+            span: span,
+            type_: None,
+            raises: false,
         }
     }
 
     pub fn default(expr: Expr) -> ExprWrapper {
         ExprWrapper {
             expr: Box::new(expr),
-            start_line: 0,
-            start_column: 0,
-            end_line: 0,
-            end_column: 0,
+            span: Span::default(),
+            type_: None,
+            raises: false,
         }
     }
 
@@ -41,6 +41,49 @@ impl ExprWrapper {
     pub fn get_mut_expr(&mut self) -> &mut Expr {
         &mut self.expr
     }
+
+    pub fn get_span(&self) -> &Span {
+        &self.span
+    }
+
+    pub fn get_type(&self) -> Option<&String> {
+        self.type_.as_ref()
+    }
+
+    pub fn set_type(&mut self, type_: Option<String>) {
+        self.type_ = type_;
+    }
+
+    pub fn can_raise(&self) -> bool {
+        self.raises
+    }
+
+    pub fn set_can_raise(&mut self, raises: bool) {
+        self.raises = raises;
+    }
+
+    pub fn get_start_line(&self) -> u64 {
+        self.span.start.line
+    }
+
+    pub fn get_start_column(&self) -> u64 {
+        self.span.start.column
+    }
+
+    pub fn get_end_line(&self) -> u64 {
+        self.span.end.line
+    }
+
+    pub fn get_end_column(&self) -> u64 {
+        self.span.end.column
+    }
+}
+
+// Like Debug, equality only looks at the expression and not where it came from or what SA found
+impl PartialEq for ExprWrapper {
+    fn eq(&self, other: &ExprWrapper) -> bool {
+        self.expr == other.expr
+    }
 }
 
 impl fmt::Debug for ExprWrapper {
@@ -49,7 +92,7 @@ impl fmt::Debug for ExprWrapper {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub enum Expr {
     // Operations between two expressions
     InfixOp(InfixOp, ExprWrapper, ExprWrapper),
@@ -59,14 +102,16 @@ pub enum Expr {
     Literal(Literals),
     // While conditional is true, run expression
     WhileLoop(ExprWrapper, ExprWrapper),
+    // Run expression with a variable counting from start up to (not including) end, by an optional step
+    ForLoop(String, ExprWrapper, ExprWrapper, Option<ExprWrapper>, ExprWrapper),
     // If condition true, run expression, optional elif, else
     If(ExprWrapper, ExprWrapper, Option<ExprWrapper>),
     // Assign an expression to an existing variable
     Assign(ExprWrapper, ExprWrapper), // REVIEW: Shouldn't the first param just be a string?
     // Fn call with name and args.
     FnCall(String, Vec<ExprWrapper>),
-    // Declare a function with a name, args(name, type), return type, and body expr
-    FnDecl(String, Vec<(String, String)>, Option<String>, ExprWrapper),
+    // Declare a function with a name, args(name, type, default), whether it takes varargs, return type, and body expr
+    FnDecl(String, Vec<(String, String, Option<ExprWrapper>)>, bool, Option<String>, ExprWrapper),
     // Run consecutive expressions
     Block(Vec<ExprWrapper>),
     // Const declaration?, variable name, type(optional in parser but not SA), and expression
@@ -75,6 +120,32 @@ pub enum Expr {
     Var(String),
     // Return an expression from a function
     Return(Option<ExprWrapper>),
+    // Leave the innermost loop
+    Break,
+    // Skip to the next iteration of the innermost loop
+    Continue,
+    // Stop the program if the condition is false, with an optional message and the condition's source
+    Assert(ExprWrapper, Option<String>, String),
+    // Import a module by its dotted path, with an optional name to refer to it by
+    Use(Vec<String>, Option<String>),
+    // Declare a class with a name, optional base class, fields (as VarDecls) and methods (as FnDecls named `Class.method`)
+    ClassDecl(String, Option<String>, Vec<ExprWrapper>, Vec<ExprWrapper>),
+    // Create an object of a class, with values for its fields in the order they're declared
+    Construct(String, Vec<ExprWrapper>),
+    // Object, field name, and the object's class (filled in by SA)
+    FieldAccess(ExprWrapper, String, Option<String>),
+    // Object, method name, args, and the object's class (filled in by SA)
+    MethodCall(ExprWrapper, String, Vec<ExprWrapper>, Option<String>),
+    // Run a block, handing an error raised in it to the first handler (error types, optional name for its
+    // message, body) naming the error's type, or running the optional else block if nothing was raised
+    When(ExprWrapper, Vec<(Vec<String>, Option<String>, ExprWrapper)>, Option<ExprWrapper>),
+    // Raise an error of a type, with an optional message
+    Raise(String, Option<String>),
     // Does nothing
     NoOp,
 }
+
+// A method's name without its class, ie `area` for `Shape.area`
+pub fn method_name(name: &str) -> &str {
+    name.rsplit('.').next().unwrap_or(name)
+}
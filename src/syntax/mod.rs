@@ -1,5 +1,5 @@
+pub mod error;
 pub mod expr;
 pub mod literals;
 pub mod op;
 pub mod parser;
-
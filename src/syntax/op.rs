@@ -1,4 +1,4 @@
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub enum InfixOp {
     // A + B
     Add,
@@ -14,6 +14,10 @@ pub enum InfixOp {
     Pow,
     // A equals B (traditionally A == B)
     Equ,
+    // A not equals B (traditionally A != B)
+    NotEqu,
+    // A is B, whether both are the same thing
+    Is,
     // A < B
     Lt,
     // A <= B
@@ -22,6 +26,10 @@ pub enum InfixOp {
     Gt,
     // A >= B
     Gte,
+    // A and B, only evaluating B when A is true
+    And,
+    // A or B, only evaluating B when A is false
+    Or,
 }
 
 impl InfixOp {
@@ -47,12 +55,39 @@ impl InfixOp {
             InfixOp::Gt  => 5,
             InfixOp::Gte => 5,
             InfixOp::Equ => 4,
-            InfixOp::Pow => 3 // Not sure about this one
+            InfixOp::NotEqu => 4,
+            InfixOp::Is  => 4,
+            InfixOp::Pow => 3, // Not sure about this one
+            InfixOp::And => 2,
+            InfixOp::Or  => 2
+        }
+    }
+
+    // Operators which compare their operands, resulting in a bool
+    pub fn is_comparison(&self) -> bool {
+        match *self {
+            InfixOp::Equ    |
+            InfixOp::NotEqu |
+            InfixOp::Is     |
+            InfixOp::Lt  |
+            InfixOp::Lte |
+            InfixOp::Gt  |
+            InfixOp::Gte => true,
+            _ => false
+        }
+    }
+
+    // Operators between two bools, which short circuit
+    pub fn is_logical(&self) -> bool {
+        match *self {
+            InfixOp::And |
+            InfixOp::Or => true,
+            _ => false
         }
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub enum UnaryOp {
     // -A
     Negate,
@@ -1,9 +1,11 @@
 #![allow(dead_code)]
 use lexical::lexer::Tokenizer;
-use lexical::tokens::Tokens;
+use lexical::span::Span;
+use lexical::tokens::{Token, Tokens};
 use lexical::tokens::Tokens::*;
 use lexical::keywords::Keywords;
 use lexical::symbols::Symbols;
+use syntax::error::{ParseError, ParseErrorKind};
 use syntax::expr::*;
 use syntax::literals::*;
 use syntax::op::*;
@@ -12,10 +14,11 @@ use lexical::types::*;
 pub struct Parser<TokType: Tokenizer> {
     lexer: TokType,
     ast_root: ExprWrapper,
-    preview_token: Option<Tokens>,
+    preview_token: Option<Token>,
+    last_span: Span,
     block_status: BlockStatus,
     indent_level: u64,
-    valid_ast: bool,
+    errors: Vec<ParseError>,
     between_brackets: bool,
     last_depth: Option<u64>,
 }
@@ -34,14 +37,15 @@ fn debunt(val: u64) -> String {
     return a;
 }
 
-impl<TokType: Tokenizer + Iterator<Item=Tokens>> Parser<TokType> {
+impl<TokType: Tokenizer> Parser<TokType> {
     pub fn new(tokenizer: TokType) -> Parser<TokType> {
         Parser {
             lexer: tokenizer,
             ast_root: ExprWrapper::default(Expr::NoOp),
             indent_level: 0,
-            valid_ast: true,
-            preview_token: Some(Indent(0)),
+            errors: Vec::new(),
+            preview_token: Some(Token::new(Indent(0), Span::default())),
+            last_span: Span::default(),
             block_status: BlockStatus::Out,
             between_brackets: false,
             last_depth: None,
@@ -53,17 +57,17 @@ impl<TokType: Tokenizer + Iterator<Item=Tokens>> Parser<TokType> {
     /// - Smartly handlers `Indent`s by:
     ///    - When in blocks ignores them
     ///    - Ensures correct indentation size, then gets the next token
-    fn _next_token(&mut self, allow_any: bool) -> Tokens {
+    fn _next_spanned_token(&mut self, allow_any: bool) -> Token {
         loop {
             let result = match self.preview_token.take() {
                 Some(tok) => tok,
-                None => match self.lexer.next() {
+                None => match self.lexer.next_token() {
                     Some(t) => t,
-                    None => Tokens::EOF,
+                    None => Token::new(Tokens::EOF, Span::new(self.last_span.end, self.last_span.end)),
                 },
             };
 
-            match result {
+            match *result.get_token() {
                 Indent(depth) => {
                     match self.block_status {
                         BlockStatus::Out => {
@@ -76,7 +80,14 @@ impl<TokType: Tokenizer + Iterator<Item=Tokens>> Parser<TokType> {
                             if self.indent_level == depth {
                                 self.block_status = BlockStatus::In;
                             } else {
-                                return self.write_error("Invalid level of indentation");
+                                // Let the `Indent` through so the block sees the dedent (or
+                                // keeps going, if overindented) and parsing carries on
+                                self.block_status = BlockStatus::Out;
+
+                                let span = *result.get_span();
+                                self.write_error_at(ParseError::new(ParseErrorKind::InvalidIndentation,
+                                                                    format!("Expected an indentation level of {}, but found {}", self.indent_level, depth),
+                                                                    span, None, None, None));
                             }
                         },
                         BlockStatus::In => {
@@ -88,10 +99,6 @@ impl<TokType: Tokenizer + Iterator<Item=Tokens>> Parser<TokType> {
                     }
                 },
                 Comment(_) => (),
-                Error(_) => {
-                    // self.write_error(&err);
-                    return result;
-                },
                 _ => {
                     return result;
                 },
@@ -99,6 +106,22 @@ impl<TokType: Tokenizer + Iterator<Item=Tokens>> Parser<TokType> {
         }
     }
 
+    /// Consume the next `Token`, keeping track of its span
+    /// - Lexer `Error`s are recorded as they're consumed, so each is only reported once
+    fn _next_token(&mut self, allow_any: bool) -> Tokens {
+        let token = self._next_spanned_token(allow_any);
+
+        self.last_span = *token.get_span();
+
+        if let Error(ref err) = *token.get_token() {
+            let span = self.last_span;
+
+            self.write_error_at(ParseError::new(ParseErrorKind::Lexical, err.clone(), span, None, None, None));
+        }
+
+        token.into_token()
+    }
+
     fn next_token(&mut self) -> Tokens {
         self._next_token(false)
     }
@@ -112,8 +135,9 @@ impl<TokType: Tokenizer + Iterator<Item=Tokens>> Parser<TokType> {
     /// The next call to `next_token` will return the same `Token` returned
     /// by the last call to `peek`
     fn _peek(&mut self, allow_any: bool) -> Tokens {
-        let tok = self._next_token(allow_any);
-        self.preview_token = Some(tok.clone());
+        let token = self._next_spanned_token(allow_any);
+        let tok = token.get_token().clone();
+        self.preview_token = Some(token);
         tok
     }
 
@@ -129,20 +153,67 @@ impl<TokType: Tokenizer + Iterator<Item=Tokens>> Parser<TokType> {
         self._peek(true)
     }
 
-    /// Create an error from the current `Lexer`s state, with a message
-    fn write_error(&mut self, msg: &str) -> Tokens {
-        let (start_line, start_column, _, _) = (0, 0, 0, 0);
-
-        self.valid_ast = false;
+    /// Record an error to be returned once parsing finishes
+    fn write_error_at(&mut self, error: ParseError) -> Tokens {
+        let token = Tokens::Error(error.to_string());
 
-        println!("filename:{}:{} {}", start_line, start_column, msg);
+        self.errors.push(error);
 
         // This token seems to always be unused:
-        Tokens::Error(format!("filename:{}:{} {}", start_line, start_column, msg))
+        token
     }
 
-    fn write_expect_error(&mut self, reason: &str, expect: &str, got: &str) {
-        self.write_error(&format!("{}. Expected {}, but got {}", reason, expect, got));
+    /// Record an error at the last consumed `Token`, with a message
+    fn write_error(&mut self, kind: ParseErrorKind, msg: &str) -> Tokens {
+        let span = self.last_span;
+
+        self.write_error_at(ParseError::new(kind, msg.to_string(), span, None, None, None))
+    }
+
+    /// Record an error at the last consumed `Token`, which was not what was expected
+    fn write_expect_error(&mut self, reason: &str, expect: &str, found: Option<Tokens>, hint: Option<&str>) {
+        // The lexer's error was already reported when it was consumed
+        if let Some(Error(_)) = found {
+            return;
+        }
+
+        let span = self.last_span;
+        let kind = match found {
+            Some(_) => ParseErrorKind::UnexpectedToken,
+            None => ParseErrorKind::MissingExpression,
+        };
+
+        self.write_error_at(ParseError::new(kind, reason.to_string(), span, Some(expect.to_string()),
+                                            found, hint.map(|hint| hint.to_string())));
+    }
+
+    /// Returns the span of the `Token` which the last call to `peek` returned
+    fn peek_span(&self) -> Span {
+        match self.preview_token {
+            Some(ref token) => *token.get_span(),
+            None => self.last_span,
+        }
+    }
+
+    /// Skip past the rest of a broken statement, including any block under it,
+    /// stopping before the next line at or below `level` so parsing can resume there
+    fn synchronize(&mut self, level: u64) {
+        self.between_brackets = false;
+
+        loop {
+            match self.peek_any() {
+                Indent(depth) if depth <= level => break,
+                Tokens::EOF => break,
+                _ => {
+                    self.next_token_any();
+                },
+            }
+        }
+    }
+
+    /// Wrap an `Expr` spanning from the start of `start` to the end of the last consumed `Token`
+    fn wrap(&self, expr: Expr, start: &Span) -> ExprWrapper {
+        ExprWrapper::new(expr, start.to(&self.last_span))
     }
 
     /// Perform any necessary on-start actions
@@ -175,19 +246,19 @@ impl<TokType: Tokenizer + Iterator<Item=Tokens>> Parser<TokType> {
             let name = match tok {
                 Tokens::Identifier(ref name) => Some(name),
                 _ => {
-                    self.write_error(&format!("Unsupported token {:?}.", tok));
+                    self.write_expect_error("", "an argument name", Some(tok.clone()), None);
                     None
                 }
             };
 
             if let Some(arg) = name {
-                args.push(ExprWrapper::default(Expr::Literal(Literals::UTF8String(arg.to_string()))));
+                args.push(ExprWrapper::new(Expr::Literal(Literals::UTF8String(arg.to_string())), self.last_span));
                 first_arg = false;
                 continue;
             }
             break;
         }
-        self.write_error(&format!("Invalid syntax."));
+        self.write_error(ParseErrorKind::UnexpectedToken, "Invalid syntax");
         None
     }
 
@@ -213,9 +284,11 @@ impl<TokType: Tokenizer + Iterator<Item=Tokens>> Parser<TokType> {
 
     #[allow(unused_variables)]
     fn parse_fn_call(&mut self, ident: String) -> Option<ExprWrapper> {
+        // The identifier has already been consumed
+        let start = self.last_span;
         let token = self.next_token();
         if !token.expect(Symbol(Symbols::ParenOpen)) {
-            self.write_error("Expected an open parenthesis here.");
+            self.write_expect_error("", "an opening paren '('", Some(token), None);
             return None;
         }
 
@@ -224,12 +297,12 @@ impl<TokType: Tokenizer + Iterator<Item=Tokens>> Parser<TokType> {
         // Check to see if there are no args
         if tok.expect(Symbol(Symbols::ParenClose)) {
             self.next_token();
-            return Some(ExprWrapper::default(Expr::FnCall(ident.to_string(), Vec::new())));
+            return Some(self.wrap(Expr::FnCall(ident.to_string(), Vec::new()), &start));
         }
 
         let parse_args = |this: &mut Parser<TokType>, seperator: Tokens| {
             if !seperator.expect(Symbol(Symbols::Comma)) {
-                this.write_error("Missing a comma between arguments.");
+                this.write_expect_error("Missing a comma between arguments", "a comma ','", Some(seperator), None);
             }
             this.parse_expression(0)
         };
@@ -241,30 +314,87 @@ impl<TokType: Tokenizer + Iterator<Item=Tokens>> Parser<TokType> {
         let args = self.collect_sequence(parse_args, sequence_end);
         self.next_token();
 
-        Some(ExprWrapper::default(Expr::FnCall(ident.to_string(), args)))
+        Some(self.wrap(Expr::FnCall(ident.to_string(), args), &start))
     }
 
     fn parse_assignment(&mut self, ident: String) -> Option<ExprWrapper> {
+        // The identifier has already been consumed
+        let start = self.last_span;
+
         // Clear the equals sign
         self.next_token();
 
+        // A missing rvalue has already been reported by `parse_expression`
         if let Some(rvalue) = self.parse_expression(0) {
-            let ident = ExprWrapper::default(Expr::Var(ident));
-            return Some(ExprWrapper::default(Expr::Assign(ident, rvalue)));
-        } else {
-            self.write_expect_error("", "An expression", "None");
+            let ident = ExprWrapper::new(Expr::Var(ident), start);
+            return Some(self.wrap(Expr::Assign(ident, rvalue), &start));
         }
 
         None
     }
 
+    /// Parse ie `a += 1`, which is the same as `a = a + 1`
+    fn parse_compound_assignment(&mut self, ident: String, op: InfixOp) -> Option<ExprWrapper> {
+        // The identifier has already been consumed
+        let start = self.last_span;
+
+        // Clear the operator
+        self.next_token();
+
+        if let Some(rvalue) = self.parse_expression(0) {
+            let lhs = ExprWrapper::new(Expr::Var(ident.clone()), start);
+            let value = self.wrap(Expr::InfixOp(op, ExprWrapper::new(Expr::Var(ident), start), rvalue), &start);
+
+            return Some(self.wrap(Expr::Assign(lhs, value), &start));
+        }
+
+        None
+    }
+
+    /// Join a qualified name into one identifier, ie `magic.fly`
+    /// - The first part has already been consumed
+    fn parse_qualified_name(&mut self, mut ident: String) -> Option<String> {
+        let start = self.last_span;
+
+        while self.peek_any() == Symbol(Symbols::Period) {
+            self.next_token();
+
+            match self.next_token() {
+                Identifier(part) => {
+                    ident.push('.');
+                    ident.push_str(&part);
+                },
+                tok => {
+                    self.write_expect_error("", "a name after the period", Some(tok), None);
+
+                    return None;
+                }
+            }
+        }
+
+        // The whole name spans as if it were a single identifier
+        self.last_span = start.to(&self.last_span);
+
+        Some(ident)
+    }
+
     fn parse_idents(&mut self, ident: String) -> Option<ExprWrapper> {
         self.next_token();
 
+        let ident = match self.parse_qualified_name(ident) {
+            Some(ident) => ident,
+            None => return None,
+        };
+
         let tok = self.peek();
         match tok {
             Symbol(Symbols::ParenOpen) => self.parse_fn_call(ident),
             Symbol(Symbols::Equals) => self.parse_assignment(ident),
+            Symbol(Symbols::PlusEquals) => self.parse_compound_assignment(ident, InfixOp::Add),
+            Symbol(Symbols::MinusEquals) => self.parse_compound_assignment(ident, InfixOp::Sub),
+            Symbol(Symbols::AsteriskEquals) => self.parse_compound_assignment(ident, InfixOp::Mul),
+            Symbol(Symbols::SlashEquals) => self.parse_compound_assignment(ident, InfixOp::Div),
+            Symbol(Symbols::PercentEquals) => self.parse_compound_assignment(ident, InfixOp::Mod),
             _ => None,
         }
     }
@@ -274,12 +404,14 @@ impl<TokType: Tokenizer + Iterator<Item=Tokens>> Parser<TokType> {
     fn parse_fn(&mut self) -> Option<ExprWrapper> {
         self.next_token();
 
+        let start = self.last_span;
+
         // Get the function name
         let tok = self.next_token();
         let fn_name = match tok {
             Identifier(string) => string,
             _ => {
-                self.write_expect_error("", "an identifier", &format!("{:?}", tok));
+                self.write_expect_error("", "an identifier", Some(tok), None);
 
                 return None;
             }
@@ -289,23 +421,39 @@ impl<TokType: Tokenizer + Iterator<Item=Tokens>> Parser<TokType> {
         let mut tok = self.next_token();
 
         if !tok.expect(Symbol(Symbols::ParenOpen)) {
-            self.write_expect_error("", "an opening paren '('", &format!("{:?}", tok));
+            self.write_expect_error("", "an opening paren '('", Some(tok), None);
 
             return None;
         }
 
-        // Get all args (ie a: u64)
-        let mut args = Vec::new();
+        // Get all args (ie a: u64, b = 2: u64, ...)
+        let mut args: Vec<(String, String, Option<ExprWrapper>)> = Vec::new();
+        let mut var_args = false;
 
         tok = self.next_token();
 
         if tok != Symbol(Symbols::ParenClose) {
             loop {
-                // Find sequence: ((Identifier : Identifier)(, (Identifier : Identifier))*)?
+                // A trailing `...` takes any number of extra args
+                if tok.expect(Symbol(Symbols::Ellipsis)) {
+                    var_args = true;
+
+                    tok = self.next_token();
+
+                    if !tok.expect(Symbol(Symbols::ParenClose)) {
+                        self.write_expect_error("", "a closing paren ')'", Some(tok), Some("`...` must be the last parameter"));
+
+                        return None;
+                    }
+
+                    break;
+                }
+
+                // Find sequence: ((Identifier (= Expr)? : Identifier)(, (Identifier (= Expr)? : Identifier))*)?
                 let arg_name = match tok {
                     Identifier(ident) => ident,
                     _ => {
-                        self.write_expect_error("", "a function name", &format!("{:?}", tok));
+                        self.write_expect_error("", "an argument name", Some(tok), None);
 
                         return None;
                     }
@@ -313,17 +461,34 @@ impl<TokType: Tokenizer + Iterator<Item=Tokens>> Parser<TokType> {
 
                 tok = self.next_token();
 
+                // Find an (optional) default value
+                let mut default = None;
+
+                if tok.expect(Symbol(Symbols::Equals)) {
+                    default = match self.parse_expression(0) {
+                        Some(exprwrapper) => Some(exprwrapper),
+                        None => return None
+                    };
+
+                    tok = self.next_token();
+                } else if args.iter().any(|&(_, _, ref default)| default.is_some()) {
+                    self.write_expect_error("", "a default value", Some(tok),
+                                            Some("Arguments after one with a default need defaults too, ie `fn foo(a = 1: i32, b = 2: i32)`"));
+
+                    return None;
+                }
+
                 if !tok.expect(Symbol(Symbols::Colon)) {
-                    self.write_expect_error("", "a colon ':'", &format!("{:?}", tok));
+                    self.write_expect_error("", "a colon ':'", Some(tok), Some("Arguments need a type, ie `fn foo(a: i32)`"));
 
                     return None;
                 }
 
                 let this_token = self.next_token();
                 match this_token {
-                    Identifier(ident) => args.push((arg_name, ident)),
+                    Identifier(ident) => args.push((arg_name, ident, default)),
                     _ => {
-                        self.write_expect_error("", "a return type", &format!("{:?}", this_token));
+                        self.write_expect_error("", "an argument type", Some(this_token), None);
 
                         return None;
                     }
@@ -339,7 +504,7 @@ impl<TokType: Tokenizer + Iterator<Item=Tokens>> Parser<TokType> {
 
                     // Found something else, error
                     _ => {
-                        self.write_expect_error("", "a closing paren ')' or comma ','", &format!("{:?}", this_token));
+                        self.write_expect_error("", "a closing paren ')' or comma ','", Some(this_token), None);
 
                         return None;
                     }
@@ -354,7 +519,7 @@ impl<TokType: Tokenizer + Iterator<Item=Tokens>> Parser<TokType> {
         // Get right arrow ->
         tok = self.next_token();
         if !tok.expect(Symbol(Symbols::RightThinArrow)) {
-            self.write_expect_error("", "a thin right arrow '->'", &format!("{:?}", tok));
+            self.write_expect_error("", "a thin right arrow '->'", Some(tok), Some("Functions declare a return type, ie `fn foo() -> i32`"));
             return None;
         }
 
@@ -366,7 +531,7 @@ impl<TokType: Tokenizer + Iterator<Item=Tokens>> Parser<TokType> {
                 Some(ident)
             },
             _ => {
-                self.write_expect_error("", "a return type", &format!("{:?}", tok));
+                self.write_expect_error("", "a return type", Some(tok), None);
 
                 return None;
             }
@@ -376,15 +541,17 @@ impl<TokType: Tokenizer + Iterator<Item=Tokens>> Parser<TokType> {
 
         // Combine the rest of the function definiton with the fn info
         let definition = self.sub_parse();
+        let span = start.to(definition.get_span());
 
-        let expr = Expr::FnDecl(fn_name, args, return_type, definition);
+        let expr = Expr::FnDecl(fn_name, args, var_args, return_type, definition);
 
-        Some(ExprWrapper::default(expr))
+        Some(ExprWrapper::new(expr, span))
     }
 
     fn parse_declaration(&mut self) -> Option<ExprWrapper> {
         let keyword = self.next_token();
         let def_decl = keyword.expect(Keyword(Keywords::Def));
+        let start = self.last_span;
 
         let token = self.next_token();
 
@@ -399,28 +566,24 @@ impl<TokType: Tokenizer + Iterator<Item=Tokens>> Parser<TokType> {
                         token = self.next_token();
                         val_type = Some(t);
                     },
-                    _ => {
-                        self.write_expect_error("", "a type", &format!("{:?}", token));
+                    found => {
+                        self.write_expect_error("", "a type", Some(found), None);
                         return None;
                     }
                 }
             }
 
             if !token.expect(Symbol(Symbols::Equals)) {
-                self.write_expect_error("", "an Equal", &format!("{:?}", token));
+                self.write_expect_error("", "an equals sign '='", Some(token), Some("Variables must be initialized, ie `var a = 1`"));
                 return None
             }
 
             let expr = self.parse_expression(0);
             if let Some(value) = expr {
-                return Some(ExprWrapper::default(Expr::VarDecl(def_decl, name, val_type, value)));
-            } else {
-                self.write_expect_error("No value", "an expression",
-                                        &format!("{:?}", token));
+                return Some(self.wrap(Expr::VarDecl(def_decl, name, val_type, value), &start));
             }
         } else {
-            self.write_expect_error("No identifier", "an identifier",
-                                    &format!("{:?}", token));
+            self.write_expect_error("No identifier", "an identifier", Some(token), None);
         }
 
         None
@@ -429,59 +592,344 @@ impl<TokType: Tokenizer + Iterator<Item=Tokens>> Parser<TokType> {
     /// Parse a while block
     fn parse_while(&mut self) -> Option<ExprWrapper> {
         self.next_token();
+
+        let start = self.last_span;
+
         if let Some(expr) = self.parse_expression(0) {
             let token = self.next_token();
             if !token.expect(Symbol(Symbols::Comma)) {
-                self.write_expect_error("Incomplete while expression",
-                                        &format!("{:?}", Symbols::Comma),
-                                        &format!("{:?}", token));
+                self.write_expect_error("Incomplete while expression", "a comma ','", Some(token),
+                                        Some("Conditions end with a comma, ie `while a,`"));
                 return None
             }
             self.incr_indentation();
 
             let block = self.sub_parse();
+            let span = start.to(block.get_span());
             let result = Expr::WhileLoop(expr, block);
 
-            Some(ExprWrapper::default(result))
+            Some(ExprWrapper::new(result, span))
         } else {
-            self.write_expect_error("While should have an expression",
-                                    &format!("{:?}", "An expression"),
-                                    &format!("{:?}", "Nothing"));
             None
         }
     }
 
+    /// Parse a for loop over a range, ie `for i in 0..10 by 2,`
+    fn parse_for(&mut self) -> Option<ExprWrapper> {
+        self.next_token();
+
+        let start = self.last_span;
+
+        let token = self.next_token();
+        let name = match token {
+            Identifier(name) => name,
+            _ => {
+                self.write_expect_error("", "a loop variable name", Some(token), None);
+                return None;
+            }
+        };
+
+        let token = self.next_token();
+        if !token.expect(Keyword(Keywords::In)) {
+            self.write_expect_error("", "`in`", Some(token), Some("For loops go over a range, ie `for i in 0..10,`"));
+            return None;
+        }
+
+        let range_start = match self.parse_expression(0) {
+            Some(exprwrapper) => exprwrapper,
+            None => return None
+        };
+
+        let token = self.next_token();
+        if !token.expect(Symbol(Symbols::DoublePeriod)) {
+            self.write_expect_error("", "a range '..'", Some(token), Some("For loops go over a range, ie `for i in 0..10,`"));
+            return None;
+        }
+
+        let range_end = match self.parse_expression(0) {
+            Some(exprwrapper) => exprwrapper,
+            None => return None
+        };
+
+        let mut step = None;
+        let mut token = self.next_token();
+
+        if token.expect(Keyword(Keywords::By)) {
+            step = match self.parse_expression(0) {
+                Some(exprwrapper) => Some(exprwrapper),
+                None => return None
+            };
+
+            token = self.next_token();
+        }
+
+        if !token.expect(Symbol(Symbols::Comma)) {
+            self.write_expect_error("Incomplete for expression", "a comma ','", Some(token),
+                                    Some("Ranges end with a comma, ie `for i in 0..10,`"));
+            return None;
+        }
+
+        self.incr_indentation();
+
+        let block = self.sub_parse();
+        let span = start.to(block.get_span());
+        let result = Expr::ForLoop(name, range_start, range_end, step, block);
+
+        Some(ExprWrapper::new(result, span))
+    }
+
     /// Handles top-level keywords to start parsing them
     fn parse_keywords(&mut self, keyword: Keywords) -> Option<ExprWrapper> {
         match keyword {
             Keywords::Var | Keywords::Def => self.parse_declaration(),
             Keywords::Function => self.parse_fn(),
             Keywords::While => self.parse_while(),
+            Keywords::For => self.parse_for(),
             Keywords::If => self.parse_if(),
             Keywords::Return => self.parse_return(),
+            Keywords::Assert => self.parse_assert(),
+            Keywords::Use => self.parse_use(),
+            Keywords::Class => self.parse_class(),
+            Keywords::When => self.parse_when(),
+            Keywords::Raise => self.parse_raise(),
+            Keywords::Break => self.parse_single_keyword(Expr::Break),
+            Keywords::Continue => self.parse_single_keyword(Expr::Continue),
+            // `pass` only exists to fill an otherwise empty block
+            Keywords::Pass => self.parse_single_keyword(Expr::NoOp),
+            Keywords::Else => {
+                let span = self.peek_span();
+
+                self.write_error_at(ParseError::new(ParseErrorKind::UnexpectedToken,
+                                                    "Found an else without an if".to_string(),
+                                                    span, None, Some(Keyword(keyword)),
+                                                    Some("An else goes at the same indentation as its if".to_string())));
+                None
+            },
+            Keywords::Throws => {
+                let span = self.peek_span();
+
+                self.write_error_at(ParseError::new(ParseErrorKind::UnexpectedToken,
+                                                    "Found a throws without a when".to_string(),
+                                                    span, None, Some(Keyword(keyword)),
+                                                    Some("A throws handler goes at the same indentation as its when".to_string())));
+                None
+            },
             _ => {
-                self.write_error(&format!("Unsupported keyword {:?}.", keyword));
+                let span = self.peek_span();
+
+                self.write_error_at(ParseError::new(ParseErrorKind::UnsupportedKeyword,
+                                                    format!("Unsupported keyword {:?}", keyword),
+                                                    span, None, Some(Keyword(keyword)), None));
                 None
             }
         }
     }
 
+    /// Parse an assert, ie `assert a equals b, "a should be b"`
+    fn parse_assert(&mut self) -> Option<ExprWrapper> {
+        self.next_token();
+
+        let start = self.last_span;
+
+        let condition = match self.parse_expression(0) {
+            Some(exprwrapper) => exprwrapper,
+            None => return None
+        };
+
+        // Kept so a failing assert can show what it was checking
+        let source_text = match self.lexer.get_source_text(condition.get_span()) {
+            Some(text) => text.to_string(),
+            None => format!("{:?}", condition),
+        };
+
+        let mut message = None;
+
+        if self.peek_any() == Symbol(Symbols::Comma) {
+            self.next_token();
+
+            match self.next_token() {
+                StrLiteral(text) => message = Some(text),
+                tok => {
+                    self.write_expect_error("", "a message string", Some(tok), Some("Assert messages are strings, ie `assert a, \"a failed\"`"));
+                    return None;
+                }
+            }
+        }
+
+        Some(self.wrap(Expr::Assert(condition, message, source_text), &start))
+    }
+
+    /// Parse a raise, ie `raise KeyError, "no such butterfly"`
+    fn parse_raise(&mut self) -> Option<ExprWrapper> {
+        self.next_token();
+
+        let start = self.last_span;
+
+        let error_type = match self.next_token() {
+            Identifier(name) => name,
+            tok => {
+                self.write_expect_error("", "an error type", Some(tok), Some("Raise names the type of error, ie `raise KeyError`"));
+                return None;
+            }
+        };
+
+        let mut message = None;
+
+        if self.peek_any() == Symbol(Symbols::Comma) {
+            self.next_token();
+
+            match self.next_token() {
+                StrLiteral(text) => message = Some(text),
+                tok => {
+                    self.write_expect_error("", "a message string", Some(tok), Some("Error messages are strings, ie `raise KeyError, \"not found\"`"));
+                    return None;
+                }
+            }
+        }
+
+        Some(self.wrap(Expr::Raise(error_type, message), &start))
+    }
+
+    /// Parse an import, ie `use std.io.print` or `use magical_butterflies as magic`
+    fn parse_use(&mut self) -> Option<ExprWrapper> {
+        self.next_token();
+
+        let start = self.last_span;
+        let mut path = Vec::new();
+
+        loop {
+            match self.next_token() {
+                Identifier(part) => path.push(part),
+                tok => {
+                    self.write_expect_error("", "a module name", Some(tok), Some("Imports name a module, ie `use std.io`"));
+
+                    return None;
+                }
+            }
+
+            if self.peek_any() != Symbol(Symbols::Period) {
+                break;
+            }
+
+            self.next_token();
+        }
+
+        let mut alias = None;
+
+        if self.peek_any() == Keyword(Keywords::As) {
+            self.next_token();
+
+            match self.next_token() {
+                Identifier(name) => alias = Some(name),
+                tok => {
+                    self.write_expect_error("", "a name for the module", Some(tok), Some("Aliases follow `as`, ie `use std.io as io`"));
+
+                    return None;
+                }
+            }
+        }
+
+        Some(self.wrap(Expr::Use(path, alias), &start))
+    }
+
+    /// Parse a class, ie `class Point from Shape` followed by its fields and methods
+    /// - Methods become functions named `Class.method` which take the object as `self`
+    fn parse_class(&mut self) -> Option<ExprWrapper> {
+        self.next_token();
+
+        let start = self.last_span;
+
+        let name = match self.next_token() {
+            Identifier(name) => name,
+            tok => {
+                self.write_expect_error("", "a class name", Some(tok), None);
+
+                return None;
+            }
+        };
+
+        let mut base = None;
+
+        if self.peek_any() == Keyword(Keywords::From) {
+            self.next_token();
+
+            match self.next_token() {
+                Identifier(base_name) => base = Some(base_name),
+                tok => {
+                    self.write_expect_error("", "a base class", Some(tok), Some("Classes inherit from another class, ie `class Circle from Shape`"));
+
+                    return None;
+                }
+            }
+        }
+
+        self.incr_indentation();
+
+        let body = self.sub_parse();
+        let span = start.to(body.get_span());
+
+        let mut fields = Vec::new();
+        let mut methods = Vec::new();
+
+        if let Expr::Block(ref exprs) = *body.get_expr() {
+            for expr_wrapper in exprs {
+                let mut member = expr_wrapper.clone();
+
+                match *member.get_mut_expr() {
+                    Expr::VarDecl(..) => fields.push(expr_wrapper.clone()),
+                    Expr::FnDecl(ref mut method_name, ref mut args, ..) => {
+                        *method_name = format!("{}.{}", name, method_name);
+                        args.insert(0, ("self".to_string(), name.clone(), None));
+                    },
+                    Expr::NoOp => (),
+                    _ => {
+                        self.write_error_at(ParseError::new(ParseErrorKind::UnexpectedToken,
+                                                            "Found a statement in a class body".to_string(),
+                                                            *expr_wrapper.get_span(), None, None,
+                                                            Some("Classes only hold fields and methods, ie `var x: i32 = 0` or `fn area() -> f64`".to_string())));
+                        continue;
+                    },
+                }
+
+                if let Expr::FnDecl(..) = *member.get_expr() {
+                    methods.push(member);
+                }
+            }
+        }
+
+        Some(ExprWrapper::new(Expr::ClassDecl(name, base, fields, methods), span))
+    }
+
+    /// Parse a statement which is just its keyword, ie `break`
+    fn parse_single_keyword(&mut self, expr: Expr) -> Option<ExprWrapper> {
+        self.next_token();
+
+        Some(ExprWrapper::new(expr, self.last_span))
+    }
+
     fn parse_return(&mut self) -> Option<ExprWrapper> {
         self.next_token();
 
+        let start = self.last_span;
+
         let wrapper = match self.parse_expression(0) {
             Some(exprwrapper) => exprwrapper,
             None => return None,
         };
 
-        Some(ExprWrapper::default(Expr::Return(Some(wrapper))))
+        Some(self.wrap(Expr::Return(Some(wrapper)), &start))
 
         // TODO: Expect newline?
     }
 
+    /// Parse an if block, along with any `else if` and `else` blocks chained after it
+    /// - A chain can be closed with a period on its own line, ie `.`
     fn parse_if(&mut self) -> Option<ExprWrapper> {
         self.next_token();
 
+        let start = self.last_span;
+        let level = self.indent_level;
+
         let condition = match self.parse_expression(0) {
             Some(exprwrapper) => exprwrapper,
             None => return None
@@ -490,7 +938,7 @@ impl<TokType: Tokenizer + Iterator<Item=Tokens>> Parser<TokType> {
         let tok = self.next_token();
 
         if !tok.expect(Symbol(Symbols::Comma)) {
-            self.write_expect_error("", "a comma ','", &format!("{:?}", tok));
+            self.write_expect_error("", "a comma ','", Some(tok), Some("Conditions end with a comma, ie `if a,`"));
 
             return None;
         }
@@ -498,10 +946,164 @@ impl<TokType: Tokenizer + Iterator<Item=Tokens>> Parser<TokType> {
         self.incr_indentation();
 
         let block = self.sub_parse();
+        let mut span = start.to(block.get_span());
+
+        let mut else_block = None;
+
+        // An `else` only belongs to this `if` when it's at the same indentation
+        if self.last_depth == Some(level) && self.peek_any() == Keyword(Keywords::Else) {
+            self.next_token();
+            self.last_depth = None;
+
+            if self.peek() == Keyword(Keywords::If) {
+                else_block = match self.parse_if() {
+                    Some(exprwrapper) => Some(exprwrapper),
+                    None => return None
+                };
+            } else {
+                let tok = self.next_token();
+
+                if !tok.expect(Symbol(Symbols::Comma)) {
+                    self.write_expect_error("", "a comma ','", Some(tok), Some("An else block starts with a comma, ie `else,`"));
+
+                    return None;
+                }
+
+                self.incr_indentation();
+
+                else_block = Some(self.sub_parse());
+            }
+        }
+
+        if let Some(ref exprwrapper) = else_block {
+            span = span.to(exprwrapper.get_span());
+        }
+
+        // The optional period closing the chain stands in for a statement on its line
+        if self.last_depth == Some(level) && self.peek_any() == Symbol(Symbols::Period) {
+            self.next_token();
+            self.last_depth = None;
+        }
 
-        let expr = Expr::If(condition, block, None);
+        let expr = Expr::If(condition, block, else_block);
 
-        Some(ExprWrapper::default(expr))
+        Some(ExprWrapper::new(expr, span))
+    }
+
+    /// Parse a when block along with its `throws` handlers and optional `else` block, ie
+    /// `when,` ... `throws KeyError or TypeError as e,` ... `else,` ...
+    /// - Like an if chain, it can be closed with a period on its own line
+    fn parse_when(&mut self) -> Option<ExprWrapper> {
+        self.next_token();
+
+        let start = self.last_span;
+        let level = self.indent_level;
+
+        let tok = self.next_token();
+
+        if !tok.expect(Symbol(Symbols::Comma)) {
+            self.write_expect_error("", "a comma ','", Some(tok), Some("A when block starts with a comma, ie `when,`"));
+
+            return None;
+        }
+
+        self.incr_indentation();
+
+        let block = self.sub_parse();
+        let mut span = start.to(block.get_span());
+
+        let mut handlers = Vec::new();
+
+        // Handlers only belong to this `when` when they're at the same indentation
+        while self.last_depth == Some(level) && self.peek_any() == Keyword(Keywords::Throws) {
+            self.next_token();
+            self.last_depth = None;
+
+            let mut error_types = Vec::new();
+
+            loop {
+                match self.next_token() {
+                    Identifier(name) => error_types.push(name),
+                    tok => {
+                        self.write_expect_error("", "an error type", Some(tok), Some("Handlers name the errors they handle, ie `throws KeyError,`"));
+
+                        return None;
+                    }
+                }
+
+                if self.peek_any() != Keyword(Keywords::Or) {
+                    break;
+                }
+
+                self.next_token();
+            }
+
+            let mut binding = None;
+            let mut tok = self.next_token();
+
+            if tok.expect(Keyword(Keywords::As)) {
+                match self.next_token() {
+                    Identifier(name) => binding = Some(name),
+                    tok => {
+                        self.write_expect_error("", "a name for the error", Some(tok), Some("Name the error's message after `as`, ie `throws KeyError as e,`"));
+
+                        return None;
+                    }
+                }
+
+                tok = self.next_token();
+            }
+
+            if !tok.expect(Symbol(Symbols::Comma)) {
+                self.write_expect_error("", "a comma ','", Some(tok), Some("Handlers end with a comma, ie `throws KeyError as e,`"));
+
+                return None;
+            }
+
+            self.incr_indentation();
+
+            let handler = self.sub_parse();
+            span = span.to(handler.get_span());
+
+            handlers.push((error_types, binding, handler));
+        }
+
+        if handlers.is_empty() {
+            self.write_expect_error("", "a `throws` handler", None, Some("A when block needs at least one handler at its indentation, ie `throws KeyError,`"));
+
+            return None;
+        }
+
+        let mut else_block = None;
+
+        if self.last_depth == Some(level) && self.peek_any() == Keyword(Keywords::Else) {
+            self.next_token();
+            self.last_depth = None;
+
+            let tok = self.next_token();
+
+            if !tok.expect(Symbol(Symbols::Comma)) {
+                self.write_expect_error("", "a comma ','", Some(tok), Some("An else block starts with a comma, ie `else,`"));
+
+                return None;
+            }
+
+            self.incr_indentation();
+
+            let exprwrapper = self.sub_parse();
+            span = span.to(exprwrapper.get_span());
+
+            else_block = Some(exprwrapper);
+        }
+
+        if self.last_depth == Some(level) && self.peek_any() == Symbol(Symbols::Period) {
+            self.next_token();
+            self.last_depth = None;
+        }
+
+        let expr = Expr::When(block, handlers, else_block);
+
+        Some(ExprWrapper::new(expr, span))
     }
 
     fn is_infix_op(&self, token: &Tokens) -> bool {
@@ -512,7 +1114,16 @@ impl<TokType: Tokenizer + Iterator<Item=Tokens>> Parser<TokType> {
             Symbol(Symbols::Slash) => true,
             Symbol(Symbols::Percent) => true,
             Symbol(Symbols::Caret) => true,
+            Symbol(Symbols::LessThan) => true,
+            Symbol(Symbols::LessThanEqual) => true,
+            Symbol(Symbols::GreaterThan) => true,
+            Symbol(Symbols::GreaterThanEqual) => true,
             Keyword(Keywords::Equals) => true,
+            // Infix `not` is always followed by `equals`
+            Keyword(Keywords::Not) => true,
+            Keyword(Keywords::Is) => true,
+            Keyword(Keywords::And) => true,
+            Keyword(Keywords::Or) => true,
             _ => false
         }
     }
@@ -525,14 +1136,22 @@ impl<TokType: Tokenizer + Iterator<Item=Tokens>> Parser<TokType> {
             Symbol(Symbols::Slash) => InfixOp::Div.get_precedence(),
             Symbol(Symbols::Percent) => InfixOp::Mod.get_precedence(),
             Symbol(Symbols::Caret) => InfixOp::Pow.get_precedence(),
+            Symbol(Symbols::LessThan) => InfixOp::Lt.get_precedence(),
+            Symbol(Symbols::LessThanEqual) => InfixOp::Lte.get_precedence(),
+            Symbol(Symbols::GreaterThan) => InfixOp::Gt.get_precedence(),
+            Symbol(Symbols::GreaterThanEqual) => InfixOp::Gte.get_precedence(),
             Keyword(Keywords::Equals) => InfixOp::Equ.get_precedence(),
+            Keyword(Keywords::Not) => InfixOp::NotEqu.get_precedence(),
+            Keyword(Keywords::Is) => InfixOp::Is.get_precedence(),
+            Keyword(Keywords::And) => InfixOp::And.get_precedence(),
+            Keyword(Keywords::Or) => InfixOp::Or.get_precedence(),
             _ => 0
         }
     }
 
     fn parse_expression(&mut self, precedence: u8) -> Option<ExprWrapper> {
         // E -> (E) | [E] | E * E | E + E | E - E | E / E | E % E | E ^ E |
-        // E equals E | E and E | E or E | not E | -E | Terminal
+        // E equals E | E not equals E | E is E | E and E | E or E | not E | -E | Terminal
         // Terminal -> identifier | literal
 
         let subroutine = self.parse_expression_subroutine();
@@ -545,6 +1164,18 @@ impl<TokType: Tokenizer + Iterator<Item=Tokens>> Parser<TokType> {
         let mut token = self.peek_any();
         while self.is_infix_op(&token) && self.get_precedence(&token) >= precedence {
             token = self.next_token_any();
+
+            if token == Keyword(Keywords::Not) {
+                let tok = self.next_token_any();
+
+                if !tok.expect(Keyword(Keywords::Equals)) {
+                    self.write_expect_error("", "`equals` after `not`", Some(tok),
+                                            Some("Use `not equals` to check that two values differ"));
+
+                    return None;
+                }
+            }
+
             let new_precedence = self.get_precedence(&token) + match token {
                 // Right associative ops don't get the +1
                 Symbol(Symbols::Caret) => 0,
@@ -559,11 +1190,21 @@ impl<TokType: Tokenizer + Iterator<Item=Tokens>> Parser<TokType> {
                     Symbol(Symbols::Slash) => InfixOp::Div,
                     Symbol(Symbols::Percent) => InfixOp::Mod,
                     Symbol(Symbols::Caret) => InfixOp::Pow,
+                    Symbol(Symbols::LessThan) => InfixOp::Lt,
+                    Symbol(Symbols::LessThanEqual) => InfixOp::Lte,
+                    Symbol(Symbols::GreaterThan) => InfixOp::Gt,
+                    Symbol(Symbols::GreaterThanEqual) => InfixOp::Gte,
                     Keyword(Keywords::Equals) => InfixOp::Equ,
+                    Keyword(Keywords::Not) => InfixOp::NotEqu,
+                    Keyword(Keywords::Is) => InfixOp::Is,
+                    Keyword(Keywords::And) => InfixOp::And,
+                    Keyword(Keywords::Or) => InfixOp::Or,
                     _ => unreachable!("Expression parse")
                 };
 
-                lhs = ExprWrapper::default(Expr::InfixOp(infix, lhs, rhs));
+                let span = lhs.get_span().to(rhs.get_span());
+
+                lhs = ExprWrapper::new(Expr::InfixOp(infix, lhs, rhs), span);
             } else {
                 return None;
             }
@@ -578,20 +1219,25 @@ impl<TokType: Tokenizer + Iterator<Item=Tokens>> Parser<TokType> {
         match self.next_token() {
             // Terminals
             BoolLiteral(val) => {
-                Some(ExprWrapper::default(Expr::Literal(Literals::Bool(val))))
+                Some(ExprWrapper::new(Expr::Literal(Literals::Bool(val)), self.last_span))
             },
             StrLiteral(string) => {
-                Some(ExprWrapper::default(Expr::Literal(Literals::UTF8String(string))))
+                Some(ExprWrapper::new(Expr::Literal(Literals::UTF8String(string)), self.last_span))
             },
             CharLiteral(chr) => {
-                Some(ExprWrapper::default(Expr::Literal(Literals::UTF8Char(chr))))
+                Some(ExprWrapper::new(Expr::Literal(Literals::UTF8Char(chr)), self.last_span))
             },
             Identifier(ident) => {
+                let ident = match self.parse_qualified_name(ident) {
+                    Some(ident) => ident,
+                    None => return None,
+                };
+
                 if let Symbol(Symbols::ParenOpen) = self.peek_any() {
                     return self.parse_fn_call(ident);
                 }
 
-                Some(ExprWrapper::default(Expr::Var(ident)))
+                Some(ExprWrapper::new(Expr::Var(ident), self.last_span))
             },
             Numeric(string, _type) => Some(self.parse_number(string, _type)),
 
@@ -602,7 +1248,7 @@ impl<TokType: Tokenizer + Iterator<Item=Tokens>> Parser<TokType> {
                 let tok = self.next_token();
 
                 if !tok.expect(Symbol(Symbols::ParenClose)) {
-                    self.write_expect_error("", "a closing paren ')'", &format!("{:?}", tok));
+                    self.write_expect_error("", "a closing paren ')'", Some(tok), None);
 
                     return None;
                 }
@@ -612,21 +1258,25 @@ impl<TokType: Tokenizer + Iterator<Item=Tokens>> Parser<TokType> {
 
             // Unary ops, precedence hard coded to a (high) 8
             Symbol(Symbols::Minus) => {
+                let start = self.last_span;
+
                 return match self.parse_expression(8) {
-                    Some(exprwrapper) => Some(ExprWrapper::default(Expr::UnaryOp(UnaryOp::Negate, exprwrapper))),
+                    Some(exprwrapper) => Some(self.wrap(Expr::UnaryOp(UnaryOp::Negate, exprwrapper), &start)),
                     None => None
                 }
             },
             Keyword(Keywords::Not) => {
+                let start = self.last_span;
+
                 return match self.parse_expression(8) {
-                    Some(exprwrapper) => Some(ExprWrapper::default(Expr::UnaryOp(UnaryOp::Not, exprwrapper))),
+                    Some(exprwrapper) => Some(self.wrap(Expr::UnaryOp(UnaryOp::Not, exprwrapper), &start)),
                     None => None
                 }
             },
 
             // Else error
-            _ => {
-                self.write_error("Not sure how you got here.");
+            tok => {
+                self.write_expect_error("", "an expression", Some(tok), None);
 
                 None
             }
@@ -728,7 +1378,7 @@ impl<TokType: Tokenizer + Iterator<Item=Tokens>> Parser<TokType> {
             }
         }
 
-        ExprWrapper::default(Expr::Literal(match type_ {
+        ExprWrapper::new(Expr::Literal(match type_ {
             Some(Types::Int32Bit)   => Literals::I32Num(int32),
             Some(Types::Int64Bit)   => Literals::I64Num(int64),
             Some(Types::UInt32Bit)  => Literals::U32Num(uint32),
@@ -743,13 +1393,16 @@ impl<TokType: Tokenizer + Iterator<Item=Tokens>> Parser<TokType> {
                     Literals::I32Num(int32)
                 }
             }
-        }))
+        }), self.last_span)
     }
 
     /// Returns an `ExprWrapper` to the root of the current AST branch
     fn sub_parse(&mut self) -> ExprWrapper {
         let mut expr = Vec::new();
         let cur_level = self.indent_level;
+
+        // An empty block sits right after whatever started it
+        let header_end = self.last_span.end;
         debug!("{}Beginning parse", debunt(cur_level));
         loop {
             debug!("{}Beginning TLL at level: {:?}", debunt(cur_level + 1), cur_level);
@@ -770,16 +1423,17 @@ impl<TokType: Tokenizer + Iterator<Item=Tokens>> Parser<TokType> {
                         // before a new statement.
                         if let Some(last_depth) = self.last_depth {
                             if last_depth != 0 {
-                                self.write_error(&format!("There were two indents in a row, {} and {}",
-                                                 last_depth, this_depth));
-                                outer_break = true;
-                                break;
+                                let span = self.peek_span();
+
+                                self.write_error_at(ParseError::new(ParseErrorKind::InvalidIndentation,
+                                                                    format!("There were two indents in a row, {} and {}", last_depth, this_depth),
+                                                                    span, None, None, None));
                             }
                         }
                         self.last_depth = Some(this_depth);
                         self.next_token_any();
                     },
-                    Error(_) | Tokens::EOF => {
+                    Tokens::EOF => {
                         outer_break = true;
                         break;
                     },
@@ -803,9 +1457,19 @@ impl<TokType: Tokenizer + Iterator<Item=Tokens>> Parser<TokType> {
                             break;
                         } else {
                             let token = self.peek_any();
-                            self.write_expect_error("There should be at most one statement per line",
-                                                     "a newline", &format!("{:?}", token));
-                            return ExprWrapper::default(Expr::NoOp)
+                            let span = self.peek_span();
+
+                            // A bad token is reported once it's skipped over
+                            if let Error(_) = token {
+                                self.synchronize(cur_level);
+                                continue;
+                            }
+
+                            self.write_error_at(ParseError::new(ParseErrorKind::MultipleStatementsPerLine,
+                                                                "There should be at most one statement per line".to_string(),
+                                                                span, Some("a newline".to_string()), Some(token),
+                                                                Some("Move the statement onto its own line".to_string())));
+                            self.synchronize(cur_level);
                         }
                     },
                 }
@@ -820,42 +1484,64 @@ impl<TokType: Tokenizer + Iterator<Item=Tokens>> Parser<TokType> {
             match self.peek() {
                 Identifier(ident) => {
                     debug!("{}TLL found an identifier: {:?}", debunt(cur_level + 1), self.peek());
-                    if let Some(exprwrapper) = self.parse_idents(ident) {
-                        expr.push(exprwrapper);
+                    match self.parse_idents(ident) {
+                        Some(exprwrapper) => expr.push(exprwrapper),
+                        None => self.synchronize(cur_level),
                     }
                 },
                 Keyword(keyword) => {
                     debug!("{}TLL found an keyword: {:?}", debunt(cur_level + 1), self.peek());
-                    if let Some(exprwrapper) = self.parse_keywords(keyword) {
-                        expr.push(exprwrapper);
+                    match self.parse_keywords(keyword) {
+                        Some(exprwrapper) => expr.push(exprwrapper),
+                        None => self.synchronize(cur_level),
                     }
                 },
-                Error(err) => {
-                    self.write_error(&err);
-                    break
-                },
+                // Recorded as it's skipped over
+                Error(_) => self.synchronize(cur_level),
                 EOF => break,
 
                 // These tokens are all illegal in top level expressions
                 Symbol(_) | StrLiteral(_) | CharLiteral(_) | BoolLiteral(_) |
                 Numeric(_, _) | Comment(_) | Indent(_) => {
                     let token = self.peek();
-                    self.write_error(&format!("Unimplemented top level token '{:?}'", token));
+                    let span = self.peek_span();
+
+                    self.write_error_at(ParseError::new(ParseErrorKind::UnexpectedToken,
+                                                        "Unimplemented top level token".to_string(),
+                                                        span, Some("a statement".to_string()), Some(token), None));
+                    self.synchronize(cur_level);
                 },
             };
         }
 
         debug!("{}Returning from parse: {:?}", debunt(cur_level), expr);
-        ExprWrapper::default(Expr::Block(expr))
+
+        let span = match (expr.first(), expr.last()) {
+            (Some(first), Some(last)) => first.get_span().to(last.get_span()),
+            _ => Span::new(header_end, header_end),
+        };
+
+        ExprWrapper::new(Expr::Block(expr), span)
     }
 
-    pub fn parse(&mut self) -> Option<ExprWrapper>{
-        let ast_root = self.sub_parse();
+    /// Parse the whole input, returning the AST root or every error found
+    pub fn parse(&mut self) -> Result<ExprWrapper, Vec<ParseError>> {
+        let (ast_root, errors) = self.parse_partial();
 
-        if self.valid_ast {
-            Some(ast_root)
+        if errors.is_empty() {
+            Ok(ast_root)
         } else {
-            None
+            Err(errors)
         }
     }
+
+    /// Parse the whole input, recovering from errors as it goes
+    ///
+    /// Statements which failed to parse are left out of the returned AST,
+    /// so it's only complete when there are no errors
+    pub fn parse_partial(&mut self) -> (ExprWrapper, Vec<ParseError>) {
+        let ast_root = self.sub_parse();
+
+        (ast_root, self.errors.drain(..).collect())
+    }
 }
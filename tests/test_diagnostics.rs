@@ -0,0 +1,136 @@
+extern crate limonite;
+
+use limonite::diagnostics::diagnostic::{Diagnostic, Severity};
+use limonite::diagnostics::emitter::Emitter;
+use limonite::lexical::lexer::Lexer;
+use limonite::lexical::span::{Position, Span};
+use limonite::semantic::analyzer::SemanticAnalyzer;
+use limonite::semantic::analyzer_trait::ASTAnalyzer;
+use limonite::syntax::parser::Parser;
+
+fn render_parse_errors(input_string: &str) -> String {
+    let mut parser = Parser::new(Lexer::new(input_string));
+    let (_, errors) = parser.parse_partial();
+    let emitter = Emitter::new("test.lim", input_string);
+
+    errors.iter().map(|error| emitter.render(&error.to_diagnostic())).collect()
+}
+
+#[test]
+fn test_parse_error() {
+    let output = render_parse_errors("while a b\n\tc = 1");
+
+    assert_eq!(output, "\
+error: Incomplete while expression
+ --> test.lim:1:9
+  |
+1 | while a b
+  |         ^ expected a comma ',', but found Identifier(\"b\")
+  |
+  = help: Conditions end with a comma, ie `while a,`
+");
+}
+
+#[test]
+fn test_lexical_errors() {
+    let output = render_parse_errors("var a = 0xz\n\tif a,\n\t\tb = 42u8");
+
+    assert_eq!(output, "\
+error: No hexadecimal value was found.
+ --> test.lim:1:9
+  |
+1 | var a = 0xz
+  |         ^^^
+error: Invalid suffix u8. Did you mean u32 or u64?
+ --> test.lim:3:7
+  |
+3 |         b = 42u8
+  |             ^^^^
+");
+}
+
+#[test]
+fn test_labels_notes_and_help() {
+    let input_string = "def a = 1\n\n\n\n\n\n\n\n\na = 2";
+    let diagnostic = Diagnostic::new(Severity::Warning, "Something about a".to_string())
+        .with_secondary_label(Span::new(Position::new(1, 5, 4), Position::new(1, 6, 5)),
+                              Some("declared here".to_string()))
+        .with_primary_label(Span::new(Position::new(10, 1, 20), Position::new(10, 6, 25)), None)
+        .with_note("a note".to_string())
+        .with_help("some help".to_string());
+
+    let output = Emitter::new("test.lim", input_string).render(&diagnostic);
+
+    assert_eq!(output, "\
+warning: Something about a
+  --> test.lim:10:1
+   |
+ 1 | def a = 1
+   |     - declared here
+10 | a = 2
+   | ^^^^^
+   |
+   = note: a note
+   = help: some help
+");
+}
+
+#[test]
+fn test_colour() {
+    let input_string = "a = 1 2";
+    let diagnostic = Diagnostic::error("Bad".to_string())
+        .with_primary_label(Span::new(Position::new(1, 7, 6), Position::new(1, 8, 7)), Some("here".to_string()));
+
+    let mut emitter = Emitter::new("test.lim", input_string);
+    emitter.set_colour(true);
+
+    assert_eq!(emitter.render(&diagnostic), "\
+\x1b[1;31merror\x1b[0m\x1b[1m: Bad\x1b[0m
+ \x1b[1;34m-->\x1b[0m test.lim:1:7
+  \x1b[1;34m|\x1b[0m
+\x1b[1;34m1\x1b[0m \x1b[1;34m|\x1b[0m a = 1 2
+  \x1b[1;34m|\x1b[0m       \x1b[1;31m^ here\x1b[0m
+");
+}
+
+#[test]
+fn test_semantic_error() {
+    let input_string = "var a = 34\nvar a = 3";
+    let mut ast_root = Parser::new(Lexer::new(input_string)).parse().unwrap();
+    let errors = SemanticAnalyzer::new().analyze(&mut ast_root).unwrap_err();
+
+    let emitter = Emitter::new("test.lim", input_string);
+    let output: String = errors.iter().map(|error| emitter.render(&error.to_diagnostic())).collect();
+
+    assert_eq!(output, "\
+error: Variable a is already declared in this scope
+ --> test.lim:2:1
+  |
+1 | var a = 34
+  | ---------- `a` first declared here
+2 | var a = 3
+  | ^^^^^^^^^ `a` redeclared here
+");
+}
+
+#[test]
+fn test_assign_to_constant() {
+    let input_string = "def a = 34\na = 3";
+    let mut ast_root = Parser::new(Lexer::new(input_string)).parse().unwrap();
+    let errors = SemanticAnalyzer::new().analyze(&mut ast_root).unwrap_err();
+
+    let emitter = Emitter::new("test.lim", input_string);
+    let output: String = errors.iter().map(|error| emitter.render(&error.to_diagnostic())).collect();
+
+    assert_eq!(output, "\
+error: Cannot assign to the constant a
+ --> test.lim:2:1
+  |
+1 | def a = 34
+  | ---------- `a` declared as a constant here
+2 | a = 3
+  | ^^^^^ cannot assign to `a` twice
+  |
+  = help: Declare `a` with `var` to make it mutable
+");
+}
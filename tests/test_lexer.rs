@@ -1,7 +1,9 @@
 extern crate limonite;
 
-use limonite::lexical::keywords::Keywords::{Def, Function, If, Is, Return, Var};
-use limonite::lexical::symbols::Symbols::{Comma, Equals, ParenClose, ParenOpen, PlusEquals, RightThinArrow};
+use limonite::lexical::keywords::Keywords::{By, Def, For, Function, If, In, Is, Return, Var};
+use limonite::lexical::symbols::Symbols::{Comma, DoublePeriod, Ellipsis, Equals, ParenClose, ParenOpen, Period, PlusEquals, RightThinArrow};
+use limonite::lexical::error::LexError;
+use limonite::lexical::span::{Position, Span};
 use limonite::lexical::tokens::Tokens;
 use limonite::lexical::tokens::Tokens::{BoolLiteral, CharLiteral, Comment, EOF, Error, Identifier, Indent, Keyword, Numeric, Symbol, StrLiteral};
 use limonite::lexical::types::Types::{Float32Bit, Float64Bit, Int32Bit, Int64Bit, UInt32Bit, UInt64Bit};
@@ -9,7 +11,7 @@ use limonite::lexical::lexer::{Lexer};
 
 fn cmp_tokens(mut lexer: Lexer, vec: Vec<Tokens>) {
     for desired_tok in vec.iter() {
-        let tok = lexer.next();
+        let tok = lexer.next().map(|token| token.into_token());
         if let Some(tok) = tok {
             if tok == *desired_tok {
                 continue;
@@ -18,7 +20,7 @@ fn cmp_tokens(mut lexer: Lexer, vec: Vec<Tokens>) {
         }
     }
 
-    let tok = lexer.next();
+    let tok = lexer.next().map(|token| token.into_token());
     if let Some(tok) = tok {
         if !tok.expect(EOF) {
             panic!(format!("Unexpected token `{:?}` found. Expected `EOF`", tok));
@@ -123,6 +125,27 @@ fn test_valid_numerics() {
     cmp_tokens(lexer, desired_output);
 }
 
+#[test]
+fn test_ranges() {
+    let input_string = "\
+for i in 0..10 by 2,
+1.5..a
+0.5
+(a, ...)
+.";
+
+    let lexer = Lexer::new(&input_string);
+    let desired_output = vec![Keyword(For), Identifier("i".to_string()), Keyword(In),
+                              Numeric("0".to_string(), None), Symbol(DoublePeriod), Numeric("10".to_string(), None),
+                              Keyword(By), Numeric("2".to_string(), None), Symbol(Comma), Indent(0),
+                              Numeric("1.5".to_string(), None), Symbol(DoublePeriod), Identifier("a".to_string()), Indent(0),
+                              Numeric("0.5".to_string(), None), Indent(0),
+                              Symbol(ParenOpen), Identifier("a".to_string()), Symbol(Comma), Symbol(Ellipsis), Symbol(ParenClose), Indent(0),
+                              Symbol(Period)];
+
+    cmp_tokens(lexer, desired_output);
+}
+
 #[test]
 fn test_invalid_numerics() {
     let input_string = "\
@@ -148,7 +171,7 @@ fn test_invalid_numerics() {
 
     let lexer = Lexer::new(&input_string);
     let desired_output = vec![Error("No hexadecimal value was found.".to_string()), Indent(0),
-                              Error("No hexadecimal value was found.".to_string()), Identifier("z".to_string()), Indent(0),
+                              Error("No hexadecimal value was found.".to_string()), Indent(0),
                               Error("Invalid suffix z. Did you mean u32, u64, i32, or i64?".to_string()), Indent(0),
                               Error("Invalid suffix i3. Did you mean i32?".to_string()), Indent(0),
                               Error("Invalid suffix i31. Did you mean i32?".to_string()), Indent(0),
@@ -156,10 +179,10 @@ fn test_invalid_numerics() {
                               Error("Invalid suffix i63. Did you mean i64?".to_string()), Indent(0),
                               Error("Invalid suffix u8. Did you mean u32 or u64?".to_string()), Indent(0),
                               Error("No binary value was found.".to_string()), Indent(0),
-                              Error("No binary value was found.".to_string()), Identifier("a".to_string()), Indent(0),
+                              Error("No binary value was found.".to_string()), Indent(0),
                               Error("Invalid suffix a. Did you mean u32, u64, i32, or i64?".to_string()), Indent(0),
                               Error("Invalid suffix f. Did you mean u32, u64, i32, or i64?".to_string()), Indent(0),
-                              Error("Invalid suffix f. Did you mean u32, u64, i32, or i64?".to_string()), Numeric("32".to_string(), None), Indent(0),
+                              Error("Invalid suffix f. Did you mean u32, u64, i32, or i64?".to_string()), Indent(0),
                               Error("Invalid suffix i3. Did you mean i32?".to_string()), Indent(0),
                               Error("Invalid suffix i31. Did you mean i32?".to_string()), Indent(0),
                               Error("No numbers found after the decimal point.".to_string()), Indent(0),
@@ -195,3 +218,56 @@ fn basic_func() -> str
 
     cmp_tokens(lexer, desired_output);
 }
+
+#[test]
+fn test_token_spans() {
+    let input_string = "\
+var a = 42
+\"é\" += a";
+
+    let spans: Vec<Span> = Lexer::new(&input_string).map(|token| *token.get_span()).collect();
+
+    let desired_spans = vec![
+        Span::new(Position::new(1, 1, 0), Position::new(1, 4, 3)),    // var
+        Span::new(Position::new(1, 5, 4), Position::new(1, 6, 5)),    // a
+        Span::new(Position::new(1, 7, 6), Position::new(1, 8, 7)),    // =
+        Span::new(Position::new(1, 9, 8), Position::new(1, 11, 10)),  // 42
+        Span::new(Position::new(1, 11, 10), Position::new(2, 1, 11)), // Indent(0)
+        Span::new(Position::new(2, 1, 11), Position::new(2, 4, 15)),  // "é"
+        Span::new(Position::new(2, 5, 16), Position::new(2, 7, 18)),  // +=
+        Span::new(Position::new(2, 8, 19), Position::new(2, 9, 20)),  // a
+    ];
+
+    assert_eq!(spans, desired_spans);
+}
+
+#[test]
+fn test_error_recovery() {
+    let input_string = "\
+a = 0xzz $ 1
+\t\"bad \\q escape\" 'ab' b";
+
+    let mut lexer = Lexer::new(&input_string);
+    let tokens: Vec<Tokens> = lexer.by_ref().map(|token| token.into_token()).collect();
+
+    let desired_output = vec![Identifier("a".to_string()), Symbol(Equals),
+                              Error("No hexadecimal value was found.".to_string()),
+                              Error("Unknown character ($).".to_string()),
+                              Numeric("1".to_string(), None), Indent(1),
+                              Error("Unknown character escape: \\q".to_string()),
+                              Error("Char literal was not closed with a '".to_string()),
+                              Identifier("b".to_string())];
+    assert_eq!(tokens, desired_output);
+
+    let desired_errors = vec![
+        LexError::new("No hexadecimal value was found.".to_string(),
+                      Span::new(Position::new(1, 5, 4), Position::new(1, 9, 8))),
+        LexError::new("Unknown character ($).".to_string(),
+                      Span::new(Position::new(1, 10, 9), Position::new(1, 11, 10))),
+        LexError::new("Unknown character escape: \\q".to_string(),
+                      Span::new(Position::new(2, 2, 14), Position::new(2, 17, 29))),
+        LexError::new("Char literal was not closed with a '".to_string(),
+                      Span::new(Position::new(2, 18, 30), Position::new(2, 22, 34))),
+    ];
+    assert_eq!(lexer.get_errors(), &desired_errors[..]);
+}
@@ -1,11 +1,21 @@
 extern crate limonite;
 
-use limonite::codegen::llvm::LLVMGenerator;
+use limonite::codegen::llvm::{LLVMGenerator, OutputKind};
+use limonite::lexical::lexer::Lexer;
+use limonite::modules::loader::ModuleLoader;
+use limonite::semantic::analyzer::SemanticAnalyzer;
+use limonite::semantic::analyzer_trait::ASTAnalyzer;
+use limonite::semantic::type_checker::TypeChecker;
+use limonite::syntax::parser::Parser;
 use limonite::syntax::expr::{Expr, ExprWrapper};
 use limonite::syntax::op::InfixOp;
 use limonite::syntax::literals::Literals;
 
+use std::env;
+use std::fs;
 use std::mem::transmute;
+use std::path::PathBuf;
+use std::process::{self, Command};
 
 macro_rules! block {
     ($($args:tt)*) => {
@@ -34,6 +44,12 @@ macro_rules! u8 {
     }
 }
 
+macro_rules! u32 {
+    ($arg:tt) => {
+        ExprWrapper::default(Expr::Literal(Literals::U32Num($arg)))
+    }
+}
+
 macro_rules! string {
     ($arg:tt) => {
         ExprWrapper::default(Expr::Literal(Literals::UTF8String($arg.into())))
@@ -50,6 +66,24 @@ macro_rules! op {
     ($left_arg:expr, > $right_arg:expr) => {
         ExprWrapper::default(Expr::InfixOp(InfixOp::Gt, $left_arg, $right_arg))
     };
+    ($left_arg:expr, <= $right_arg:expr) => {
+        ExprWrapper::default(Expr::InfixOp(InfixOp::Lte, $left_arg, $right_arg))
+    };
+    ($left_arg:expr, >= $right_arg:expr) => {
+        ExprWrapper::default(Expr::InfixOp(InfixOp::Gte, $left_arg, $right_arg))
+    };
+    ($left_arg:expr, equals $right_arg:expr) => {
+        ExprWrapper::default(Expr::InfixOp(InfixOp::Equ, $left_arg, $right_arg))
+    };
+    ($left_arg:expr, and $right_arg:expr) => {
+        ExprWrapper::default(Expr::InfixOp(InfixOp::And, $left_arg, $right_arg))
+    };
+    ($left_arg:expr, or $right_arg:expr) => {
+        ExprWrapper::default(Expr::InfixOp(InfixOp::Or, $left_arg, $right_arg))
+    };
+    ($left_arg:expr, not equals $right_arg:expr) => {
+        ExprWrapper::default(Expr::InfixOp(InfixOp::NotEqu, $left_arg, $right_arg))
+    };
 }
 
 macro_rules! assign {
@@ -62,6 +96,15 @@ macro_rules! assign {
     ($left_arg:expr, -= $right_arg:expr) => {
         ExprWrapper::default(Expr::Assign($left_arg, ExprWrapper::default(Expr::InfixOp(InfixOp::Sub, $left_arg, $right_arg))))
     };
+    ($left_arg:expr, *= $right_arg:expr) => {
+        ExprWrapper::default(Expr::Assign($left_arg, ExprWrapper::default(Expr::InfixOp(InfixOp::Mul, $left_arg, $right_arg))))
+    };
+    ($left_arg:expr, /= $right_arg:expr) => {
+        ExprWrapper::default(Expr::Assign($left_arg, ExprWrapper::default(Expr::InfixOp(InfixOp::Div, $left_arg, $right_arg))))
+    };
+    ($left_arg:expr, %= $right_arg:expr) => {
+        ExprWrapper::default(Expr::Assign($left_arg, ExprWrapper::default(Expr::InfixOp(InfixOp::Mod, $left_arg, $right_arg))))
+    };
 }
 
 #[test]
@@ -71,8 +114,8 @@ fn test_sum_function() {
     //     return left + right
 
     let ret = ret!(op!(var!("left"), + var!("right")));
-    let fn_args = vec![("left".into(), "u64".into()), ("right".into(), "u64".into())];
-    let ast = ExprWrapper::default(Expr::FnDecl("add_two_ints".into(), fn_args, Some("u64".into()), ret));
+    let fn_args = vec![("left".into(), "u64".into(), None), ("right".into(), "u64".into(), None)];
+    let ast = ExprWrapper::default(Expr::FnDecl("add_two_ints".into(), fn_args, false, Some("u64".into()), ret));
 
     let mut llvm_generator = LLVMGenerator::new();
 
@@ -107,7 +150,7 @@ fn test_while_lt_increment_u8() {
         while_loop,
         ret
     ];
-    let ast = ExprWrapper::default(Expr::FnDecl("inc_until".into(), Vec::new(), Some("u8".into()), body));
+    let ast = ExprWrapper::default(Expr::FnDecl("inc_until".into(), Vec::new(), false, Some("u8".into()), body));
 
     let mut llvm_generator = LLVMGenerator::new();
 
@@ -142,7 +185,7 @@ fn test_while_gt_decrement_u8() {
         while_loop,
         ret
     ];
-    let ast = ExprWrapper::default(Expr::FnDecl("dec_until".into(), Vec::new(), Some("u8".into()), body));
+    let ast = ExprWrapper::default(Expr::FnDecl("dec_until".into(), Vec::new(), false, Some("u8".into()), body));
 
     let mut llvm_generator = LLVMGenerator::new();
 
@@ -156,6 +199,853 @@ fn test_while_gt_decrement_u8() {
     assert_eq!(dec_until(), 0);
 }
 
+#[test]
+fn test_comparisons() {
+    // Creates limonite functions that look like:
+    // fn lte(left: u32, right: u32) -> bool,
+    //     return left <= right
+    //
+    // fn gte(left: u32, right: u32) -> bool,
+    //     return left >= right
+
+    let ret = ret!(op!(var!("left"), <= var!("right")));
+    let fn_args = vec![("left".into(), "u32".into(), None), ("right".into(), "u32".into(), None)];
+    let ast = ExprWrapper::default(Expr::FnDecl("lte".into(), fn_args, false, Some("bool".into()), ret));
+
+    let mut llvm_generator = LLVMGenerator::new();
+
+    llvm_generator.add_module(ast, false, false);
+    llvm_generator.initialize(true);
+
+    let address = llvm_generator.get_function_address("lte").expect("Could not find function address");
+
+    let lte: extern "C" fn(u32, u32) -> bool = unsafe { transmute(address) };
+
+    assert!(lte(1, 2));
+    assert!(lte(2, 2));
+    assert!(!lte(3, 2));
+
+    let ret = ret!(op!(var!("left"), >= var!("right")));
+    let fn_args = vec![("left".into(), "u32".into(), None), ("right".into(), "u32".into(), None)];
+    let ast = ExprWrapper::default(Expr::FnDecl("gte".into(), fn_args, false, Some("bool".into()), ret));
+
+    let mut llvm_generator = LLVMGenerator::new();
+
+    llvm_generator.add_module(ast, false, false);
+    llvm_generator.initialize(true);
+
+    let address = llvm_generator.get_function_address("gte").expect("Could not find function address");
+
+    let gte: extern "C" fn(u32, u32) -> bool = unsafe { transmute(address) };
+
+    assert!(!gte(1, 2));
+    assert!(gte(2, 2));
+    assert!(gte(3, 2));
+}
+
+#[test]
+fn test_signed_comparisons() {
+    // Whether a comparison is signed comes from the types SA finds, so this one is parsed and checked
+    let input_string = "fn lt(left: i32, right: i32) -> bool
+\treturn left < right
+
+fn lte(left: i32, right: i32) -> bool
+\treturn left <= right
+
+fn gt(left: i32, right: i32) -> bool
+\treturn left > right
+
+fn gte(left: i32, right: i32) -> bool
+\treturn left >= right
+
+fn below_zero() -> bool
+\treturn -1 < 0";
+
+    let mut ast = Parser::new(Lexer::new(input_string)).parse().expect("Expected an ast root");
+    let mut type_checker = TypeChecker::new();
+
+    type_checker.analyze(&mut ast);
+    assert!(type_checker.get_errors().is_empty());
+
+    let mut llvm_generator = LLVMGenerator::new();
+
+    llvm_generator.add_module(ast, true, false);
+    llvm_generator.initialize(true);
+
+    let compare = |name| -> extern "C" fn(i32, i32) -> bool {
+        let address = llvm_generator.get_function_address(name).expect("Could not find function address");
+
+        unsafe { transmute(address) }
+    };
+
+    let (lt, lte, gt, gte) = (compare("lt"), compare("lte"), compare("gt"), compare("gte"));
+
+    assert!(lt(-1, 0));
+    assert!(!lt(0, -1));
+    assert!(lte(-3, -3));
+    assert!(!lte(2, -7));
+    assert!(gt(5, -5));
+    assert!(!gt(-5, 5));
+    assert!(gte(-2, -2));
+    assert!(!gte(-9, 1));
+
+    let address = llvm_generator.get_function_address("below_zero").expect("Could not find function address");
+    let below_zero: extern "C" fn() -> bool = unsafe { transmute(address) };
+
+    assert!(below_zero());
+}
+
+#[test]
+fn test_logical_ops() {
+    // Creates a limonite function that looks like:
+    // fn in_range(left: u32, right: u32) -> bool,
+    //     return left < 10 and right > 5 or left not equals right
+
+    let and = op!(op!(var!("left"), < u32!(10)), and op!(var!("right"), > u32!(5)));
+    let ret = ret!(op!(and, or op!(var!("left"), not equals var!("right"))));
+    let fn_args = vec![("left".into(), "u32".into(), None), ("right".into(), "u32".into(), None)];
+    let ast = ExprWrapper::default(Expr::FnDecl("in_range".into(), fn_args, false, Some("bool".into()), ret));
+
+    let mut llvm_generator = LLVMGenerator::new();
+
+    llvm_generator.add_module(ast, false, false);
+    llvm_generator.initialize(true);
+
+    let address = llvm_generator.get_function_address("in_range").expect("Could not find function address");
+
+    let in_range: extern "C" fn(u32, u32) -> bool = unsafe { transmute(address) };
+
+    assert!(in_range(1, 6));
+    assert!(in_range(20, 6));
+    assert!(!in_range(20, 20));
+    assert!(!in_range(3, 3));
+}
+
+#[test]
+fn test_if_else() {
+    // Creates limonite functions that look like:
+    // fn max(left: u32, right: u32) -> u32,
+    //     if left > right,
+    //         return left
+    //     else,
+    //         return right
+    //
+    // fn clamp(value: u32) -> u32,
+    //     if value > 10,
+    //         return 10
+    //     return value
+
+    let if_else = ExprWrapper::default(Expr::If(op!(var!("left"), > var!("right")),
+                                                block![ret!(var!("left"))],
+                                                Some(block![ret!(var!("right"))])));
+    let fn_args = vec![("left".into(), "u32".into(), None), ("right".into(), "u32".into(), None)];
+    let ast = ExprWrapper::default(Expr::FnDecl("max".into(), fn_args, false, Some("u32".into()), block![if_else]));
+
+    let mut llvm_generator = LLVMGenerator::new();
+
+    llvm_generator.add_module(ast, false, false);
+    llvm_generator.initialize(true);
+
+    let address = llvm_generator.get_function_address("max").expect("Could not find function address");
+
+    let max: extern "C" fn(u32, u32) -> u32 = unsafe { transmute(address) };
+
+    assert_eq!(max(3, 7), 7);
+    assert_eq!(max(7, 3), 7);
+
+    let if_no_else = ExprWrapper::default(Expr::If(op!(var!("value"), > u32!(10)), block![ret!(u32!(10))], None));
+    let fn_args = vec![("value".into(), "u32".into(), None)];
+    let body = block![
+        if_no_else,
+        ret!(var!("value"))
+    ];
+    let ast = ExprWrapper::default(Expr::FnDecl("clamp".into(), fn_args, false, Some("u32".into()), body));
+
+    let mut llvm_generator = LLVMGenerator::new();
+
+    llvm_generator.add_module(ast, false, false);
+    llvm_generator.initialize(true);
+
+    let address = llvm_generator.get_function_address("clamp").expect("Could not find function address");
+
+    let clamp: extern "C" fn(u32) -> u32 = unsafe { transmute(address) };
+
+    assert_eq!(clamp(4), 4);
+    assert_eq!(clamp(40), 10);
+}
+
+#[test]
+fn test_for_loop() {
+    // Creates a limonite function that looks like:
+    // fn sum_range(end: u32, step: u32) -> u32,
+    //     var total = 0u32
+    //
+    //     for i in 1..end by step,
+    //         total += i
+    //
+    //     return total
+
+    let var_decl = ExprWrapper::default(Expr::VarDecl(false, "total".into(), Some("u32".into()), u32!(0)));
+    let loop_body = assign!(var!("total"), += var!("i"));
+    let for_loop = ExprWrapper::default(Expr::ForLoop("i".into(), u32!(1), var!("end"), Some(var!("step")), loop_body));
+    let body = block![
+        var_decl,
+        for_loop,
+        ret!(var!("total"))
+    ];
+    let fn_args = vec![("end".into(), "u32".into(), None), ("step".into(), "u32".into(), None)];
+    let ast = ExprWrapper::default(Expr::FnDecl("sum_range".into(), fn_args, false, Some("u32".into()), body));
+
+    let mut llvm_generator = LLVMGenerator::new();
+
+    llvm_generator.add_module(ast, false, false);
+    llvm_generator.initialize(true);
+
+    let address = llvm_generator.get_function_address("sum_range").expect("Could not find function address");
+
+    let sum_range: extern "C" fn(u32, u32) -> u32 = unsafe { transmute(address) };
+
+    assert_eq!(sum_range(6, 1), 15);
+    assert_eq!(sum_range(10, 2), 25);
+    assert_eq!(sum_range(1, 1), 0);
+}
+
+#[test]
+fn test_signed_for_loop() {
+    let input_string = "fn sum_signed() -> i32
+\tvar total = 0
+\tfor i in -5..4,
+\t\ttotal = total + i
+\treturn total
+
+fn count_pairs(n: i32) -> i32
+\tvar count = 0
+\tfor i in 0..n,
+\t\tfor j in -3..3 by 2,
+\t\t\tcount = count + 1
+\treturn count";
+
+    let mut ast = Parser::new(Lexer::new(input_string)).parse().expect("Expected an ast root");
+    let mut type_checker = TypeChecker::new();
+
+    type_checker.analyze(&mut ast);
+    assert!(type_checker.get_errors().is_empty());
+
+    let mut llvm_generator = LLVMGenerator::new();
+
+    llvm_generator.add_module(ast, true, false);
+    llvm_generator.initialize(true);
+
+    // Loop variables are allocated once, up front, rather than on every run of the outer loop
+    let count_pairs_ir = llvm_generator.get_function_ir("count_pairs").expect("Could not find function");
+    let later_blocks = count_pairs_ir.splitn(2, "\n\n").nth(1).unwrap_or("");
+
+    assert!(!later_blocks.contains("alloca"), "{}", count_pairs_ir);
+
+    let sum_signed_address = llvm_generator.get_function_address("sum_signed").expect("Could not find function address");
+    let count_pairs_address = llvm_generator.get_function_address("count_pairs").expect("Could not find function address");
+
+    let sum_signed: extern "C" fn() -> i32 = unsafe { transmute(sum_signed_address) };
+    let count_pairs: extern "C" fn(i32) -> i32 = unsafe { transmute(count_pairs_address) };
+
+    assert_eq!(sum_signed(), -9);
+    assert_eq!(count_pairs(3), 9);
+    assert_eq!(count_pairs(0), 0);
+}
+
+#[test]
+fn test_break_continue() {
+    // Creates limonite functions that look like:
+    // fn sum_between(low: u32, high: u32) -> u32,
+    //     var total = 0u32
+    //
+    //     for i in 0..100,
+    //         if i equals high,
+    //             break
+    //         if i < low,
+    //             continue
+    //         total += i
+    //
+    //     return total
+    //
+    // fn first_over(limit: u32) -> u32,
+    //     var i = 0u32
+    //
+    //     while true,
+    //         i += 1
+    //         if i > limit,
+    //             break
+    //
+    //     return i
+
+    let var_decl = ExprWrapper::default(Expr::VarDecl(false, "total".into(), Some("u32".into()), u32!(0)));
+    let break_if = ExprWrapper::default(Expr::If(op!(var!("i"), equals var!("high")), block![ExprWrapper::default(Expr::Break)], None));
+    let continue_if = ExprWrapper::default(Expr::If(op!(var!("i"), < var!("low")), block![ExprWrapper::default(Expr::Continue)], None));
+    let loop_body = block![
+        break_if,
+        continue_if,
+        assign!(var!("total"), += var!("i"))
+    ];
+    let for_loop = ExprWrapper::default(Expr::ForLoop("i".into(), u32!(0), u32!(100), None, loop_body));
+    let body = block![
+        var_decl,
+        for_loop,
+        ret!(var!("total"))
+    ];
+    let fn_args = vec![("low".into(), "u32".into(), None), ("high".into(), "u32".into(), None)];
+    let ast = ExprWrapper::default(Expr::FnDecl("sum_between".into(), fn_args, false, Some("u32".into()), body));
+
+    let mut llvm_generator = LLVMGenerator::new();
+
+    llvm_generator.add_module(ast, false, false);
+    llvm_generator.initialize(true);
+
+    let address = llvm_generator.get_function_address("sum_between").expect("Could not find function address");
+
+    let sum_between: extern "C" fn(u32, u32) -> u32 = unsafe { transmute(address) };
+
+    assert_eq!(sum_between(3, 6), 12);
+    assert_eq!(sum_between(0, 0), 0);
+
+    let var_decl = ExprWrapper::default(Expr::VarDecl(false, "i".into(), Some("u32".into()), u32!(0)));
+    let break_if = ExprWrapper::default(Expr::If(op!(var!("i"), > var!("limit")), block![ExprWrapper::default(Expr::Break)], None));
+    let loop_body = block![
+        assign!(var!("i"), += u32!(1)),
+        break_if
+    ];
+    let while_loop = ExprWrapper::default(Expr::WhileLoop(ExprWrapper::default(Expr::Literal(Literals::Bool(true))), loop_body));
+    let body = block![
+        var_decl,
+        while_loop,
+        ret!(var!("i"))
+    ];
+    let fn_args = vec![("limit".into(), "u32".into(), None)];
+    let ast = ExprWrapper::default(Expr::FnDecl("first_over".into(), fn_args, false, Some("u32".into()), body));
+
+    let mut llvm_generator = LLVMGenerator::new();
+
+    llvm_generator.add_module(ast, false, false);
+    llvm_generator.initialize(true);
+
+    let address = llvm_generator.get_function_address("first_over").expect("Could not find function address");
+
+    let first_over: extern "C" fn(u32) -> u32 = unsafe { transmute(address) };
+
+    assert_eq!(first_over(4), 5);
+}
+
+#[test]
+fn test_assert() {
+    // Creates a limonite function that looks like:
+    // fn checked_add(left: u32, right: u32) -> u32,
+    //     assert left < 100, "left is too big"
+    //     assert right < 100
+    //     return left + right
+    //
+    // A failing assert exits the process, so only passing ones are run
+
+    let body = block![
+        ExprWrapper::default(Expr::Assert(op!(var!("left"), < u32!(100)), Some("left is too big".into()), "left < 100".into())),
+        ExprWrapper::default(Expr::Assert(op!(var!("right"), < u32!(100)), None, "right < 100".into())),
+        ret!(op!(var!("left"), + var!("right")))
+    ];
+    let fn_args = vec![("left".into(), "u32".into(), None), ("right".into(), "u32".into(), None)];
+    let ast = ExprWrapper::default(Expr::FnDecl("checked_add".into(), fn_args, false, Some("u32".into()), body));
+
+    let mut llvm_generator = LLVMGenerator::new();
+
+    llvm_generator.set_file_name("test.lim");
+    llvm_generator.add_module(ast, false, false);
+    llvm_generator.initialize(true);
+
+    let address = llvm_generator.get_function_address("checked_add").expect("Could not find function address");
+
+    let checked_add: extern "C" fn(u32, u32) -> u32 = unsafe { transmute(address) };
+
+    assert_eq!(checked_add(40, 2), 42);
+}
+
+#[test]
+fn test_compound_assignment() {
+    // Creates a limonite function that looks like:
+    // fn scramble(value: u32) -> u32,
+    //     var x = value
+    //     x *= 7
+    //     x /= 2
+    //     x %= 10
+    //     return x
+
+    let body = block![
+        ExprWrapper::default(Expr::VarDecl(false, "x".into(), Some("u32".into()), var!("value"))),
+        assign!(var!("x"), *= u32!(7)),
+        assign!(var!("x"), /= u32!(2)),
+        assign!(var!("x"), %= u32!(10)),
+        ret!(var!("x"))
+    ];
+    let fn_args = vec![("value".into(), "u32".into(), None)];
+    let ast = ExprWrapper::default(Expr::FnDecl("scramble".into(), fn_args, false, Some("u32".into()), body));
+
+    let mut llvm_generator = LLVMGenerator::new();
+
+    llvm_generator.add_module(ast, false, false);
+    llvm_generator.initialize(true);
+
+    let address = llvm_generator.get_function_address("scramble").expect("Could not find function address");
+
+    let scramble: extern "C" fn(u32) -> u32 = unsafe { transmute(address) };
+
+    assert_eq!(scramble(5), 7);
+    assert_eq!(scramble(9), 1);
+}
+
+#[test]
+fn test_signed_division() {
+    let input_string = "fn quotient(left: i32, right: i32) -> i32
+\treturn left / right
+
+fn remainder(left: i32, right: i32) -> i32
+\treturn left % right
+
+fn remainder_assign(value: i32) -> i32
+\tvar x = value
+\tx %= 3
+\treturn x
+
+fn literal_remainder() -> i32
+\treturn -7 % 3";
+
+    let mut ast = Parser::new(Lexer::new(input_string)).parse().expect("Expected an ast root");
+    let mut type_checker = TypeChecker::new();
+
+    type_checker.analyze(&mut ast);
+    assert!(type_checker.get_errors().is_empty());
+
+    let mut llvm_generator = LLVMGenerator::new();
+
+    llvm_generator.add_module(ast, true, false);
+    llvm_generator.initialize(true);
+
+    let quotient_address = llvm_generator.get_function_address("quotient").expect("Could not find function address");
+    let remainder_address = llvm_generator.get_function_address("remainder").expect("Could not find function address");
+    let remainder_assign_address = llvm_generator.get_function_address("remainder_assign").expect("Could not find function address");
+    let literal_remainder_address = llvm_generator.get_function_address("literal_remainder").expect("Could not find function address");
+
+    let quotient: extern "C" fn(i32, i32) -> i32 = unsafe { transmute(quotient_address) };
+    let remainder: extern "C" fn(i32, i32) -> i32 = unsafe { transmute(remainder_address) };
+    let remainder_assign: extern "C" fn(i32) -> i32 = unsafe { transmute(remainder_assign_address) };
+    let literal_remainder: extern "C" fn() -> i32 = unsafe { transmute(literal_remainder_address) };
+
+    assert_eq!(quotient(-7, 2), -3);
+    assert_eq!(quotient(7, -2), -3);
+    assert_eq!(remainder(-7, 3), -1);
+    assert_eq!(remainder(7, -3), 1);
+    assert_eq!(remainder_assign(-7), -1);
+    assert_eq!(remainder_assign(8), 2);
+    assert_eq!(literal_remainder(), -1);
+}
+
+#[test]
+fn test_var_args() {
+    // Creates a limonite function that looks like:
+    // fn climb(n: u32, ...) -> u32,
+    //     if n > 5,
+    //         return n
+    //     return climb(n + 1, n)
+
+    let climb_call = ExprWrapper::default(Expr::FnCall("climb".into(), vec![op!(var!("n"), + u32!(1)), var!("n")]));
+    let body = block![
+        ExprWrapper::default(Expr::If(op!(var!("n"), > u32!(5)), block![ret!(var!("n"))], None)),
+        ret!(climb_call)
+    ];
+    let fn_args = vec![("n".into(), "u32".into(), None)];
+    let ast = ExprWrapper::default(Expr::FnDecl("climb".into(), fn_args, true, Some("u32".into()), body));
+
+    let mut llvm_generator = LLVMGenerator::new();
+
+    llvm_generator.add_module(ast, false, false);
+    llvm_generator.initialize(true);
+
+    let address = llvm_generator.get_function_address("climb").expect("Could not find function address");
+
+    let climb: extern "C" fn(u32, ...) -> u32 = unsafe { transmute(address) };
+
+    assert_eq!(climb(0), 6);
+    assert_eq!(climb(3, 1u32, 2u32), 6);
+}
+
+#[test]
+fn test_fn_params() {
+    // Creates a limonite function that looks like:
+    // fn digits(n: u32) -> u8,
+    //     var count = 1
+    //     while n >= 10,
+    //         n /= 10
+    //         count += 1
+    //     return count
+
+    let loop_body = block![
+        assign!(var!("n"), /= u32!(10)),
+        assign!(var!("count"), += u8!(1))
+    ];
+    let body = block![
+        ExprWrapper::default(Expr::VarDecl(false, "count".into(), Some("u8".into()), u8!(1))),
+        ExprWrapper::default(Expr::WhileLoop(op!(var!("n"), >= u32!(10)), loop_body)),
+        ret!(var!("count"))
+    ];
+    let fn_args = vec![("n".into(), "u32".into(), None)];
+    let ast = ExprWrapper::default(Expr::FnDecl("digits".into(), fn_args, false, Some("u8".into()), body));
+
+    let mut llvm_generator = LLVMGenerator::new();
+
+    llvm_generator.add_module(ast, false, false);
+    llvm_generator.initialize(true);
+
+    let address = llvm_generator.get_function_address("digits").expect("Could not find function address");
+
+    let digits: extern "C" fn(u32) -> u8 = unsafe { transmute(address) };
+
+    assert_eq!(digits(7), 1);
+    assert_eq!(digits(4096), 4);
+}
+
+#[test]
+fn test_nested_fn_calls() {
+    // Creates a limonite script that looks like:
+    // fn quadruple(n: u32) -> u32,
+    //     return double(double(n))
+    //
+    // fn double(n: u32) -> u32,
+    //     var m = n
+    //     m += n
+    //     return m
+
+    let double_body = block![
+        ExprWrapper::default(Expr::VarDecl(false, "m".into(), Some("u32".into()), var!("n"))),
+        assign!(var!("m"), += var!("n")),
+        ret!(var!("m"))
+    ];
+    let double_call = |arg| ExprWrapper::default(Expr::FnCall("double".into(), vec![arg]));
+    let quadruple_body = block![
+        ret!(double_call(double_call(var!("n"))))
+    ];
+    let script = block![
+        ExprWrapper::default(Expr::FnDecl("quadruple".into(), vec![("n".into(), "u32".into(), None)], false, Some("u32".into()), quadruple_body)),
+        ExprWrapper::default(Expr::FnDecl("double".into(), vec![("n".into(), "u32".into(), None)], false, Some("u32".into()), double_body))
+    ];
+
+    let mut llvm_generator = LLVMGenerator::new();
+
+    llvm_generator.add_module(script, true, false);
+    llvm_generator.initialize(true);
+
+    let address = llvm_generator.get_function_address("quadruple").expect("Could not find function address");
+
+    let quadruple: extern "C" fn(u32) -> u32 = unsafe { transmute(address) };
+
+    assert_eq!(quadruple(3), 12);
+    assert_eq!(quadruple(10), 40);
+}
+
+#[test]
+fn test_imported_module() {
+    // Creates two limonite modules that look like:
+    // >> magical_butterflies.lim
+    // fn fly(n: u32) -> u32,
+    //     return n + 1
+    //
+    // >> main.lim
+    // use magical_butterflies
+    // fn fly_twice(n: u32) -> u32,
+    //     return magical_butterflies.fly(magical_butterflies.fly(n))
+
+    let fly_body = block![ret!(op!(var!("n"), + u32!(1)))];
+    let imported = block![
+        ExprWrapper::default(Expr::FnDecl("magical_butterflies.fly".into(), vec![("n".into(), "u32".into(), None)], false, Some("u32".into()), fly_body))
+    ];
+
+    let fly_call = |arg| ExprWrapper::default(Expr::FnCall("magical_butterflies.fly".into(), vec![arg]));
+    let fly_twice_body = block![ret!(fly_call(fly_call(var!("n"))))];
+    let fn_args = vec![("n".into(), "u32".into(), None)];
+    let ast = ExprWrapper::default(Expr::FnDecl("fly_twice".into(), fn_args, false, Some("u32".into()), fly_twice_body));
+
+    let mut llvm_generator = LLVMGenerator::new();
+
+    llvm_generator.add_imported_module("magical_butterflies", imported);
+    llvm_generator.add_module(ast, false, false);
+    llvm_generator.initialize(true);
+
+    let address = llvm_generator.get_function_address("fly_twice").expect("Could not find function address");
+
+    let fly_twice: extern "C" fn(u32) -> u32 = unsafe { transmute(address) };
+
+    assert_eq!(fly_twice(5), 7);
+}
+
+#[test]
+fn test_classes() {
+    // Objects need SA to work out the class of each field access and method call,
+    // so this one is parsed and checked rather than built by hand
+    let input_string = "class Shape
+\tvar sides: i32 = 0
+
+\tfn area() -> i32
+\t\treturn 0
+
+\tfn describe() -> i32
+\t\treturn self.area() + self.sides
+
+class Square from Shape
+\tvar width: i32 = 1
+
+\tfn area() -> i32
+\t\treturn self.width * self.width
+
+fn describe_square(width: i32) -> i32
+\tvar shape: Shape = Square(3, width)
+\treturn shape.describe()
+
+fn grow(width: i32) -> i32
+\tvar square = Square()
+\tsquare.width = width + 1
+\treturn square.area()";
+
+    let mut ast = Parser::new(Lexer::new(input_string)).parse().expect("Expected an ast root");
+    let mut type_checker = TypeChecker::new();
+
+    type_checker.analyze(&mut ast);
+    assert!(type_checker.get_errors().is_empty());
+
+    let mut llvm_generator = LLVMGenerator::new();
+
+    llvm_generator.add_module(ast, true, false);
+    llvm_generator.initialize(true);
+
+    let describe_square_address = llvm_generator.get_function_address("describe_square").expect("Could not find function address");
+    let grow_address = llvm_generator.get_function_address("grow").expect("Could not find function address");
+
+    let describe_square: extern "C" fn(i32) -> i32 = unsafe { transmute(describe_square_address) };
+    let grow: extern "C" fn(i32) -> i32 = unsafe { transmute(grow_address) };
+
+    // Shape's describe calls Square's area through the vtable
+    assert_eq!(describe_square(4), 19);
+    assert_eq!(grow(2), 9);
+}
+
+#[test]
+fn test_when() {
+    let input_string = "class TooBig
+\tpass
+
+class TooSmall
+\tpass
+
+fn check(n: i32) -> i32
+\tif n > 10,
+\t\traise TooBig, \"n is too big\"
+\treturn n
+
+fn double(n: i32) -> i32
+\treturn n * 2
+
+fn quadruple(n: i32) -> i32
+\treturn double(double(n))
+
+fn checked_twice(n: i32) -> i32
+\treturn check(n) + check(n)
+
+fn clamp(n: i32) -> i32
+\tvar result = 0
+\twhen,
+\t\tresult = checked_twice(n)
+\tthrows TooSmall or TooBig as e,
+\t\tresult = 10
+\telse,
+\t\tresult += 100
+\treturn result
+
+fn unhandled(n: i32) -> i32
+\tvar result = 0
+\twhen,
+\t\twhen,
+\t\t\tresult = check(n)
+\t\tthrows TooSmall,
+\t\t\tresult = 1
+\tthrows TooBig,
+\t\tresult = 2
+\treturn result";
+
+    let mut ast = Parser::new(Lexer::new(input_string)).parse().expect("Expected an ast root");
+    let mut type_checker = TypeChecker::new();
+
+    type_checker.analyze(&mut ast);
+    assert!(type_checker.get_errors().is_empty());
+
+    let mut llvm_generator = LLVMGenerator::new();
+
+    llvm_generator.add_module(ast, true, false);
+
+    // Only calls which can raise are followed by a check for an error
+    let ir = |name| llvm_generator.get_function_ir(name).expect("Could not find function");
+
+    assert!(ir("checked_twice").contains("limonite.error_kind"));
+    assert!(!ir("quadruple").contains("limonite.error_kind"));
+
+    llvm_generator.initialize(true);
+
+    let clamp_address = llvm_generator.get_function_address("clamp").expect("Could not find function address");
+    let unhandled_address = llvm_generator.get_function_address("unhandled").expect("Could not find function address");
+
+    let clamp: extern "C" fn(i32) -> i32 = unsafe { transmute(clamp_address) };
+    let unhandled: extern "C" fn(i32) -> i32 = unsafe { transmute(unhandled_address) };
+
+    // Errors raised in a call leave the rest of the body and skip the else
+    assert_eq!(clamp(3), 106);
+    assert_eq!(clamp(20), 10);
+    assert_eq!(clamp(4), 108);
+
+    // An inner when without a handler for the error passes it to the outer one
+    assert_eq!(unhandled(20), 2);
+    assert_eq!(unhandled(5), 5);
+}
+
+#[test]
+fn test_save_binary() {
+    let generate = |input_string: &str| {
+        let mut ast = Parser::new(Lexer::new(input_string)).parse().expect("Expected an ast root");
+        let mut type_checker = TypeChecker::new();
+
+        type_checker.analyze(&mut ast);
+        assert!(type_checker.get_errors().is_empty());
+
+        let mut llvm_generator = LLVMGenerator::new();
+
+        llvm_generator.set_file_name("maths.lim");
+        llvm_generator.add_module(ast, true, false);
+        llvm_generator
+    };
+
+    let dir = env::temp_dir().join(format!("limonite-test-save-binary-{}", process::id()));
+    fs::create_dir_all(&dir).expect("Could not create a build directory");
+
+    let passing = generate("fn double(n: i32) -> i32\n\treturn n * 2\nassert double(2) equals 4");
+    let failing = generate("assert 1 + 1 equals 3, \"bad maths\"");
+
+    passing.save_binary(&dir.join("passing"), OutputKind::Executable).expect("Could not build an executable");
+    failing.save_binary(&dir.join("failing"), OutputKind::Executable).expect("Could not build an executable");
+
+    let passed = Command::new(dir.join("passing")).output().expect("Could not run the executable");
+    let failed = Command::new(dir.join("failing")).output().expect("Could not run the executable");
+
+    assert!(passed.status.success());
+    assert_eq!(failed.status.code(), Some(1));
+    assert_eq!(String::from_utf8_lossy(&failed.stderr), "maths.lim:1: Assertion failed: 1 + 1 equals 3, bad maths\n");
+
+    // An error nothing handles is reported where it was raised, and ends the program
+    let uncaught = generate("fn check(n: i32) -> i32\n\tif n > 1,\n\t\traise ValueError, \"bad value\"\n\treturn n\ncheck(2)");
+    uncaught.save_binary(&dir.join("uncaught"), OutputKind::Executable).expect("Could not build an executable");
+
+    let raised = Command::new(dir.join("uncaught")).output().expect("Could not run the executable");
+    assert_eq!(raised.status.code(), Some(1));
+    assert_eq!(String::from_utf8_lossy(&raised.stderr), "maths.lim:3: Uncaught ValueError: bad value\n");
+
+    // Each module is compiled to its own object file, so their constant strings can't clash when linked
+    let source = "use checks\nassert checks.halve(8) equals 4, \"bad halving\"";
+    let mut modules = ModuleLoader::new(vec![PathBuf::from("tests/lang/modules")]).load("maths.lim", source.to_string())
+                                                                                  .expect("Expected the imports to load");
+    let mut main_module = modules.pop().expect("Expected the entry file to be loaded");
+    let mut checks = modules.pop().expect("Expected the imported module to be loaded");
+
+    let mut semantic_analyzer = SemanticAnalyzer::new();
+    semantic_analyzer.declare_module(checks.get_ast());
+
+    assert!(SemanticAnalyzer::new().analyze(checks.get_mut_ast()).is_ok());
+    assert!(semantic_analyzer.analyze(main_module.get_mut_ast()).is_ok());
+
+    let mut importing = LLVMGenerator::new();
+
+    importing.set_file_name(checks.get_file_name());
+    importing.add_imported_module("checks", checks.into_ast());
+    importing.set_file_name("maths.lim");
+    importing.add_module(main_module.into_ast(), true, false);
+    importing.save_binary(&dir.join("importing"), OutputKind::Executable).expect("Could not build an executable");
+
+    let imported = Command::new(dir.join("importing")).output().expect("Could not run the executable");
+    assert!(imported.status.success());
+
+    for &(output_kind, file_name) in &[(OutputKind::Object, "passing.o"), (OutputKind::Assembly, "passing.s"), (OutputKind::Bitcode, "passing.bc")] {
+        passing.save_binary(&dir.join(file_name), output_kind).expect("Could not save the module");
+
+        let metadata = fs::metadata(dir.join(file_name)).expect("Expected the module to be saved");
+        assert!(metadata.len() > 0);
+    }
+
+    let assembly = fs::read_to_string(dir.join("passing.s")).expect("Could not read the assembly");
+    assert!(assembly.contains("double"));
+
+    // Other machines can be compiled for too, whose objects say which they're for (183 is AArch64's ELF machine)
+    let mut cross = generate("fn double(n: i32) -> i32\n\treturn n * 2\nassert double(2) equals 4");
+    cross.set_target_triple("aarch64-unknown-linux-gnu");
+    cross.set_target_cpu("cortex-a53");
+    cross.save_binary(&dir.join("cross.o"), OutputKind::Object).expect("Could not save the module");
+
+    let object = fs::read(dir.join("cross.o")).expect("Could not read the object");
+    assert_eq!(&object[..4], b"\x7fELF");
+    assert_eq!(object[18..20], [183, 0]);
+
+    cross.set_target_triple("not-a-machine");
+    assert!(cross.save_binary(&dir.join("bad.o"), OutputKind::Object).is_err());
+
+    fs::remove_dir_all(&dir).expect("Could not remove the build directory");
+}
+
+#[test]
+fn test_optimization_levels() {
+    let input_string = "fn sum_to(n: i32) -> i32
+\tvar total = 0
+\tfor i in 0..n,
+\t\ttotal = total + i
+\treturn total
+
+class Counter
+\tvar count: i32 = 0
+
+\tfn step() -> i32
+\t\treturn self.count + 1
+
+fn count_twice(start: i32) -> i32
+\tvar counter = Counter(start)
+\treturn counter.step() + counter.step()";
+
+    // Every level should give the same answers, only faster
+    for opt_level in 0..4 {
+        let mut ast = Parser::new(Lexer::new(input_string)).parse().expect("Expected an ast root");
+        let mut type_checker = TypeChecker::new();
+
+        type_checker.analyze(&mut ast);
+        assert!(type_checker.get_errors().is_empty());
+
+        let mut llvm_generator = LLVMGenerator::new();
+
+        llvm_generator.set_optimization_level(opt_level);
+        llvm_generator.add_module(ast, true, false);
+        llvm_generator.initialize(true);
+
+        // The passes really ran if the locals left the stack for registers
+        let sum_to_ir = llvm_generator.get_function_ir("sum_to").expect("Could not find function");
+        assert_eq!(sum_to_ir.contains("alloca"), opt_level == 0, "-O{}:\n{}", opt_level, sum_to_ir);
+
+        let sum_to_address = llvm_generator.get_function_address("sum_to").expect("Could not find function address");
+        let count_twice_address = llvm_generator.get_function_address("count_twice").expect("Could not find function address");
+
+        let sum_to: extern "C" fn(i32) -> i32 = unsafe { transmute(sum_to_address) };
+        let count_twice: extern "C" fn(i32) -> i32 = unsafe { transmute(count_twice_address) };
+
+        assert_eq!(sum_to(0), 0);
+        assert_eq!(sum_to(100), 4950);
+        assert_eq!(count_twice(4), 10);
+    }
+}
+
 #[test]
 fn test_hello_world() {
     // Creates a limonite function that looks like:
@@ -172,7 +1062,7 @@ fn test_hello_world() {
         print_call,
         ret!(),
     ];
-    let ast = ExprWrapper::default(Expr::FnDecl("hello_world".into(), Vec::new(), None, body));
+    let ast = ExprWrapper::default(Expr::FnDecl("hello_world".into(), Vec::new(), false, None, body));
 
     let mut llvm_generator = LLVMGenerator::new();
 
@@ -0,0 +1,91 @@
+extern crate limonite;
+
+use std::path::PathBuf;
+
+use limonite::modules::error::ModuleErrorKind;
+use limonite::modules::loader::ModuleLoader;
+use limonite::syntax::expr::{Expr, ExprWrapper};
+use limonite::syntax::literals::Literals;
+use limonite::syntax::op::InfixOp;
+
+fn loader() -> ModuleLoader {
+    ModuleLoader::new(vec![PathBuf::from("tests/lang/modules")])
+}
+
+#[test]
+fn test_load_imports() {
+    let input_string = "use std.io.print\nuse garden.flowers\nuse magical_butterflies as magic\nmagic.fly(flowers.bloom())";
+
+    let modules = loader().load("main.lim", input_string.to_string()).expect("Expected the imports to load");
+
+    // Each module comes after the ones it imports
+    let names: Vec<&str> = modules.iter().map(|module| module.get_name()).collect();
+    assert_eq!(names, vec!["magical_butterflies", "garden.flowers", "main"]);
+    assert_eq!(modules[2].get_imports(), &["garden.flowers".to_string(), "magical_butterflies".to_string()]);
+
+    // Imported functions are named after their module, including calls between them
+    let butterflies = ExprWrapper::default(Expr::Block(vec![
+        ExprWrapper::default(Expr::FnDecl("magical_butterflies.fly".to_string(), vec![("n".to_string(), "i32".to_string(), None)],
+                                          false, Some("i32".to_string()), ExprWrapper::default(Expr::Block(vec![
+            ExprWrapper::default(Expr::Return(Some(ExprWrapper::default(Expr::InfixOp(
+                InfixOp::Add,
+                ExprWrapper::default(Expr::FnCall("magical_butterflies.wings".to_string(),
+                                                  vec![ExprWrapper::default(Expr::Var("n".to_string()))])),
+                ExprWrapper::default(Expr::Literal(Literals::I32Num(1))),
+            ))))),
+        ])))),
+        ExprWrapper::default(Expr::FnDecl("magical_butterflies.wings".to_string(), vec![("n".to_string(), "i32".to_string(), None)],
+                                          false, Some("i32".to_string()), ExprWrapper::default(Expr::Block(vec![
+            ExprWrapper::default(Expr::Return(Some(ExprWrapper::default(Expr::InfixOp(
+                InfixOp::Mul,
+                ExprWrapper::default(Expr::Var("n".to_string())),
+                ExprWrapper::default(Expr::Literal(Literals::I32Num(2))),
+            ))))),
+        ])))),
+    ]));
+    assert_eq!(*modules[0].get_ast(), butterflies);
+
+    // Calls through an import's name or alias are pointed at the renamed functions
+    if let Expr::Block(ref exprs) = *modules[2].get_ast().get_expr() {
+        assert_eq!(*exprs[3].get_expr(), Expr::FnCall("magical_butterflies.fly".to_string(), vec![
+            ExprWrapper::default(Expr::FnCall("garden.flowers.bloom".to_string(), vec![])),
+        ]));
+    } else {
+        panic!("Expected a block");
+    }
+}
+
+#[test]
+fn test_load_errors() {
+    let mut loader = loader();
+    let errors = match loader.load("main.lim", "use missing\nuse circular_a".to_string()) {
+        Ok(_) => panic!("Expected the imports to fail"),
+        Err(errors) => errors,
+    };
+
+    let kinds: Vec<&ModuleErrorKind> = errors.iter().map(|error| error.get_kind()).collect();
+    assert_eq!(kinds, vec![
+        &ModuleErrorKind::NotFound { name: "missing".to_string(), searched: vec!["tests/lang/modules/missing.lim".to_string()] },
+        &ModuleErrorKind::CircularImport { name: "circular_a".to_string() },
+    ]);
+
+    // Each error is shown in the file with the offending `use`
+    assert_eq!(errors[0].get_file_name(), "main.lim");
+    assert_eq!(errors[1].get_file_name(), "tests/lang/modules/circular_b.lim");
+    assert_eq!(loader.get_source("tests/lang/modules/circular_b.lim"), Some("use circular_a\n"));
+}
+
+#[test]
+fn test_load_class_in_module() {
+    let errors = match loader().load("main.lim", "use shapes".to_string()) {
+        Ok(_) => panic!("Expected the import to fail"),
+        Err(errors) => errors,
+    };
+
+    let kinds: Vec<&ModuleErrorKind> = errors.iter().map(|error| error.get_kind()).collect();
+    assert_eq!(kinds, vec![&ModuleErrorKind::TopLevelStatement { name: "shapes".to_string() }]);
+
+    // Classes can only be declared in the file being run, which the error points out
+    assert_eq!(errors[0].get_file_name(), "tests/lang/modules/shapes.lim");
+    assert!(errors[0].to_diagnostic().get_help().iter().any(|help| help.contains("classes")));
+}
@@ -3,12 +3,14 @@ extern crate env_logger;
 
 use std::vec::IntoIter;
 
-use limonite::lexical::lexer::Tokenizer;
-use limonite::lexical::tokens::Tokens;
+use limonite::lexical::lexer::{Lexer, Tokenizer};
+use limonite::lexical::span::{Position, Span};
+use limonite::lexical::tokens::{Token, Tokens};
 use limonite::lexical::tokens::Tokens::*;
 use limonite::lexical::types::Types;
 use limonite::lexical::keywords::Keywords;
 use limonite::lexical::symbols::Symbols;
+use limonite::syntax::error::ParseErrorKind;
 use limonite::syntax::parser::Parser;
 use limonite::syntax::expr::{Expr, ExprWrapper};
 use limonite::syntax::literals::*;
@@ -26,27 +28,24 @@ impl MockLexer {
     }
 }
 
-impl Iterator for MockLexer {
-    type Item = Tokens;
-    fn next(&mut self) -> Option<Tokens> {
+impl Tokenizer for MockLexer {
+    fn next_token(&mut self) -> Option<Token> {
         let next = self.tokens.next();
         match next {
-            Some(tok) => Some(tok),
+            Some(tok) => Some(Token::new(tok, Span::default())),
             None => None,
         }
     }
 }
 
-impl Tokenizer for MockLexer {}
-
 fn general_expect_test(tokens: Vec<Tokens>,
                        expected: Vec<ExprWrapper>,
                        should_match: bool) {
     let lexer = MockLexer::new(tokens);
     let mut parser = Parser::new(lexer);
     let ast_root = match parser.parse() {
-        Some(ast) => ast,
-        None => {
+        Ok(ast) => ast,
+        Err(_) => {
             if should_match {
                 panic!("Expected an ast root");
             }
@@ -122,7 +121,7 @@ fn test_valid_fn_definition() {
     ];
     let desired_ast = vec![
         ExprWrapper::default(
-            Expr::FnDecl("foo".to_string(), Vec::new(), Some("int".to_string()),
+            Expr::FnDecl("foo".to_string(), Vec::new(), false, Some("int".to_string()),
             ExprWrapper::default(Expr::Block(Vec::new()))))
     ];
     expect_test(tokens, desired_ast);
@@ -142,10 +141,10 @@ fn test_valid_fn_definition() {
     ];
 
     // One arg: foo(bar: i32) -> str
-    let args = vec![("bar".to_string(), "int".to_string())];
+    let args = vec![("bar".to_string(), "int".to_string(), None)];
     let desired_ast = vec![
         ExprWrapper::default(
-            Expr::FnDecl("foo".to_string(), args, Some("str".to_string()),
+            Expr::FnDecl("foo".to_string(), args, false, Some("str".to_string()),
             ExprWrapper::default(Expr::Block(Vec::new()))))
     ];
     expect_test(tokens, desired_ast);
@@ -173,12 +172,12 @@ fn test_valid_fn_definition() {
     ];
     //
     // Multiple args: foo(bar: i32, left: Obj, right: Obj) -> None
-    let args = vec![("bar".to_string(), "int".to_string()),
-                    ("left".to_string(), "Obj".to_string()),
-                    ("right".to_string(), "Obj".to_string())];
+    let args = vec![("bar".to_string(), "int".to_string(), None),
+                    ("left".to_string(), "Obj".to_string(), None),
+                    ("right".to_string(), "Obj".to_string(), None)];
     let desired_ast = vec![
         ExprWrapper::default(
-            Expr::FnDecl("foo".to_string(), args, Some("None".to_string()),
+            Expr::FnDecl("foo".to_string(), args, false, Some("None".to_string()),
             ExprWrapper::default(Expr::Block(Vec::new()))))
     ];
     expect_test(tokens, desired_ast);
@@ -241,6 +240,94 @@ fn test_expression_precedence_add_mult() {
     expect_test(tokens, desired_ast);
 }
 
+#[test]
+fn test_expression_precedence_comparison() {
+    // Make sure a < b + c >= d generates (a < (b + c)) >= d
+    let tokens = vec![
+        Keyword(Keywords::If),
+        Identifier("a".to_string()),
+        Symbol(Symbols::LessThan),
+        Identifier("b".to_string()),
+        Symbol(Symbols::Plus),
+        Identifier("c".to_string()),
+        Symbol(Symbols::GreaterThanEqual),
+        Identifier("d".to_string()),
+        Symbol(Symbols::Comma),
+        Indent(1)
+    ];
+
+    let add = ExprWrapper::default(Expr::InfixOp(InfixOp::Add,
+              ExprWrapper::default(Expr::Var("b".to_string())),
+              ExprWrapper::default(Expr::Var("c".to_string()))));
+    let lt = ExprWrapper::default(Expr::InfixOp(InfixOp::Lt,
+             ExprWrapper::default(Expr::Var("a".to_string())), add));
+    let condition = ExprWrapper::default(Expr::InfixOp(InfixOp::Gte, lt,
+                    ExprWrapper::default(Expr::Var("d".to_string()))));
+
+    let desired_ast = vec![
+        ExprWrapper::default(
+            Expr::If(condition, ExprWrapper::default(Expr::Block(vec![])), None))
+    ];
+    expect_test(tokens, desired_ast);
+}
+
+#[test]
+fn test_expression_precedence_logical() {
+    // Make sure a or b and c not equals d generates (a or b) and (c not equals d)
+    let tokens = vec![
+        Keyword(Keywords::If),
+        Identifier("a".to_string()),
+        Keyword(Keywords::Or),
+        Identifier("b".to_string()),
+        Keyword(Keywords::And),
+        Identifier("c".to_string()),
+        Keyword(Keywords::Not),
+        Keyword(Keywords::Equals),
+        Identifier("d".to_string()),
+        Symbol(Symbols::Comma),
+        Indent(1)
+    ];
+
+    let or = ExprWrapper::default(Expr::InfixOp(InfixOp::Or,
+             ExprWrapper::default(Expr::Var("a".to_string())),
+             ExprWrapper::default(Expr::Var("b".to_string()))));
+    let not_equ = ExprWrapper::default(Expr::InfixOp(InfixOp::NotEqu,
+                  ExprWrapper::default(Expr::Var("c".to_string())),
+                  ExprWrapper::default(Expr::Var("d".to_string()))));
+    let condition = ExprWrapper::default(Expr::InfixOp(InfixOp::And, or, not_equ));
+
+    let desired_ast = vec![
+        ExprWrapper::default(
+            Expr::If(condition, ExprWrapper::default(Expr::Block(vec![])), None))
+    ];
+    expect_test(tokens, desired_ast);
+}
+
+#[test]
+fn test_expression_is_and_not() {
+    // Make sure not a is b generates (not a) is b
+    let tokens = vec![
+        Keyword(Keywords::If),
+        Keyword(Keywords::Not),
+        Identifier("a".to_string()),
+        Keyword(Keywords::Is),
+        Identifier("b".to_string()),
+        Symbol(Symbols::Comma),
+        Indent(1)
+    ];
+
+    let not = ExprWrapper::default(Expr::UnaryOp(UnaryOp::Not,
+              ExprWrapper::default(Expr::Var("a".to_string()))));
+    let condition = ExprWrapper::default(Expr::InfixOp(InfixOp::Is, not,
+                    ExprWrapper::default(Expr::Var("b".to_string()))));
+
+    let desired_ast = vec![
+        ExprWrapper::default(
+            Expr::If(condition, ExprWrapper::default(Expr::Block(vec![])), None))
+    ];
+    expect_test(tokens, desired_ast);
+}
+
 #[test]
 fn test_expression_precedence_pow() {
     // Make sure a ^ b ^ c generates a ^ (b ^ c) which is right associative
@@ -652,3 +739,426 @@ fn test_nested_indent_then_dedent() {
     expect_test(tokens, desired_ast);
 
 }
+
+#[test]
+fn test_expression_spans() {
+    // while a + 1,
+    //     b = foo(a)
+    let input_string = "while a + 1,\n\tb = foo(a)";
+
+    let mut parser = Parser::new(Lexer::new(input_string));
+    let ast_root = parser.parse().expect("Expected an ast root");
+
+    let while_loop = match *ast_root.get_expr() {
+        Expr::Block(ref exprs) => &exprs[0],
+        ref expr => panic!("Expected a block, but found {:?}", expr),
+    };
+
+    assert_eq!((while_loop.get_start_line(), while_loop.get_start_column()), (1, 1));
+    assert_eq!((while_loop.get_end_line(), while_loop.get_end_column()), (2, 12));
+
+    let (condition, body) = match *while_loop.get_expr() {
+        Expr::WhileLoop(ref condition, ref body) => (condition, body),
+        ref expr => panic!("Expected a while loop, but found {:?}", expr),
+    };
+
+    assert_eq!(*condition.get_span(), Span::new(Position::new(1, 7, 6), Position::new(1, 12, 11)));
+
+    if let Expr::InfixOp(_, ref lhs, ref rhs) = *condition.get_expr() {
+        assert_eq!(*lhs.get_span(), Span::new(Position::new(1, 7, 6), Position::new(1, 8, 7)));
+        assert_eq!(*rhs.get_span(), Span::new(Position::new(1, 11, 10), Position::new(1, 12, 11)));
+    } else {
+        panic!("Expected an infix op, but found {:?}", condition);
+    }
+
+    let assign = match *body.get_expr() {
+        Expr::Block(ref exprs) => &exprs[0],
+        ref expr => panic!("Expected a block, but found {:?}", expr),
+    };
+
+    assert_eq!(*body.get_span(), *assign.get_span());
+    assert_eq!(*assign.get_span(), Span::new(Position::new(2, 2, 14), Position::new(2, 12, 24)));
+
+    if let Expr::Assign(_, ref fn_call) = *assign.get_expr() {
+        assert_eq!(*fn_call.get_span(), Span::new(Position::new(2, 6, 18), Position::new(2, 12, 24)));
+    } else {
+        panic!("Expected an assignment, but found {:?}", assign);
+    }
+}
+
+#[test]
+fn test_parse_errors() {
+    let mut parser = Parser::new(Lexer::new("while a b"));
+    let errors = match parser.parse() {
+        Ok(ast) => panic!("Expected parse errors, but found {:?}", ast),
+        Err(errors) => errors,
+    };
+
+    let error = &errors[0];
+    assert_eq!(error.get_kind(), ParseErrorKind::UnexpectedToken);
+    assert_eq!(error.get_expected(), Some("a comma ','"));
+    assert_eq!(error.get_found(), Some(&Identifier("b".to_string())));
+    assert_eq!(*error.get_span(), Span::new(Position::new(1, 9, 8), Position::new(1, 10, 9)));
+    assert!(error.get_hint().is_some());
+}
+
+#[test]
+fn test_parse_error_recovery() {
+    // a = 1 2
+    // while a b
+    //     c = 2
+    // d = 3
+    let input_string = "a = 1 2\nwhile a b\n\tc = 2\nd = 3";
+
+    let mut parser = Parser::new(Lexer::new(input_string));
+    let (ast_root, errors) = parser.parse_partial();
+
+    let kinds: Vec<ParseErrorKind> = errors.iter().map(|error| error.get_kind()).collect();
+    assert_eq!(kinds, vec![ParseErrorKind::MultipleStatementsPerLine, ParseErrorKind::UnexpectedToken]);
+    assert_eq!(errors[0].get_span().start, Position::new(1, 7, 6));
+    assert_eq!(errors[1].get_span().start, Position::new(2, 9, 16));
+
+    // The broken statements, and the block under the broken while, are skipped over
+    let desired_ast = Expr::Block(vec![
+        ExprWrapper::default(Expr::Assign(
+            ExprWrapper::default(Expr::Var("a".to_string())),
+            ExprWrapper::default(Expr::Literal(Literals::I32Num(1))),
+        )),
+        ExprWrapper::default(Expr::Assign(
+            ExprWrapper::default(Expr::Var("d".to_string())),
+            ExprWrapper::default(Expr::Literal(Literals::I32Num(3))),
+        )),
+    ]);
+    assert_eq!(*ast_root.get_expr(), desired_ast);
+}
+
+#[test]
+fn test_lexical_errors() {
+    // Each bad token is reported once, and parsing carries on past it
+    let input_string = "a = 0xz\nb = 1 $\nc = 2";
+
+    let mut parser = Parser::new(Lexer::new(input_string));
+    let (ast_root, errors) = parser.parse_partial();
+
+    let messages: Vec<&str> = errors.iter().map(|error| error.get_message()).collect();
+    assert_eq!(messages, vec!["No hexadecimal value was found.", "Unknown character ($)."]);
+    assert!(errors.iter().all(|error| error.get_kind() == ParseErrorKind::Lexical));
+
+    match *ast_root.get_expr() {
+        Expr::Block(ref exprs) => assert_eq!(exprs.len(), 2),
+        ref expr => panic!("Expected a block, but found {:?}", expr),
+    }
+}
+
+#[test]
+fn test_if_else_chain() {
+    // if a,
+    //     b = 1
+    // else if c,
+    //     b = 2
+    // else,
+    //     b = 3
+    // .
+    // d = 4
+    let input_string = "if a,\n\tb = 1\nelse if c,\n\tb = 2\nelse,\n\tb = 3\n.\nd = 4";
+
+    let mut parser = Parser::new(Lexer::new(input_string));
+    let ast_root = parser.parse().expect("Expected an ast root");
+
+    let assign = |name: &str, value: i32| ExprWrapper::default(Expr::Assign(
+        ExprWrapper::default(Expr::Var(name.to_string())),
+        ExprWrapper::default(Expr::Literal(Literals::I32Num(value))),
+    ));
+    let block = |exprwrapper: ExprWrapper| ExprWrapper::default(Expr::Block(vec![exprwrapper]));
+
+    let else_if = ExprWrapper::default(Expr::If(
+        ExprWrapper::default(Expr::Var("c".to_string())),
+        block(assign("b", 2)),
+        Some(block(assign("b", 3))),
+    ));
+    let desired_ast = Expr::Block(vec![
+        ExprWrapper::default(Expr::If(
+            ExprWrapper::default(Expr::Var("a".to_string())),
+            block(assign("b", 1)),
+            Some(else_if),
+        )),
+        assign("d", 4),
+    ]);
+    assert_eq!(*ast_root.get_expr(), desired_ast);
+}
+
+#[test]
+fn test_nested_if_else() {
+    // The else belongs to the if at its own indentation
+    // if a,
+    //     if b,
+    //         c = 1
+    // else,
+    //     c = 2
+    let input_string = "if a,\n\tif b,\n\t\tc = 1\nelse,\n\tc = 2";
+
+    let mut parser = Parser::new(Lexer::new(input_string));
+    let ast_root = parser.parse().expect("Expected an ast root");
+
+    let assign = |value: i32| ExprWrapper::default(Expr::Block(vec![ExprWrapper::default(Expr::Assign(
+        ExprWrapper::default(Expr::Var("c".to_string())),
+        ExprWrapper::default(Expr::Literal(Literals::I32Num(value))),
+    ))]));
+
+    let inner_if = ExprWrapper::default(Expr::If(ExprWrapper::default(Expr::Var("b".to_string())), assign(1), None));
+    let desired_ast = Expr::Block(vec![
+        ExprWrapper::default(Expr::If(
+            ExprWrapper::default(Expr::Var("a".to_string())),
+            ExprWrapper::default(Expr::Block(vec![inner_if])),
+            Some(assign(2)),
+        )),
+    ]);
+    assert_eq!(*ast_root.get_expr(), desired_ast);
+}
+
+#[test]
+fn test_else_without_if() {
+    let input_string = "a = 1\nelse,\n\ta = 2\nb = 3";
+
+    let mut parser = Parser::new(Lexer::new(input_string));
+    let (ast_root, errors) = parser.parse_partial();
+
+    let kinds: Vec<ParseErrorKind> = errors.iter().map(|error| error.get_kind()).collect();
+    assert_eq!(kinds, vec![ParseErrorKind::UnexpectedToken]);
+    assert_eq!(*ast_root.get_expr(), Expr::Block(vec![
+        ExprWrapper::default(Expr::Assign(
+            ExprWrapper::default(Expr::Var("a".to_string())),
+            ExprWrapper::default(Expr::Literal(Literals::I32Num(1))),
+        )),
+        ExprWrapper::default(Expr::Assign(
+            ExprWrapper::default(Expr::Var("b".to_string())),
+            ExprWrapper::default(Expr::Literal(Literals::I32Num(3))),
+        )),
+    ]));
+}
+
+#[test]
+fn test_for_loop() {
+    // for i in 0..10 by 2,
+    //     a = i
+    // for j in 1..b,
+    //     a = j
+    let input_string = "for i in 0..10 by 2,\n\ta = i\nfor j in 1..b,\n\ta = j";
+
+    let mut parser = Parser::new(Lexer::new(input_string));
+    let ast_root = parser.parse().expect("Expected an ast root");
+
+    let var = |name: &str| ExprWrapper::default(Expr::Var(name.to_string()));
+    let int = |value: i32| ExprWrapper::default(Expr::Literal(Literals::I32Num(value)));
+    let assign = |name: &str| ExprWrapper::default(Expr::Block(vec![
+        ExprWrapper::default(Expr::Assign(var("a"), var(name)))
+    ]));
+
+    let desired_ast = Expr::Block(vec![
+        ExprWrapper::default(Expr::ForLoop("i".to_string(), int(0), int(10), Some(int(2)), assign("i"))),
+        ExprWrapper::default(Expr::ForLoop("j".to_string(), int(1), var("b"), None, assign("j"))),
+    ]);
+    assert_eq!(*ast_root.get_expr(), desired_ast);
+}
+
+#[test]
+fn test_loop_control() {
+    // while a,
+    //     pass
+    // for i in 0..b,
+    //     continue
+    //     break
+    let input_string = "while a,\n\tpass\nfor i in 0..b,\n\tcontinue\n\tbreak";
+
+    let mut parser = Parser::new(Lexer::new(input_string));
+    let ast_root = parser.parse().expect("Expected an ast root");
+
+    let desired_ast = Expr::Block(vec![
+        ExprWrapper::default(Expr::WhileLoop(
+            ExprWrapper::default(Expr::Var("a".to_string())),
+            ExprWrapper::default(Expr::Block(vec![ExprWrapper::default(Expr::NoOp)])),
+        )),
+        ExprWrapper::default(Expr::ForLoop("i".to_string(),
+            ExprWrapper::default(Expr::Literal(Literals::I32Num(0))),
+            ExprWrapper::default(Expr::Var("b".to_string())),
+            None,
+            ExprWrapper::default(Expr::Block(vec![
+                ExprWrapper::default(Expr::Continue),
+                ExprWrapper::default(Expr::Break),
+            ])),
+        )),
+    ]);
+    assert_eq!(*ast_root.get_expr(), desired_ast);
+}
+
+#[test]
+fn test_assert() {
+    // The source of the condition is kept for the failure message
+    let input_string = "assert a equals  b\nassert c, \"c failed\"";
+
+    let mut parser = Parser::new(Lexer::new(input_string));
+    let ast_root = parser.parse().expect("Expected an ast root");
+
+    let equ = ExprWrapper::default(Expr::InfixOp(InfixOp::Equ,
+              ExprWrapper::default(Expr::Var("a".to_string())),
+              ExprWrapper::default(Expr::Var("b".to_string()))));
+
+    let desired_ast = Expr::Block(vec![
+        ExprWrapper::default(Expr::Assert(equ, None, "a equals  b".to_string())),
+        ExprWrapper::default(Expr::Assert(ExprWrapper::default(Expr::Var("c".to_string())),
+                                          Some("c failed".to_string()), "c".to_string())),
+    ]);
+    assert_eq!(*ast_root.get_expr(), desired_ast);
+}
+
+#[test]
+fn test_compound_assignment() {
+    let input_string = "a += 1\na -= 2\na *= 3\na /= 4\na %= b + 5";
+
+    let mut parser = Parser::new(Lexer::new(input_string));
+    let ast_root = parser.parse().expect("Expected an ast root");
+
+    let var = |name: &str| ExprWrapper::default(Expr::Var(name.to_string()));
+    let int = |value: i32| ExprWrapper::default(Expr::Literal(Literals::I32Num(value)));
+    let compound = |op: InfixOp, rhs: ExprWrapper| ExprWrapper::default(Expr::Assign(var("a"),
+                   ExprWrapper::default(Expr::InfixOp(op, var("a"), rhs))));
+
+    let desired_ast = Expr::Block(vec![
+        compound(InfixOp::Add, int(1)),
+        compound(InfixOp::Sub, int(2)),
+        compound(InfixOp::Mul, int(3)),
+        compound(InfixOp::Div, int(4)),
+        compound(InfixOp::Mod, ExprWrapper::default(Expr::InfixOp(InfixOp::Add, var("b"), int(5)))),
+    ]);
+    assert_eq!(*ast_root.get_expr(), desired_ast);
+}
+
+#[test]
+fn test_fn_defaults_and_var_args() {
+    let input_string = "fn foo(a: i32, b = 2: i32, c = False: bool, ...) -> i32\n\treturn a";
+
+    let mut parser = Parser::new(Lexer::new(input_string));
+    let ast_root = parser.parse().expect("Expected an ast root");
+
+    let args = vec![
+        ("a".to_string(), "i32".to_string(), None),
+        ("b".to_string(), "i32".to_string(), Some(ExprWrapper::default(Expr::Literal(Literals::I32Num(2))))),
+        ("c".to_string(), "bool".to_string(), Some(ExprWrapper::default(Expr::Literal(Literals::Bool(false))))),
+    ];
+    let body = ExprWrapper::default(Expr::Block(vec![
+        ExprWrapper::default(Expr::Return(Some(ExprWrapper::default(Expr::Var("a".to_string()))))),
+    ]));
+    let desired_ast = Expr::Block(vec![
+        ExprWrapper::default(Expr::FnDecl("foo".to_string(), args, true, Some("i32".to_string()), body)),
+    ]);
+    assert_eq!(*ast_root.get_expr(), desired_ast);
+
+    // Once an arg has a default, the rest need one too, and nothing may follow `...`
+    for input_string in &["fn foo(a = 1: i32, b: i32)\n\tpass", "fn foo(..., a: i32)\n\tpass"] {
+        let mut parser = Parser::new(Lexer::new(input_string));
+        let (_, errors) = parser.parse_partial();
+
+        let kinds: Vec<ParseErrorKind> = errors.iter().map(|error| error.get_kind()).collect();
+        assert_eq!(kinds, vec![ParseErrorKind::UnexpectedToken]);
+    }
+}
+
+#[test]
+fn test_use() {
+    let input_string = "use std.io.print\nuse magical_butterflies as magic\nmagic.fly()\nvar a = magic.wings.count";
+
+    let mut parser = Parser::new(Lexer::new(input_string));
+    let ast_root = parser.parse().expect("Expected an ast root");
+
+    let path = |parts: &[&str]| parts.iter().map(|part| part.to_string()).collect();
+
+    let desired_ast = Expr::Block(vec![
+        ExprWrapper::default(Expr::Use(path(&["std", "io", "print"]), None)),
+        ExprWrapper::default(Expr::Use(path(&["magical_butterflies"]), Some("magic".to_string()))),
+        ExprWrapper::default(Expr::FnCall("magic.fly".to_string(), vec![])),
+        ExprWrapper::default(Expr::VarDecl(false, "a".to_string(), None, ExprWrapper::default(Expr::Var("magic.wings.count".to_string())))),
+    ]);
+    assert_eq!(*ast_root.get_expr(), desired_ast);
+
+    for input_string in &["use", "use std.", "use std as", "magic.()"] {
+        let mut parser = Parser::new(Lexer::new(input_string));
+        let (_, errors) = parser.parse_partial();
+
+        let kinds: Vec<ParseErrorKind> = errors.iter().map(|error| error.get_kind()).collect();
+        assert_eq!(kinds, vec![ParseErrorKind::UnexpectedToken]);
+    }
+}
+
+#[test]
+fn test_class() {
+    let input_string = "class Square from Shape\n\tdef width: i32 = 1\n\n\tfn area() -> i32\n\t\treturn self.width * self.width\nvar s = Square(2)";
+
+    let mut parser = Parser::new(Lexer::new(input_string));
+    let ast_root = parser.parse().expect("Expected an ast root");
+
+    let var = |name: &str| ExprWrapper::default(Expr::Var(name.to_string()));
+    let area_body = ExprWrapper::default(Expr::Block(vec![
+        ExprWrapper::default(Expr::Return(Some(ExprWrapper::default(Expr::InfixOp(InfixOp::Mul, var("self.width"), var("self.width")))))),
+    ]));
+
+    // Methods take the object as `self`, and are named after their class
+    let desired_ast = Expr::Block(vec![
+        ExprWrapper::default(Expr::ClassDecl("Square".to_string(), Some("Shape".to_string()),
+            vec![ExprWrapper::default(Expr::VarDecl(true, "width".to_string(), Some("i32".to_string()),
+                                                    ExprWrapper::default(Expr::Literal(Literals::I32Num(1)))))],
+            vec![ExprWrapper::default(Expr::FnDecl("Square.area".to_string(), vec![("self".to_string(), "Square".to_string(), None)],
+                                                   false, Some("i32".to_string()), area_body))])),
+        ExprWrapper::default(Expr::VarDecl(false, "s".to_string(), None,
+                                           ExprWrapper::default(Expr::FnCall("Square".to_string(),
+                                                                             vec![ExprWrapper::default(Expr::Literal(Literals::I32Num(2)))])))),
+    ]);
+    assert_eq!(*ast_root.get_expr(), desired_ast);
+
+    for input_string in &["class", "class Square from", "class Square\n\tprint(\"sides\")"] {
+        let mut parser = Parser::new(Lexer::new(input_string));
+        let (_, errors) = parser.parse_partial();
+
+        let kinds: Vec<ParseErrorKind> = errors.iter().map(|error| error.get_kind()).collect();
+        assert_eq!(kinds, vec![ParseErrorKind::UnexpectedToken]);
+    }
+}
+
+#[test]
+fn test_when() {
+    // when,
+    //     fly()
+    // throws KeyError or TypeError as e,
+    //     print(e)
+    // throws ValueError,
+    //     raise Broken, "could not fly"
+    // else,
+    //     land()
+    let input_string = "when,\n\tfly()\nthrows KeyError or TypeError as e,\n\tprint(e)\nthrows ValueError,\n\traise Broken, \"could not fly\"\nelse,\n\tland()";
+
+    let mut parser = Parser::new(Lexer::new(input_string));
+    let ast_root = parser.parse().expect("Expected an ast root");
+
+    let call = |name: &str, args| ExprWrapper::default(Expr::FnCall(name.to_string(), args));
+    let block = |exprwrapper: ExprWrapper| ExprWrapper::default(Expr::Block(vec![exprwrapper]));
+
+    let desired_ast = Expr::Block(vec![
+        ExprWrapper::default(Expr::When(
+            block(call("fly", vec![])),
+            vec![
+                (vec!["KeyError".to_string(), "TypeError".to_string()], Some("e".to_string()),
+                 block(call("print", vec![ExprWrapper::default(Expr::Var("e".to_string()))]))),
+                (vec!["ValueError".to_string()], None,
+                 block(ExprWrapper::default(Expr::Raise("Broken".to_string(), Some("could not fly".to_string()))))),
+            ],
+            Some(block(call("land", vec![]))),
+        )),
+    ]);
+    assert_eq!(*ast_root.get_expr(), desired_ast);
+
+    for input_string in &["when,\n\tfly()\nland()", "when,\n\tfly()\nthrows,\n\tland()", "throws KeyError,\n\tland()", "raise 5"] {
+        let mut parser = Parser::new(Lexer::new(input_string));
+        let (_, errors) = parser.parse_partial();
+
+        assert!(!errors.is_empty(), "Expected {:?} not to parse", input_string);
+    }
+}
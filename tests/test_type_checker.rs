@@ -1,8 +1,12 @@
 extern crate limonite;
 
+use limonite::lexical::span::Span;
 use limonite::syntax::expr::{Expr, ExprWrapper};
 use limonite::syntax::op::InfixOp::*;
+use limonite::syntax::op::UnaryOp;
 use limonite::syntax::literals::Literals::*;
+use limonite::semantic::analyzer::SemanticAnalyzer;
+use limonite::semantic::error::SemanticErrorKind;
 use limonite::semantic::type_checker::TypeChecker;
 use limonite::semantic::analyzer_trait::ASTAnalyzer;
 
@@ -60,8 +64,6 @@ fn test_infix_op_valid() {
 }
 
 #[test]
-#[should_panic]
-// Won't panic when actually displaying errors correctly
 fn test_infix_op_invalid() {
     // 'a' + 5
     let mut input_ast = ExprWrapper::default(
@@ -72,5 +74,819 @@ fn test_infix_op_invalid() {
         )
     );
 
-    TypeChecker::new().analyze(&mut input_ast);
+    let mut type_checker = TypeChecker::new();
+    type_checker.analyze(&mut input_ast);
+
+    let kinds: Vec<&SemanticErrorKind> = type_checker.get_errors().iter().map(|error| error.get_kind()).collect();
+    assert_eq!(kinds, vec![&SemanticErrorKind::TypeMismatch { expected: "char".into(), found: "u32".into() }]);
+}
+
+#[test]
+fn test_errors_are_collected() {
+    // while 1 + 'a',
+    //     if 2,
+    //         ...
+    let mut input_ast = ExprWrapper::default(
+        Expr::WhileLoop(
+            ExprWrapper::default(Expr::InfixOp(
+                Add,
+                ExprWrapper::default(Expr::Literal(I32Num(1))),
+                ExprWrapper::default(Expr::Literal(UTF8Char('a'))),
+            )),
+            ExprWrapper::default(Expr::Block(vec![
+                ExprWrapper::default(Expr::If(
+                    ExprWrapper::default(Expr::Literal(I32Num(2))),
+                    ExprWrapper::default(Expr::Block(vec![])),
+                    None,
+                )),
+            ])),
+        )
+    );
+
+    let errors = match SemanticAnalyzer::new().analyze(&mut input_ast) {
+        Ok(()) => panic!("Expected semantic errors"),
+        Err(errors) => errors,
+    };
+
+    let kinds: Vec<&SemanticErrorKind> = errors.iter().map(|error| error.get_kind()).collect();
+    assert_eq!(kinds, vec![&SemanticErrorKind::TypeMismatch { expected: "i32".into(), found: "char".into() },
+                           &SemanticErrorKind::NonBoolCondition { found: "i32".into() }]);
+}
+
+#[test]
+fn test_variable_lookup() {
+    // var a = 1
+    // var b: i32 = a
+    // b + 'c'
+    let mut input_ast = ExprWrapper::default(Expr::Block(vec![
+        ExprWrapper::default(Expr::VarDecl(false, "a".into(), None,
+                                           ExprWrapper::default(Expr::Literal(I32Num(1))))),
+        ExprWrapper::default(Expr::VarDecl(false, "b".into(), Some("i32".into()),
+                                           ExprWrapper::default(Expr::Var("a".into())))),
+        ExprWrapper::default(Expr::InfixOp(
+            Add,
+            ExprWrapper::default(Expr::Var("b".into())),
+            ExprWrapper::default(Expr::Literal(UTF8Char('c'))),
+        )),
+    ]));
+
+    let mut type_checker = TypeChecker::new();
+    type_checker.analyze(&mut input_ast);
+
+    let kinds: Vec<&SemanticErrorKind> = type_checker.get_errors().iter().map(|error| error.get_kind()).collect();
+    assert_eq!(kinds, vec![&SemanticErrorKind::TypeMismatch { expected: "i32".into(), found: "char".into() }]);
+}
+
+#[test]
+fn test_variable_scopes() {
+    // var a = 1
+    // if True,
+    //     var b = a
+    // fn foo(c: i32) -> i32
+    //     return a + c
+    // b
+    let mut input_ast = ExprWrapper::default(Expr::Block(vec![
+        ExprWrapper::default(Expr::VarDecl(false, "a".into(), None,
+                                           ExprWrapper::default(Expr::Literal(I32Num(1))))),
+        ExprWrapper::default(Expr::If(
+            ExprWrapper::default(Expr::Literal(Bool(true))),
+            ExprWrapper::default(Expr::Block(vec![
+                ExprWrapper::default(Expr::VarDecl(false, "b".into(), None,
+                                                   ExprWrapper::default(Expr::Var("a".into())))),
+            ])),
+            None,
+        )),
+        ExprWrapper::default(Expr::FnDecl("foo".into(), vec![("c".into(), "i32".into(), None)], false, Some("i32".into()),
+            ExprWrapper::default(Expr::Block(vec![
+                ExprWrapper::default(Expr::Return(Some(ExprWrapper::default(Expr::InfixOp(
+                    Add,
+                    ExprWrapper::default(Expr::Var("a".into())),
+                    ExprWrapper::default(Expr::Var("c".into())),
+                ))))),
+            ])),
+        )),
+        ExprWrapper::default(Expr::Var("b".into())),
+    ]));
+
+    let mut type_checker = TypeChecker::new();
+    type_checker.analyze(&mut input_ast);
+
+    // Functions can't see outside variables, and `b` went out of scope with the if
+    let kinds: Vec<&SemanticErrorKind> = type_checker.get_errors().iter().map(|error| error.get_kind()).collect();
+    assert_eq!(kinds, vec![&SemanticErrorKind::UndeclaredVariable { name: "a".into() },
+                           &SemanticErrorKind::UndeclaredVariable { name: "b".into() }]);
+}
+
+#[test]
+fn test_redeclaration_and_shadowing() {
+    // var a = 1
+    // var a = 2
+    // if True,
+    //     var a = 3
+    // fn foo(b: i32) -> i32
+    //     var a = 4
+    //     return a
+    let mut input_ast = ExprWrapper::default(Expr::Block(vec![
+        ExprWrapper::default(Expr::VarDecl(false, "a".into(), None,
+                                           ExprWrapper::default(Expr::Literal(I32Num(1))))),
+        ExprWrapper::default(Expr::VarDecl(false, "a".into(), None,
+                                           ExprWrapper::default(Expr::Literal(I32Num(2))))),
+        ExprWrapper::default(Expr::If(
+            ExprWrapper::default(Expr::Literal(Bool(true))),
+            ExprWrapper::default(Expr::Block(vec![
+                ExprWrapper::default(Expr::VarDecl(false, "a".into(), None,
+                                                   ExprWrapper::default(Expr::Literal(I32Num(3))))),
+            ])),
+            None,
+        )),
+        ExprWrapper::default(Expr::FnDecl("foo".into(), vec![("b".into(), "i32".into(), None)], false, Some("i32".into()),
+            ExprWrapper::default(Expr::Block(vec![
+                ExprWrapper::default(Expr::VarDecl(false, "a".into(), None,
+                                                   ExprWrapper::default(Expr::Literal(I32Num(4))))),
+                ExprWrapper::default(Expr::Return(Some(ExprWrapper::default(Expr::Var("a".into()))))),
+            ])),
+        )),
+    ]));
+
+    let mut type_checker = TypeChecker::new();
+    type_checker.analyze(&mut input_ast);
+
+    // Functions are free to reuse names from outside of them
+    let kinds: Vec<&SemanticErrorKind> = type_checker.get_errors().iter().map(|error| error.get_kind()).collect();
+    assert_eq!(kinds, vec![&SemanticErrorKind::Redeclaration { name: "a".into(), previous: Span::default() },
+                           &SemanticErrorKind::Shadowing { name: "a".into(), previous: Span::default() }]);
+}
+
+#[test]
+fn test_assign_to_constant() {
+    // def a = 1
+    // var b = 2
+    // b = 3
+    // a = 4
+    let mut input_ast = ExprWrapper::default(Expr::Block(vec![
+        ExprWrapper::default(Expr::VarDecl(true, "a".into(), None,
+                                           ExprWrapper::default(Expr::Literal(I32Num(1))))),
+        ExprWrapper::default(Expr::VarDecl(false, "b".into(), None,
+                                           ExprWrapper::default(Expr::Literal(I32Num(2))))),
+        ExprWrapper::default(Expr::Assign(ExprWrapper::default(Expr::Var("b".into())),
+                                          ExprWrapper::default(Expr::Literal(I32Num(3))))),
+        ExprWrapper::default(Expr::Assign(ExprWrapper::default(Expr::Var("a".into())),
+                                          ExprWrapper::default(Expr::Literal(I32Num(4))))),
+    ]));
+
+    let mut type_checker = TypeChecker::new();
+    type_checker.analyze(&mut input_ast);
+
+    let kinds: Vec<&SemanticErrorKind> = type_checker.get_errors().iter().map(|error| error.get_kind()).collect();
+    assert_eq!(kinds, vec![&SemanticErrorKind::AssignToConstant { name: "a".into(), declared_at: Span::default() }]);
+}
+
+#[test]
+fn test_comparison_is_bool() {
+    // var a = 1 <= 2
+    // if a,
+    //     ...
+    // if 1 > 'a',
+    //     ...
+    let mut input_ast = ExprWrapper::default(Expr::Block(vec![
+        ExprWrapper::default(Expr::VarDecl(false, "a".into(), None,
+            ExprWrapper::default(Expr::InfixOp(
+                Lte,
+                ExprWrapper::default(Expr::Literal(I32Num(1))),
+                ExprWrapper::default(Expr::Literal(I32Num(2))),
+            )))),
+        ExprWrapper::default(Expr::If(ExprWrapper::default(Expr::Var("a".into())),
+                                      ExprWrapper::default(Expr::Block(vec![])), None)),
+        ExprWrapper::default(Expr::If(
+            ExprWrapper::default(Expr::InfixOp(
+                Gt,
+                ExprWrapper::default(Expr::Literal(I32Num(1))),
+                ExprWrapper::default(Expr::Literal(UTF8Char('a'))),
+            )),
+            ExprWrapper::default(Expr::Block(vec![])),
+            None,
+        )),
+    ]));
+
+    let mut type_checker = TypeChecker::new();
+    type_checker.analyze(&mut input_ast);
+
+    // Mismatched operands are still reported, but the condition is a bool regardless
+    let kinds: Vec<&SemanticErrorKind> = type_checker.get_errors().iter().map(|error| error.get_kind()).collect();
+    assert_eq!(kinds, vec![&SemanticErrorKind::TypeMismatch { expected: "i32".into(), found: "char".into() }]);
+}
+
+#[test]
+fn test_logical_operands_are_bool() {
+    // var a = 1 < 2 and true
+    // var b = a or 1
+    let mut input_ast = ExprWrapper::default(Expr::Block(vec![
+        ExprWrapper::default(Expr::VarDecl(false, "a".into(), None,
+            ExprWrapper::default(Expr::InfixOp(
+                And,
+                ExprWrapper::default(Expr::InfixOp(
+                    Lt,
+                    ExprWrapper::default(Expr::Literal(I32Num(1))),
+                    ExprWrapper::default(Expr::Literal(I32Num(2))),
+                )),
+                ExprWrapper::default(Expr::Literal(Bool(true))),
+            )))),
+        ExprWrapper::default(Expr::VarDecl(false, "b".into(), None,
+            ExprWrapper::default(Expr::InfixOp(
+                Or,
+                ExprWrapper::default(Expr::Var("a".into())),
+                ExprWrapper::default(Expr::Literal(I32Num(1))),
+            )))),
+    ]));
+
+    let mut type_checker = TypeChecker::new();
+    type_checker.analyze(&mut input_ast);
+
+    let kinds: Vec<&SemanticErrorKind> = type_checker.get_errors().iter().map(|error| error.get_kind()).collect();
+    assert_eq!(kinds, vec![&SemanticErrorKind::NonBoolCondition { found: "i32".into() }]);
+}
+
+#[test]
+fn test_for_loop_range() {
+    // for i in 0..10 by 2,
+    //     var a: i32 = i
+    // for j in 0..10u8,
+    //     ...
+    // for k in 0.5..2.0,
+    //     ...
+    // i
+    let mut input_ast = ExprWrapper::default(Expr::Block(vec![
+        ExprWrapper::default(Expr::ForLoop("i".into(),
+            ExprWrapper::default(Expr::Literal(I32Num(0))),
+            ExprWrapper::default(Expr::Literal(I32Num(10))),
+            Some(ExprWrapper::default(Expr::Literal(I32Num(2)))),
+            ExprWrapper::default(Expr::Block(vec![
+                ExprWrapper::default(Expr::VarDecl(false, "a".into(), Some("i32".into()),
+                                                   ExprWrapper::default(Expr::Var("i".into())))),
+            ])),
+        )),
+        ExprWrapper::default(Expr::ForLoop("j".into(),
+            ExprWrapper::default(Expr::Literal(I32Num(0))),
+            ExprWrapper::default(Expr::Literal(U8Num(10))),
+            None,
+            ExprWrapper::default(Expr::Block(vec![])),
+        )),
+        ExprWrapper::default(Expr::ForLoop("k".into(),
+            ExprWrapper::default(Expr::Literal(F64Num(0.5))),
+            ExprWrapper::default(Expr::Literal(F64Num(2.0))),
+            None,
+            ExprWrapper::default(Expr::Block(vec![])),
+        )),
+        ExprWrapper::default(Expr::Var("i".into())),
+    ]));
+
+    let mut type_checker = TypeChecker::new();
+    type_checker.analyze(&mut input_ast);
+
+    let kinds: Vec<&SemanticErrorKind> = type_checker.get_errors().iter().map(|error| error.get_kind()).collect();
+    assert_eq!(kinds, vec![&SemanticErrorKind::TypeMismatch { expected: "i32".into(), found: "u8".into() },
+                           &SemanticErrorKind::NonIntegerRange { found: "f64".into() },
+                           &SemanticErrorKind::NonIntegerRange { found: "f64".into() },
+                           &SemanticErrorKind::UndeclaredVariable { name: "i".into() }]);
+}
+
+#[test]
+fn test_for_loop_step() {
+    // for i in 0..10 by 0,
+    //     ...
+    // for j in 10..0 by -1,
+    //     ...
+    // for k in 0..10 by step,
+    //     ...
+    // for l in 0..10 by 18446744073709551615,
+    //     ...
+    let mut input_ast = ExprWrapper::default(Expr::Block(vec![
+        ExprWrapper::default(Expr::VarDecl(false, "step".into(), None, ExprWrapper::default(Expr::Literal(I32Num(0))))),
+        ExprWrapper::default(Expr::ForLoop("i".into(),
+            ExprWrapper::default(Expr::Literal(I32Num(0))),
+            ExprWrapper::default(Expr::Literal(I32Num(10))),
+            Some(ExprWrapper::default(Expr::Literal(I32Num(0)))),
+            ExprWrapper::default(Expr::Block(vec![])),
+        )),
+        ExprWrapper::default(Expr::ForLoop("j".into(),
+            ExprWrapper::default(Expr::Literal(I32Num(10))),
+            ExprWrapper::default(Expr::Literal(I32Num(0))),
+            Some(ExprWrapper::default(Expr::UnaryOp(UnaryOp::Negate, ExprWrapper::default(Expr::Literal(I32Num(1)))))),
+            ExprWrapper::default(Expr::Block(vec![])),
+        )),
+        // Only known when the program runs, so it's checked then
+        ExprWrapper::default(Expr::ForLoop("k".into(),
+            ExprWrapper::default(Expr::Literal(I32Num(0))),
+            ExprWrapper::default(Expr::Literal(I32Num(10))),
+            Some(ExprWrapper::default(Expr::Var("step".into()))),
+            ExprWrapper::default(Expr::Block(vec![])),
+        )),
+        // Too big for an i64, but still positive
+        ExprWrapper::default(Expr::ForLoop("l".into(),
+            ExprWrapper::default(Expr::Literal(U64Num(0))),
+            ExprWrapper::default(Expr::Literal(U64Num(10))),
+            Some(ExprWrapper::default(Expr::Literal(U64Num(u64::max_value())))),
+            ExprWrapper::default(Expr::Block(vec![])),
+        )),
+    ]));
+
+    let mut type_checker = TypeChecker::new();
+    type_checker.analyze(&mut input_ast);
+
+    let kinds: Vec<&SemanticErrorKind> = type_checker.get_errors().iter().map(|error| error.get_kind()).collect();
+    assert_eq!(kinds, vec![&SemanticErrorKind::NonPositiveStep { found: 0 },
+                           &SemanticErrorKind::NonPositiveStep { found: -1 }]);
+}
+
+#[test]
+fn test_loop_control_outside_loop() {
+    // while true,
+    //     break
+    //     fn foo() -> None
+    //         continue
+    // break
+    let mut input_ast = ExprWrapper::default(Expr::Block(vec![
+        ExprWrapper::default(Expr::WhileLoop(
+            ExprWrapper::default(Expr::Literal(Bool(true))),
+            ExprWrapper::default(Expr::Block(vec![
+                ExprWrapper::default(Expr::Break),
+                ExprWrapper::default(Expr::FnDecl("foo".into(), vec![], false, None,
+                    ExprWrapper::default(Expr::Block(vec![ExprWrapper::default(Expr::Continue)])))),
+            ])),
+        )),
+        ExprWrapper::default(Expr::Break),
+    ]));
+
+    let mut type_checker = TypeChecker::new();
+    type_checker.analyze(&mut input_ast);
+
+    let kinds: Vec<&SemanticErrorKind> = type_checker.get_errors().iter().map(|error| error.get_kind()).collect();
+    assert_eq!(kinds, vec![&SemanticErrorKind::OutsideOfLoop { statement: "continue".into() },
+                           &SemanticErrorKind::OutsideOfLoop { statement: "break".into() }]);
+}
+
+#[test]
+fn test_assert_is_bool() {
+    // assert 1 < 2
+    // assert 1
+    let mut input_ast = ExprWrapper::default(Expr::Block(vec![
+        ExprWrapper::default(Expr::Assert(
+            ExprWrapper::default(Expr::InfixOp(
+                Lt,
+                ExprWrapper::default(Expr::Literal(I32Num(1))),
+                ExprWrapper::default(Expr::Literal(I32Num(2))),
+            )),
+            None,
+            "1 < 2".into(),
+        )),
+        ExprWrapper::default(Expr::Assert(ExprWrapper::default(Expr::Literal(I32Num(1))), None, "1".into())),
+    ]));
+
+    let mut type_checker = TypeChecker::new();
+    type_checker.analyze(&mut input_ast);
+
+    let kinds: Vec<&SemanticErrorKind> = type_checker.get_errors().iter().map(|error| error.get_kind()).collect();
+    assert_eq!(kinds, vec![&SemanticErrorKind::NonBoolCondition { found: "i32".into() }]);
+}
+
+#[test]
+fn test_compound_assignment() {
+    // def a = 1
+    // var b = 2
+    // a += 1
+    // b *= 'c'
+    let compound = |name: &str, op, rhs| ExprWrapper::default(Expr::Assign(
+        ExprWrapper::default(Expr::Var(name.into())),
+        ExprWrapper::default(Expr::InfixOp(op, ExprWrapper::default(Expr::Var(name.into())), rhs)),
+    ));
+
+    let mut input_ast = ExprWrapper::default(Expr::Block(vec![
+        ExprWrapper::default(Expr::VarDecl(true, "a".into(), None, ExprWrapper::default(Expr::Literal(I32Num(1))))),
+        ExprWrapper::default(Expr::VarDecl(false, "b".into(), None, ExprWrapper::default(Expr::Literal(I32Num(2))))),
+        compound("a", Add, ExprWrapper::default(Expr::Literal(I32Num(1)))),
+        compound("b", Mul, ExprWrapper::default(Expr::Literal(UTF8Char('c')))),
+    ]));
+
+    let mut type_checker = TypeChecker::new();
+    type_checker.analyze(&mut input_ast);
+
+    let kinds: Vec<&SemanticErrorKind> = type_checker.get_errors().iter().map(|error| error.get_kind()).collect();
+    assert_eq!(kinds, vec![&SemanticErrorKind::AssignToConstant { name: "a".into(), declared_at: Span::default() },
+                           &SemanticErrorKind::TypeMismatch { expected: "i32".into(), found: "char".into() }]);
+}
+
+#[test]
+fn test_fn_call_defaults() {
+    // fn foo(a: i32, b = 2: i32, c = 'c': i32),
+    //     return a
+    // foo(1)
+    let args = vec![
+        ("a".into(), "i32".into(), None),
+        ("b".into(), "i32".into(), Some(ExprWrapper::default(Expr::Literal(I32Num(2))))),
+        ("c".into(), "i32".into(), Some(ExprWrapper::default(Expr::Literal(UTF8Char('c'))))),
+    ];
+    let mut input_ast = ExprWrapper::default(Expr::Block(vec![
+        ExprWrapper::default(Expr::FnDecl("foo".into(), args, false, Some("i32".into()),
+            ExprWrapper::default(Expr::Block(vec![
+                ExprWrapper::default(Expr::Return(Some(ExprWrapper::default(Expr::Var("a".into()))))),
+            ])))),
+        ExprWrapper::default(Expr::FnCall("foo".into(), vec![ExprWrapper::default(Expr::Literal(I32Num(1)))])),
+    ]));
+
+    let mut type_checker = TypeChecker::new();
+    type_checker.analyze(&mut input_ast);
+
+    let kinds: Vec<&SemanticErrorKind> = type_checker.get_errors().iter().map(|error| error.get_kind()).collect();
+    assert_eq!(kinds, vec![&SemanticErrorKind::TypeMismatch { expected: "i32".into(), found: "char".into() }]);
+
+    // The call was given the missing args
+    if let Expr::Block(ref exprs) = *input_ast.get_expr() {
+        assert_eq!(*exprs[1].get_expr(), Expr::FnCall("foo".into(), vec![
+            ExprWrapper::default(Expr::Literal(I32Num(1))),
+            ExprWrapper::default(Expr::Literal(I32Num(2))),
+            ExprWrapper::default(Expr::Literal(UTF8Char('c'))),
+        ]));
+    } else {
+        panic!("Expected a block");
+    }
+}
+
+#[test]
+fn test_fn_call_defaults_are_constant() {
+    // var x = 5
+    // fn foo(a = x: i32, b = -1 + 2: i32) -> i32,
+    //     return a + b
+    // fn bar() -> i32,
+    //     var x = 7
+    //     return foo()
+    let var = |name: &str| ExprWrapper::default(Expr::Var(name.into()));
+    let int = |value| ExprWrapper::default(Expr::Literal(I32Num(value)));
+    let args = vec![
+        ("a".into(), "i32".into(), Some(var("x"))),
+        ("b".into(), "i32".into(), Some(ExprWrapper::default(Expr::InfixOp(Add,
+            ExprWrapper::default(Expr::UnaryOp(UnaryOp::Negate, int(1))),
+            int(2),
+        )))),
+    ];
+    let mut input_ast = ExprWrapper::default(Expr::Block(vec![
+        ExprWrapper::default(Expr::VarDecl(false, "x".into(), None, int(5))),
+        ExprWrapper::default(Expr::FnDecl("foo".into(), args, false, Some("i32".into()),
+            ExprWrapper::default(Expr::Block(vec![
+                ExprWrapper::default(Expr::Return(Some(ExprWrapper::default(Expr::InfixOp(Add, var("a"), var("b")))))),
+            ])))),
+        ExprWrapper::default(Expr::FnDecl("bar".into(), vec![], false, Some("i32".into()),
+            ExprWrapper::default(Expr::Block(vec![
+                ExprWrapper::default(Expr::VarDecl(false, "x".into(), None, int(7))),
+                ExprWrapper::default(Expr::Return(Some(ExprWrapper::default(Expr::FnCall("foo".into(), vec![]))))),
+            ])))),
+    ]));
+
+    let mut type_checker = TypeChecker::new();
+    type_checker.analyze(&mut input_ast);
+
+    // Only reported where it's declared, rather than again wherever the default is used
+    let kinds: Vec<&SemanticErrorKind> = type_checker.get_errors().iter().map(|error| error.get_kind()).collect();
+    assert_eq!(kinds, vec![&SemanticErrorKind::NonConstantDefault { name: "a".into() }]);
+}
+
+#[test]
+fn test_fn_call_checks() {
+    // var a = twice(1)
+    // var b: str = twice(2)
+    // twice()
+    // twice(1, 2)
+    // twice('c')
+    // print("hi", 1)
+    // nothing(1)
+    // fn twice(n: i32) -> i32,
+    //     return n + n
+    let call = |name: &str, args| ExprWrapper::default(Expr::FnCall(name.into(), args));
+    let int = |value| ExprWrapper::default(Expr::Literal(I32Num(value)));
+
+    let mut input_ast = ExprWrapper::default(Expr::Block(vec![
+        ExprWrapper::default(Expr::VarDecl(false, "a".into(), None, call("twice", vec![int(1)]))),
+        ExprWrapper::default(Expr::VarDecl(false, "b".into(), Some("str".into()), call("twice", vec![int(2)]))),
+        call("twice", vec![]),
+        call("twice", vec![int(1), int(2)]),
+        call("twice", vec![ExprWrapper::default(Expr::Literal(UTF8Char('c')))]),
+        call("print", vec![ExprWrapper::default(Expr::Literal(UTF8String("hi".into()))), int(1)]),
+        call("nothing", vec![int(1)]),
+        ExprWrapper::default(Expr::FnDecl("twice".into(), vec![("n".into(), "i32".into(), None)], false, Some("i32".into()),
+            ExprWrapper::default(Expr::Block(vec![
+                ExprWrapper::default(Expr::Return(Some(ExprWrapper::default(Expr::InfixOp(
+                    Add,
+                    ExprWrapper::default(Expr::Var("n".into())),
+                    ExprWrapper::default(Expr::Var("n".into())),
+                ))))),
+            ])))),
+    ]));
+
+    let mut type_checker = TypeChecker::new();
+    type_checker.analyze(&mut input_ast);
+
+    let kinds: Vec<&SemanticErrorKind> = type_checker.get_errors().iter().map(|error| error.get_kind()).collect();
+    assert_eq!(kinds, vec![
+        &SemanticErrorKind::TypeMismatch { expected: "str".into(), found: "i32".into() },
+        &SemanticErrorKind::ArgumentCountMismatch { name: "twice".into(), expected: 1, found: 0, var_args: false },
+        &SemanticErrorKind::ArgumentCountMismatch { name: "twice".into(), expected: 1, found: 2, var_args: false },
+        &SemanticErrorKind::TypeMismatch { expected: "i32".into(), found: "char".into() },
+        &SemanticErrorKind::ArgumentCountMismatch { name: "print".into(), expected: 1, found: 2, var_args: false },
+        &SemanticErrorKind::UndeclaredFunction { name: "nothing".into() },
+    ]);
+
+    // The call's type is the function's return type
+    if let Expr::Block(ref exprs) = *input_ast.get_expr() {
+        assert_eq!(*exprs[0].get_expr(), Expr::VarDecl(false, "a".into(), Some("i32".into()),
+                                                       call("twice", vec![int(1)])));
+    } else {
+        panic!("Expected a block");
+    }
+}
+
+#[test]
+fn test_fn_redeclaration() {
+    // fn twice(n: i32) -> i32,
+    //     return n + n
+    // fn twice(n: i64) -> i64,
+    //     return n + n
+    // fn print(n: i32),
+    //     ...
+    // twice(1)
+    let twice = |type_: &str| ExprWrapper::default(Expr::FnDecl("twice".into(), vec![("n".into(), type_.into(), None)], false,
+                                                                Some(type_.into()),
+                                                                ExprWrapper::default(Expr::Block(vec![
+                                                                    ExprWrapper::default(Expr::Return(Some(ExprWrapper::default(Expr::InfixOp(
+                                                                        Add,
+                                                                        ExprWrapper::default(Expr::Var("n".into())),
+                                                                        ExprWrapper::default(Expr::Var("n".into())),
+                                                                    ))))),
+                                                                ]))));
+
+    let mut input_ast = ExprWrapper::default(Expr::Block(vec![
+        twice("i32"),
+        twice("i64"),
+        ExprWrapper::default(Expr::FnDecl("print".into(), vec![("n".into(), "i32".into(), None)], false, None,
+            ExprWrapper::default(Expr::Block(vec![])))),
+        ExprWrapper::default(Expr::FnCall("twice".into(), vec![ExprWrapper::default(Expr::Literal(I32Num(1)))])),
+    ]));
+
+    let mut type_checker = TypeChecker::new();
+    type_checker.analyze(&mut input_ast);
+
+    // The first declaration is the one which is called
+    let kinds: Vec<&SemanticErrorKind> = type_checker.get_errors().iter().map(|error| error.get_kind()).collect();
+    assert_eq!(kinds, vec![&SemanticErrorKind::Redeclaration { name: "twice".into(), previous: Span::default() },
+                           &SemanticErrorKind::Redeclaration { name: "print".into(), previous: Span::default() }]);
+}
+
+#[test]
+fn test_fn_missing_return() {
+    // fn only_if() -> i32,
+    //     if True,
+    //         return 1
+    // fn both_branches() -> i32,
+    //     if True,
+    //         return 1
+    //     else,
+    //         return 2
+    let return_one = || ExprWrapper::default(Expr::Return(Some(ExprWrapper::default(Expr::Literal(I32Num(1))))));
+    let return_two = || ExprWrapper::default(Expr::Return(Some(ExprWrapper::default(Expr::Literal(I32Num(2))))));
+    let function = |name: &str, opt_else: Option<ExprWrapper>| {
+        ExprWrapper::default(Expr::FnDecl(name.into(), vec![], false, Some("i32".into()),
+                                          ExprWrapper::default(Expr::Block(vec![
+                                              ExprWrapper::default(Expr::If(
+                                                  ExprWrapper::default(Expr::Literal(Bool(true))),
+                                                  ExprWrapper::default(Expr::Block(vec![return_one()])),
+                                                  opt_else,
+                                              )),
+                                          ]))))
+    };
+
+    let mut input_ast = ExprWrapper::default(Expr::Block(vec![
+        function("only_if", None),
+        function("both_branches", Some(ExprWrapper::default(Expr::Block(vec![return_two()])))),
+    ]));
+
+    let mut type_checker = TypeChecker::new();
+    type_checker.analyze(&mut input_ast);
+
+    let kinds: Vec<&SemanticErrorKind> = type_checker.get_errors().iter().map(|error| error.get_kind()).collect();
+    assert_eq!(kinds, vec![&SemanticErrorKind::ReturnTypeMismatch { expected: Some("i32".into()), found: None }]);
+}
+
+#[test]
+fn test_fn_while_true_return() {
+    // fn forever() -> i32,
+    //     while True,
+    //         if False,
+    //             return 1
+    // fn breaking() -> i32,
+    //     while True,
+    //         if False,
+    //             break
+    // fn nested() -> i32,
+    //     while True,
+    //         while True,
+    //             break
+    let while_true = |body| ExprWrapper::default(Expr::WhileLoop(ExprWrapper::default(Expr::Literal(Bool(true))),
+                                                                 ExprWrapper::default(Expr::Block(vec![body]))));
+    let if_false = |body| ExprWrapper::default(Expr::If(ExprWrapper::default(Expr::Literal(Bool(false))),
+                                                        ExprWrapper::default(Expr::Block(vec![body])), None));
+    let function = |name: &str, body| ExprWrapper::default(Expr::FnDecl(name.into(), vec![], false, Some("i32".into()),
+                                                                        ExprWrapper::default(Expr::Block(vec![body]))));
+
+    let mut input_ast = ExprWrapper::default(Expr::Block(vec![
+        function("forever", while_true(if_false(ExprWrapper::default(Expr::Return(Some(ExprWrapper::default(Expr::Literal(I32Num(1))))))))),
+        function("breaking", while_true(if_false(ExprWrapper::default(Expr::Break)))),
+        function("nested", while_true(while_true(ExprWrapper::default(Expr::Break)))),
+    ]));
+
+    let mut type_checker = TypeChecker::new();
+    type_checker.analyze(&mut input_ast);
+
+    // Only `breaking` can leave its loop and reach the end of the function
+    let kinds: Vec<&SemanticErrorKind> = type_checker.get_errors().iter().map(|error| error.get_kind()).collect();
+    assert_eq!(kinds, vec![&SemanticErrorKind::ReturnTypeMismatch { expected: Some("i32".into()), found: None }]);
+}
+
+#[test]
+fn test_classes() {
+    // class Shape
+    //     var sides: i32 = 0
+    //     fn area() -> i32
+    //         return self.sides
+    // class Square from Shape
+    //     def width: i32 = 1
+    //     fn area() -> i32
+    //         return self.width
+    // var shape: Shape = Square(4)
+    // var area = shape.area()
+    // shape.width
+    // shape.area(1)
+    // area.sides
+    // var square: Square = Shape()
+    // class Circle from Oval
+    let var = |name: &str| ExprWrapper::default(Expr::Var(name.into()));
+    let int = |value| ExprWrapper::default(Expr::Literal(I32Num(value)));
+    let decl = |name: &str, type_: Option<&str>, value| ExprWrapper::default(Expr::VarDecl(false, name.into(), type_.map(|type_| type_.into()), value));
+    let area = |class: &str, field: &str| ExprWrapper::default(Expr::FnDecl(format!("{}.area", class), vec![("self".into(), class.into(), None)],
+                                                                            false, Some("i32".into()),
+                                                                            ExprWrapper::default(Expr::Block(vec![
+                                                                                ExprWrapper::default(Expr::Return(Some(var(&format!("self.{}", field))))),
+                                                                            ]))));
+    let call = |name: &str, args| ExprWrapper::default(Expr::FnCall(name.into(), args));
+
+    let mut input_ast = ExprWrapper::default(Expr::Block(vec![
+        ExprWrapper::default(Expr::ClassDecl("Shape".into(), None, vec![decl("sides", Some("i32"), int(0))], vec![area("Shape", "sides")])),
+        ExprWrapper::default(Expr::ClassDecl("Square".into(), Some("Shape".into()),
+                                             vec![ExprWrapper::default(Expr::VarDecl(true, "width".into(), Some("i32".into()), int(1)))],
+                                             vec![area("Square", "width")])),
+        decl("shape", Some("Shape"), call("Square", vec![int(4)])),
+        decl("area", None, call("shape.area", vec![])),
+        var("shape.width"),
+        call("shape.area", vec![int(1)]),
+        var("area.sides"),
+        decl("square", Some("Square"), call("Shape", vec![])),
+        ExprWrapper::default(Expr::ClassDecl("Circle".into(), Some("Oval".into()), vec![], vec![])),
+    ]));
+
+    let mut type_checker = TypeChecker::new();
+    type_checker.analyze(&mut input_ast);
+
+    let kinds: Vec<&SemanticErrorKind> = type_checker.get_errors().iter().map(|error| error.get_kind()).collect();
+    assert_eq!(kinds, vec![
+        &SemanticErrorKind::UndeclaredClass { name: "Oval".into() },
+        &SemanticErrorKind::UnknownMember { class: "Shape".into(), member: "width".into() },
+        &SemanticErrorKind::ArgumentCountMismatch { name: "Shape.area".into(), expected: 0, found: 1, var_args: false },
+        &SemanticErrorKind::NotAnObject { found: "i32".into() },
+        &SemanticErrorKind::TypeMismatch { expected: "Square".into(), found: "Shape".into() },
+    ]);
+
+    // Calling a class constructs an object, with defaults for any fields left out, and
+    // methods are called on whichever class the object is known as
+    if let Expr::Block(ref exprs) = *input_ast.get_expr() {
+        assert_eq!(*exprs[2].get_expr(), Expr::VarDecl(false, "shape".into(), Some("Shape".into()),
+                                                       ExprWrapper::default(Expr::Construct("Square".into(), vec![int(4), int(1)]))));
+        assert_eq!(*exprs[3].get_expr(), Expr::VarDecl(false, "area".into(), Some("i32".into()),
+                                                       ExprWrapper::default(Expr::MethodCall(var("shape"), "area".into(), vec![],
+                                                                                             Some("Shape".into())))));
+    } else {
+        panic!("Expected a block");
+    }
+}
+
+#[test]
+fn test_class_field_defaults_are_constant() {
+    // var start = 3
+    // class Counter
+    //     var count: i32 = start
+    //     var step: i32 = 1
+    // fn make() -> Counter,
+    //     var start = 4
+    //     return Counter()
+    let int = |value| ExprWrapper::default(Expr::Literal(I32Num(value)));
+    let field = |name: &str, value| ExprWrapper::default(Expr::VarDecl(false, name.into(), Some("i32".into()), value));
+
+    let mut input_ast = ExprWrapper::default(Expr::Block(vec![
+        ExprWrapper::default(Expr::VarDecl(false, "start".into(), None, int(3))),
+        ExprWrapper::default(Expr::ClassDecl("Counter".into(), None, vec![
+            field("count", ExprWrapper::default(Expr::Var("start".into()))),
+            field("step", int(1)),
+        ], vec![])),
+        ExprWrapper::default(Expr::FnDecl("make".into(), vec![], false, Some("Counter".into()),
+            ExprWrapper::default(Expr::Block(vec![
+                ExprWrapper::default(Expr::VarDecl(false, "start".into(), None, int(4))),
+                ExprWrapper::default(Expr::Return(Some(ExprWrapper::default(Expr::FnCall("Counter".into(), vec![]))))),
+            ])))),
+    ]));
+
+    let mut type_checker = TypeChecker::new();
+    type_checker.analyze(&mut input_ast);
+
+    // Field defaults are passed to the constructor, so they're held to the same rule as param defaults
+    let kinds: Vec<&SemanticErrorKind> = type_checker.get_errors().iter().map(|error| error.get_kind()).collect();
+    assert_eq!(kinds, vec![&SemanticErrorKind::NonConstantDefault { name: "count".into() }]);
+}
+
+#[test]
+fn test_when() {
+    // when,
+    //     raise KeyError
+    // throws KeyError as e,
+    //     var message = e
+    // else,
+    //     e
+    let var = |name: &str| ExprWrapper::default(Expr::Var(name.into()));
+    let block = |exprwrapper: ExprWrapper| ExprWrapper::default(Expr::Block(vec![exprwrapper]));
+
+    let mut input_ast = ExprWrapper::default(Expr::Block(vec![
+        ExprWrapper::default(Expr::When(
+            block(ExprWrapper::default(Expr::Raise("KeyError".into(), None))),
+            vec![(vec!["KeyError".into()], Some("e".into()),
+                  block(ExprWrapper::default(Expr::VarDecl(false, "message".into(), None, var("e")))))],
+            Some(block(var("e"))),
+        )),
+    ]));
+
+    let mut type_checker = TypeChecker::new();
+    type_checker.analyze(&mut input_ast);
+
+    // The error is only bound inside its handler, as a string
+    let kinds: Vec<&SemanticErrorKind> = type_checker.get_errors().iter().map(|error| error.get_kind()).collect();
+    assert_eq!(kinds, vec![&SemanticErrorKind::UndeclaredVariable { name: "e".into() }]);
+}
+
+#[test]
+fn test_error_types() {
+    // when,
+    //     twice(1)
+    //     one()
+    // throws TooBig or TooBgi,
+    //     pass
+    // raise Oops
+    // class TooBig
+    //     pass
+    // fn check(n: i32) -> i32,
+    //     raise TooBig
+    // fn twice(n: i32) -> i32,
+    //     return check(n)
+    // fn one() -> i32,
+    //     return 1
+    let call = |name: &str, args| ExprWrapper::default(Expr::FnCall(name.into(), args));
+    let function = |name: &str, args, body| ExprWrapper::default(Expr::FnDecl(name.into(), args, false, Some("i32".into()),
+                                                                              ExprWrapper::default(Expr::Block(vec![body]))));
+    let n = || vec![("n".to_string(), "i32".to_string(), None)];
+
+    let mut input_ast = ExprWrapper::default(Expr::Block(vec![
+        ExprWrapper::default(Expr::When(
+            ExprWrapper::default(Expr::Block(vec![
+                call("twice", vec![ExprWrapper::default(Expr::Literal(I32Num(1)))]),
+                call("one", vec![]),
+            ])),
+            vec![(vec!["TooBig".into(), "TooBgi".into()], None, ExprWrapper::default(Expr::Block(vec![ExprWrapper::default(Expr::NoOp)])))],
+            None,
+        )),
+        ExprWrapper::default(Expr::Raise("Oops".into(), None)),
+        ExprWrapper::default(Expr::ClassDecl("TooBig".into(), None, vec![], vec![])),
+        function("check", n(), ExprWrapper::default(Expr::Raise("TooBig".into(), None))),
+        function("twice", n(), ExprWrapper::default(Expr::Return(Some(call("check", vec![ExprWrapper::default(Expr::Var("n".into()))]))))),
+        function("one", vec![], ExprWrapper::default(Expr::Return(Some(ExprWrapper::default(Expr::Literal(I32Num(1))))))),
+    ]));
+
+    let mut type_checker = TypeChecker::new();
+    type_checker.analyze(&mut input_ast);
+
+    let kinds: Vec<&SemanticErrorKind> = type_checker.get_errors().iter().map(|error| error.get_kind()).collect();
+    assert_eq!(kinds, vec![&SemanticErrorKind::UnknownErrorType { name: "TooBgi".into() },
+                           &SemanticErrorKind::UnknownErrorType { name: "Oops".into() }]);
+
+    // A call can raise if the function raises, or calls one which does, even if it's declared later
+    if let Expr::Block(ref exprs) = *input_ast.get_expr() {
+        if let Expr::When(ref body, ..) = *exprs[0].get_expr() {
+            if let Expr::Block(ref calls) = *body.get_expr() {
+                assert!(calls[0].can_raise());
+                assert!(!calls[1].can_raise());
+            }
+        }
+    }
 }
@@ -1,7 +1,9 @@
 extern crate limonite;
 
+pub mod test_diagnostics;
 pub mod test_lexer;
 #[cfg(feature="llvm-backend")]
 pub mod test_llvm_codegen;
+pub mod test_modules;
 pub mod test_parser;
 pub mod test_type_checker;